@@ -18,6 +18,14 @@ pub mod cache_me;
 #[cfg(feature = "trace_adaptor")]
 pub mod trace;
 
+/// [`TraceSpans`] bot adaptor which opens a [`tracing`] span for every
+/// request.
+///
+/// [`TraceSpans`]: trace_spans::TraceSpans
+/// [`tracing`]: ::tracing
+#[cfg(feature = "trace_spans")]
+pub mod trace_spans;
+
 /// [`ErasedRequester`] bot adaptor which allows to erase type of
 /// [`Requester`].
 ///
@@ -33,15 +41,96 @@ pub mod erased;
 #[cfg(feature = "throttle")]
 pub mod throttle;
 
+/// [`Retry`] bot adaptor which retries failed requests.
+///
+/// [`Retry`]: retry::Retry
+#[cfg(feature = "retry")]
+pub mod retry;
+
+/// [`FollowMigrations`] bot adaptor which follows group to supergroup
+/// migrations.
+///
+/// [`FollowMigrations`]: follow_migrations::FollowMigrations
+#[cfg(feature = "follow_migrations")]
+pub mod follow_migrations;
+
+/// [`Metrics`] bot adaptor which collects statistics of requests.
+///
+/// [`Metrics`]: metrics::Metrics
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+/// [`Record`] bot adaptor which records requests and responses, and
+/// [`Replay`] which plays them back.
+///
+/// [`Record`]: record::Record
+/// [`Replay`]: record::Replay
+#[cfg(feature = "record_replay")]
+pub mod record;
+
+/// [`FileCache`] bot adaptor which reuses `file_id`s of already uploaded
+/// files.
+///
+/// [`FileCache`]: file_cache::FileCache
+#[cfg(feature = "file_cache")]
+pub mod file_cache;
+
+/// [`TtlCache`] bot adaptor which caches responses of read-only methods.
+///
+/// [`TtlCache`]: ttl_cache::TtlCache
+#[cfg(feature = "ttl_cache")]
+pub mod ttl_cache;
+
+/// [`Coalesce`] bot adaptor which sends identical concurrent requests only
+/// once.
+///
+/// [`Coalesce`]: coalesce::Coalesce
+#[cfg(feature = "coalesce")]
+pub mod coalesce;
+
+/// [`CircuitBreaker`] bot adaptor which fails requests fast while Telegram is
+/// unavailable.
+///
+/// [`CircuitBreaker`]: circuit_breaker::CircuitBreaker
+#[cfg(feature = "circuit_breaker")]
+pub mod circuit_breaker;
+
+/// [`Timeout`] bot adaptor which limits the time requests can take and
+/// allows to cancel them.
+///
+/// [`Timeout`]: timeout::Timeout
+#[cfg(feature = "timeout")]
+pub mod timeout;
+
 mod parse_mode;
 
 #[cfg(feature = "cache_me")]
 pub use cache_me::CacheMe;
+#[cfg(feature = "circuit_breaker")]
+pub use circuit_breaker::CircuitBreaker;
+#[cfg(feature = "coalesce")]
+pub use coalesce::Coalesce;
 #[cfg(feature = "erased")]
 pub use erased::ErasedRequester;
+#[cfg(feature = "file_cache")]
+pub use file_cache::FileCache;
+#[cfg(feature = "follow_migrations")]
+pub use follow_migrations::FollowMigrations;
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+#[cfg(feature = "record_replay")]
+pub use record::{Record, Replay};
+#[cfg(feature = "retry")]
+pub use retry::Retry;
 #[cfg(feature = "throttle")]
 pub use throttle::Throttle;
+#[cfg(feature = "timeout")]
+pub use timeout::Timeout;
 #[cfg(feature = "trace_adaptor")]
 pub use trace::Trace;
+#[cfg(feature = "trace_spans")]
+pub use trace_spans::TraceSpans;
+#[cfg(feature = "ttl_cache")]
+pub use ttl_cache::TtlCache;
 
 pub use parse_mode::DefaultParseMode;
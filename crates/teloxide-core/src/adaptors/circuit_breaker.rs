@@ -0,0 +1,737 @@
+use std::{
+    fmt,
+    future::{Future, IntoFuture},
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::ready;
+use url::Url;
+
+use crate::{
+    errors::RequestError,
+    requests::{HasPayload, Output, Request, Requester},
+    types::*,
+};
+
+// Required to not trigger `clippy::type-complexity` lint
+type IsFailure = Arc<dyn Fn(&RequestError) -> bool + Send + Sync>;
+type OnStateChange = Arc<dyn Fn(State, State) + Send + Sync>;
+
+/// Fails requests fast while Telegram is unavailable.
+///
+/// After [`Settings::failure_threshold`] consecutive failures (by default
+/// network errors, timeouts and server errors, see [`Settings::is_failure`])
+/// the circuit breaker _opens_: requests are not sent and immediately fail with
+/// [`RequestError::CircuitOpen`].
+///
+/// After [`Settings::open_for`] the circuit breaker becomes _half-open_ and
+/// lets [`Settings::half_open_requests`] requests through to probe whether
+/// Telegram is available again. If a probe succeeds, the circuit breaker
+/// _closes_ and all requests are sent as usual, otherwise it opens again.
+///
+/// The state is shared between clones of the adaptor. State changes can be
+/// observed with [`Settings::on_state_change`], to, for example, pause
+/// handling of updates while the circuit breaker is open.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use teloxide_core::{
+///     adaptors::circuit_breaker::{Settings, State},
+///     prelude::*,
+/// };
+///
+/// # async {
+/// let bot = Bot::from_env().circuit_breaker(
+///     Settings::default()
+///         .failure_threshold(10)
+///         .open_for(Duration::from_secs(60))
+///         .on_state_change(|from, to| log::warn!("Circuit breaker: {from:?} -> {to:?}")),
+/// );
+///
+/// if bot.state() == State::Open {
+///     // Don't even try
+/// }
+///
+/// let me = bot.get_me().await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker<B> {
+    inner: B,
+    breaker: Arc<Breaker>,
+}
+
+/// Settings of the [`CircuitBreaker`] adaptor.
+#[must_use]
+#[non_exhaustive]
+pub struct Settings {
+    /// Number of consecutive failures after which the circuit breaker opens.
+    pub failure_threshold: u32,
+
+    /// Time for which the circuit breaker stays open before it lets probe
+    /// requests through.
+    pub open_for: Duration,
+
+    /// Maximum number of probe requests sent concurrently while the circuit
+    /// breaker is half-open.
+    pub half_open_requests: u32,
+
+    /// Returns `true` if an error means that Telegram is unavailable.
+    ///
+    /// Requests which failed with other errors (e.g. [`ApiError::BotBlocked`])
+    /// are considered successful, since Telegram did handle them.
+    ///
+    /// [`ApiError::BotBlocked`]: crate::ApiError::BotBlocked
+    pub is_failure: IsFailure,
+
+    /// Functions called with the old and the new state, when the state
+    /// changes.
+    pub on_state_change: Vec<OnStateChange>,
+}
+
+/// State of a [`CircuitBreaker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    /// Requests are sent as usual.
+    Closed,
+
+    /// Requests fail with [`RequestError::CircuitOpen`] without being sent.
+    Open,
+
+    /// Some requests are sent to check whether Telegram is available again,
+    /// others fail with [`RequestError::CircuitOpen`].
+    HalfOpen,
+}
+
+impl<B> CircuitBreaker<B> {
+    /// Creates new circuit breaker adaptor.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::circuit_breaker`]
+    /// instead.
+    ///
+    /// [`RequesterExt::circuit_breaker`]: crate::requests::RequesterExt::circuit_breaker
+    pub fn new(inner: B, settings: Settings) -> Self {
+        let breaker = Breaker {
+            settings,
+            inner: Mutex::new(Inner {
+                state: State::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+                probes: 0,
+            }),
+        };
+        Self { inner, breaker: Arc::new(breaker) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the current state of the circuit breaker.
+    ///
+    /// Note that an open circuit breaker becomes half-open only when a request
+    /// is sent after [`Settings::open_for`].
+    pub fn state(&self) -> State {
+        self.breaker.inner.lock().unwrap().state
+    }
+}
+
+impl Settings {
+    pub fn failure_threshold(mut self, val: u32) -> Self {
+        self.failure_threshold = val;
+        self
+    }
+
+    pub fn open_for(mut self, val: Duration) -> Self {
+        self.open_for = val;
+        self
+    }
+
+    pub fn half_open_requests(mut self, val: u32) -> Self {
+        self.half_open_requests = val;
+        self
+    }
+
+    /// Sets a function which returns `true` if an error means that Telegram
+    /// is unavailable.
+    pub fn is_failure<F>(mut self, val: F) -> Self
+    where
+        F: Fn(&RequestError) -> bool + Send + Sync + 'static,
+    {
+        self.is_failure = Arc::new(val);
+        self
+    }
+
+    /// Adds a function, which is called with the old and the new state, when
+    /// the state changes.
+    ///
+    /// The function is called from the task which sent the request that
+    /// caused the change, so it should not block.
+    pub fn on_state_change<F>(mut self, val: F) -> Self
+    where
+        F: Fn(State, State) + Send + Sync + 'static,
+    {
+        self.on_state_change.push(Arc::new(val));
+        self
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            open_for: Duration::from_secs(30),
+            half_open_requests: 1,
+            is_failure: Arc::new(is_outage),
+            on_state_change: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("failure_threshold", &self.failure_threshold)
+            .field("open_for", &self.open_for)
+            .field("half_open_requests", &self.half_open_requests)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Default [`Settings::is_failure`], returns `true` for network errors,
+/// timeouts, server errors (`5xx` error codes) and responses which are not
+/// responses of the Bot API (these are usually error pages of proxies).
+fn is_outage(error: &RequestError) -> bool {
+    match error {
+        RequestError::Network(_) | RequestError::TimedOut => true,
+        // Responses of the Bot API which `teloxide` failed to parse are not outages
+        RequestError::InvalidJson { raw, .. } => !is_api_response(raw),
+        RequestError::Api(_) => error.error_code().is_some_and(|code| code >= 500),
+        _ => false,
+    }
+}
+
+/// Returns `true` if `raw` is a JSON object with an `ok` field, like all
+/// responses of the Bot API.
+fn is_api_response(raw: &str) -> bool {
+    serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(raw)
+        .is_ok_and(|response| response.contains_key("ok"))
+}
+
+#[derive(Debug)]
+struct Breaker {
+    settings: Settings,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: State,
+    // Consecutive failures, while closed
+    failures: u32,
+    opened_at: Instant,
+    // Probe requests in flight, while half-open
+    probes: u32,
+}
+
+/// Permission to send a request, given by [`Breaker::acquire`].
+struct Permit {
+    breaker: Arc<Breaker>,
+    probe: bool,
+}
+
+impl Breaker {
+    fn acquire(self: &Arc<Self>) -> Option<Permit> {
+        let mut inner = self.inner.lock().unwrap();
+
+        let probe = match inner.state {
+            State::Closed => false,
+            State::Open if inner.opened_at.elapsed() < self.settings.open_for => return None,
+            State::HalfOpen if inner.probes >= self.settings.half_open_requests => return None,
+            State::Open | State::HalfOpen => true,
+        };
+
+        let change = if probe {
+            inner.probes += 1;
+            inner.set_state(State::HalfOpen)
+        } else {
+            None
+        };
+        drop(inner);
+
+        self.notify(change);
+        Some(Permit { breaker: Arc::clone(self), probe })
+    }
+
+    fn notify(&self, change: Option<(State, State)>) {
+        if let Some((from, to)) = change {
+            for f in &self.settings.on_state_change {
+                f(from, to);
+            }
+        }
+    }
+}
+
+impl Inner {
+    /// Sets the state, returns the old and the new state if they differ.
+    fn set_state(&mut self, state: State) -> Option<(State, State)> {
+        let old = mem::replace(&mut self.state, state);
+        (old != state).then_some((old, state))
+    }
+}
+
+impl Permit {
+    fn complete(self, failed: bool) {
+        let breaker = Arc::clone(&self.breaker);
+        let probe = self.probe;
+        // Probes are released by `drop`
+        drop(self);
+
+        let mut inner = breaker.inner.lock().unwrap();
+        let change = match (inner.state, failed) {
+            (State::Closed, false) => {
+                inner.failures = 0;
+                None
+            }
+            (State::Closed, true) => {
+                inner.failures += 1;
+                if inner.failures >= breaker.settings.failure_threshold {
+                    inner.opened_at = Instant::now();
+                    inner.set_state(State::Open)
+                } else {
+                    None
+                }
+            }
+            (State::HalfOpen, false) if probe => {
+                inner.failures = 0;
+                inner.set_state(State::Closed)
+            }
+            (State::HalfOpen, true) if probe => {
+                inner.opened_at = Instant::now();
+                inner.set_state(State::Open)
+            }
+            // Results of requests sent before the last state change
+            _ => None,
+        };
+        drop(inner);
+
+        breaker.notify(change);
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if self.probe {
+            let mut inner = self.breaker.inner.lock().unwrap();
+            inner.probes = inner.probes.saturating_sub(1);
+        }
+    }
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        CircuitBreakerRequest<B::$T>
+    };
+}
+
+macro_rules! fwd_inner {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        CircuitBreakerRequest {
+            inner: $this.inner().$m($($arg),*),
+            breaker: Arc::clone(&$this.breaker),
+        }
+    };
+}
+
+impl<B> Requester for CircuitBreaker<B>
+where
+    B: Requester<Err = RequestError>,
+{
+    type Err = RequestError;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fwd_inner, fty
+    }
+}
+
+download_forward! {
+    B
+    CircuitBreaker<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`CircuitBreaker`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct CircuitBreakerRequest<R> {
+    inner: R,
+    breaker: Arc<Breaker>,
+}
+
+impl<R> HasPayload for CircuitBreakerRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for CircuitBreakerRequest<R>
+where
+    R: Request<Err = RequestError>,
+{
+    type Err = RequestError;
+
+    type Send = CircuitBreakerSend<R::Send>;
+
+    type SendRef = CircuitBreakerSend<R::SendRef>;
+
+    fn send(self) -> Self::Send {
+        CircuitBreakerSend { breaker: self.breaker, permit: None, inner: self.inner.send() }
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        CircuitBreakerSend {
+            breaker: Arc::clone(&self.breaker),
+            permit: None,
+            inner: self.inner.send_ref(),
+        }
+    }
+}
+
+impl<R> IntoFuture for CircuitBreakerRequest<R>
+where
+    R: Request<Err = RequestError>,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`CircuitBreakerRequest`]s.
+#[pin_project::pin_project]
+pub struct CircuitBreakerSend<F> {
+    breaker: Arc<Breaker>,
+    permit: Option<Permit>,
+    #[pin]
+    inner: F,
+}
+
+impl<F, T> Future for CircuitBreakerSend<F>
+where
+    F: Future<Output = Result<T, RequestError>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        // The permit is acquired on the first poll, so that requests which
+        // were created, but not yet sent, don't count as probes
+        if this.permit.is_none() {
+            match this.breaker.acquire() {
+                Some(permit) => *this.permit = Some(permit),
+                None => return Poll::Ready(Err(RequestError::CircuitOpen)),
+            }
+        }
+
+        let ret = ready!(this.inner.poll(cx));
+        if let Some(permit) = this.permit.take() {
+            let failed =
+                ret.as_ref().err().is_some_and(|err| (this.breaker.settings.is_failure)(err));
+            permit.complete(failed);
+        }
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::{
+        adaptors::circuit_breaker::{CircuitBreaker, Settings, State},
+        errors::{ApiError, ErrorResponse, RequestError},
+        payloads::GetMe,
+        prelude::*,
+        testing::MockBot,
+    };
+
+    #[tokio::test]
+    async fn opens_and_recovers() {
+        let inner = MockBot::new();
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let settings = Settings::default()
+            .failure_threshold(2)
+            .open_for(Duration::from_millis(100))
+            .on_state_change({
+                let changes = Arc::clone(&changes);
+                move |from, to| changes.lock().unwrap().push((from, to))
+            });
+        let bot = CircuitBreaker::new(inner.clone(), settings);
+
+        let response = ErrorResponse {
+            error_code: Some(502),
+            description: "Bad Gateway".to_owned(),
+            parameters: None,
+        };
+        let outage = RequestError::from(ApiError::Unknown(response));
+        inner.respond_error::<GetMe>(outage.clone());
+        inner.respond_error::<GetMe>(RequestError::from(ApiError::BotBlocked));
+        inner.respond_error::<GetMe>(outage.clone());
+        inner.respond_error::<GetMe>(RequestError::TimedOut);
+
+        // Errors which are not failures reset the counter
+        for _ in 0..3 {
+            bot.get_me().await.unwrap_err();
+        }
+        assert_eq!(bot.state(), State::Closed);
+
+        bot.get_me().await.unwrap_err();
+        assert_eq!(bot.state(), State::Open);
+        assert!(matches!(bot.get_me().await, Err(RequestError::CircuitOpen)));
+        assert_eq!(inner.requests().len(), 4);
+
+        // A failed probe opens the circuit breaker again
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        inner.respond_error::<GetMe>(outage);
+        bot.get_me().await.unwrap_err();
+        assert_eq!(bot.state(), State::Open);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        bot.get_me().await.unwrap();
+        assert_eq!(bot.state(), State::Closed);
+        assert_eq!(inner.requests().len(), 6);
+
+        use State::*;
+        assert_eq!(
+            *changes.lock().unwrap(),
+            [
+                (Closed, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Open),
+                (Open, HalfOpen),
+                (HalfOpen, Closed)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_error_pages_are_failures() {
+        let inner = MockBot::new();
+        let bot = CircuitBreaker::new(inner.clone(), Settings::default().failure_threshold(2));
+
+        let page = invalid_json("<html>502 Bad Gateway</html>");
+        inner.respond_error::<GetMe>(page.clone());
+        // A response of the Bot API which couldn't be parsed resets the counter
+        inner.respond_error::<GetMe>(invalid_json(r#"{"ok":true,"result":{"id":"x"}}"#));
+        inner.respond_error::<GetMe>(page.clone());
+        for _ in 0..3 {
+            bot.get_me().await.unwrap_err();
+        }
+        assert_eq!(bot.state(), State::Closed);
+
+        inner.respond_error::<GetMe>(page);
+        bot.get_me().await.unwrap_err();
+        assert_eq!(bot.state(), State::Open);
+    }
+
+    fn invalid_json(raw: &str) -> RequestError {
+        let source = serde_json::from_str::<u8>(raw).unwrap_err();
+        RequestError::InvalidJson { source: source.into(), raw: raw.into() }
+    }
+}
@@ -0,0 +1,547 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+};
+
+use futures::{
+    future::{BoxFuture, Shared},
+    FutureExt,
+};
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Sends identical concurrent read-only requests only once.
+///
+/// If a request is sent while an identical request (with the same method and
+/// payload) is still waiting for a response, it doesn't make another call to
+/// Telegram, but waits for the response of the first request instead. Both
+/// successful responses and errors are shared with all the waiting requests.
+///
+/// This is useful when many updates are handled at once and their handlers
+/// request the same data, e.g. administrators of a chat. Unlike [`TtlCache`],
+/// this adaptor never returns responses of requests which completed before
+/// the request was sent, so it can't return outdated data.
+///
+/// Only `get_*` methods which don't change anything are coalesced, e.g.
+/// [`get_chat`], [`get_chat_member`], [`get_chat_administrators`] or
+/// [`get_my_commands`]. Other requests are always sent.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{prelude::*, types::ChatId};
+///
+/// # async {
+/// let bot = Bot::from_env().coalesce();
+///
+/// // Only one `getChatAdministrators` request is sent to Telegram
+/// let (a, b) = futures::join!(
+///     bot.get_chat_administrators(ChatId(-1)).send(),
+///     bot.get_chat_administrators(ChatId(-1)).send(),
+/// );
+/// assert_eq!(a?, b?);
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`TtlCache`]: crate::adaptors::TtlCache
+/// [`get_chat`]: crate::requests::Requester::get_chat
+/// [`get_chat_member`]: crate::requests::Requester::get_chat_member
+/// [`get_chat_administrators`]: crate::requests::Requester::get_chat_administrators
+/// [`get_my_commands`]: crate::requests::Requester::get_my_commands
+#[derive(Clone)]
+pub struct Coalesce<B> {
+    inner: B,
+    in_flight: Arc<InFlight>,
+}
+
+// Requests waiting for a response, by method name and payload. Values are
+// `InFlightEntry<R>` of the corresponding request type.
+type InFlight = Mutex<HashMap<String, Box<dyn Any + Send>>>;
+
+type SharedSend<R> = Shared<BoxFuture<'static, Result<Output<R>, <R as Request>::Err>>>;
+
+struct InFlightEntry<R: Request> {
+    shared: SharedSend<R>,
+    waiters: usize,
+}
+
+/// A request waiting for the response of an in-flight request.
+///
+/// The in-flight request is removed when the first waiter gets the response,
+/// or when all waiters are dropped, so that the next identical request is sent
+/// again.
+struct Waiter<R>
+where
+    R: Request + 'static,
+    Output<R>: 'static,
+{
+    in_flight: Arc<InFlight>,
+    key: String,
+    shared: SharedSend<R>,
+    completed: bool,
+}
+
+impl<B> Coalesce<B> {
+    /// Creates new adaptor.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::coalesce`] instead.
+    ///
+    /// [`RequesterExt::coalesce`]: crate::requests::RequesterExt::coalesce
+    pub fn new(inner: B) -> Self {
+        Self { inner, in_flight: <_>::default() }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for Coalesce<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coalesce").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        CoalesceRequest {
+            inner: Arc::new($this.inner().$m($($arg),*)),
+            in_flight: Arc::clone(&$this.in_flight),
+        }
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        CoalesceRequest<B::$T>
+    };
+}
+
+macro_rules! fid {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        $this.inner().$m($($arg),*)
+    };
+}
+
+macro_rules! ftyid {
+    ($T:ident) => {
+        B::$T
+    };
+}
+
+impl<B> Requester for Coalesce<B>
+where
+    B: Requester,
+    B::Err: Clone + Send + Sync + 'static,
+    B::GetMe: Clone + Send + Sync + 'static,
+    B::GetChat: Clone + Send + Sync + 'static,
+    B::GetChatAdministrators: Clone + Send + Sync + 'static,
+    B::GetChatMembersCount: Clone + Send + Sync + 'static,
+    B::GetChatMemberCount: Clone + Send + Sync + 'static,
+    B::GetChatMember: Clone + Send + Sync + 'static,
+    B::GetUserProfilePhotos: Clone + Send + Sync + 'static,
+    B::GetStickerSet: Clone + Send + Sync + 'static,
+    B::GetCustomEmojiStickers: Clone + Send + Sync + 'static,
+    B::GetForumTopicIconStickers: Clone + Send + Sync + 'static,
+    B::GetMyCommands: Clone + Send + Sync + 'static,
+    B::GetMyName: Clone + Send + Sync + 'static,
+    B::GetMyDescription: Clone + Send + Sync + 'static,
+    B::GetMyShortDescription: Clone + Send + Sync + 'static,
+    B::GetChatMenuButton: Clone + Send + Sync + 'static,
+    B::GetMyDefaultAdministratorRights: Clone + Send + Sync + 'static,
+    B::GetUserChatBoosts: Clone + Send + Sync + 'static,
+    B::GetBusinessConnection: Clone + Send + Sync + 'static,
+    B::GetAvailableGifts: Clone + Send + Sync + 'static,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        get_me,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        get_user_profile_photos,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        get_forum_topic_icon_stickers,
+        get_my_commands,
+        get_my_name,
+        get_my_description,
+        get_my_short_description,
+        get_chat_menu_button,
+        get_my_default_administrator_rights,
+        get_user_chat_boosts,
+        get_business_connection,
+        get_available_gifts
+        => f, fty
+    }
+
+    requester_forward! {
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_sticker,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        set_my_commands,
+        set_my_name,
+        set_my_description,
+        set_my_short_description,
+        set_chat_menu_button,
+        set_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fid, ftyid
+    }
+}
+
+download_forward! {
+    B
+    Coalesce<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`Coalesce`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+pub struct CoalesceRequest<R> {
+    inner: Arc<R>,
+    in_flight: Arc<InFlight>,
+}
+
+impl<R> Clone for CoalesceRequest<R> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner), in_flight: Arc::clone(&self.in_flight) }
+    }
+}
+
+impl<R> HasPayload for CoalesceRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    type Payload = R::Payload;
+
+    /// Note that if this request was already sent via `send_ref` and it
+    /// didn't yet complete, this method will clone the underlying request.
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        Arc::make_mut(&mut self.inner).payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for CoalesceRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Payload: Serialize,
+    R::Err: Clone + Send + Sync + 'static,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    type Err = R::Err;
+
+    type Send = CoalesceSend<R>;
+
+    type SendRef = CoalesceSend<R>;
+
+    fn send(self) -> Self::Send {
+        CoalesceSend(send(self).boxed())
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        CoalesceSend(send(self.clone()).boxed())
+    }
+}
+
+impl<R> IntoFuture for CoalesceRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Payload: Serialize,
+    R::Err: Clone + Send + Sync + 'static,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`CoalesceRequest`]s.
+#[pin_project::pin_project]
+pub struct CoalesceSend<R: Request>(#[pin] BoxFuture<'static, Result<Output<R>, R::Err>>);
+
+impl<R: Request> Future for CoalesceSend<R> {
+    type Output = Result<Output<R>, R::Err>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        self.project().0.poll(cx)
+    }
+}
+
+async fn send<R>(request: CoalesceRequest<R>) -> Result<Output<R>, R::Err>
+where
+    R: Request + Send + Sync + 'static,
+    R::Payload: Serialize,
+    R::Err: Clone + Send + Sync + 'static,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    let CoalesceRequest { inner, in_flight } = request;
+
+    let key = match serde_json::to_string(inner.payload_ref()) {
+        Ok(payload) => format!("{}:{payload}", R::Payload::NAME),
+        Err(err) => {
+            log::warn!(
+                "Couldn't serialize a payload of {} to coalesce it: {err}",
+                R::Payload::NAME
+            );
+            return inner.send_ref().await;
+        }
+    };
+
+    let shared = {
+        let mut map = in_flight.lock().unwrap();
+        let existing = map.get_mut(&key).and_then(|entry| entry.downcast_mut::<InFlightEntry<R>>());
+
+        match existing {
+            Some(entry) => {
+                entry.waiters += 1;
+                entry.shared.clone()
+            }
+            None => {
+                let shared: SharedSend<R> = async move { inner.send_ref().await }.boxed().shared();
+                let entry = InFlightEntry::<R> { shared: shared.clone(), waiters: 1 };
+                map.insert(key.clone(), Box::new(entry));
+                shared
+            }
+        }
+    };
+
+    let mut waiter = Waiter::<R> { in_flight, key, shared, completed: false };
+    let res = waiter.shared.clone().await;
+    waiter.completed = true;
+
+    res
+}
+
+impl<R> Drop for Waiter<R>
+where
+    R: Request + 'static,
+    Output<R>: 'static,
+{
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().unwrap();
+
+        // The request may be already removed and replaced by a newer one
+        let Some(entry) = in_flight
+            .get_mut(&self.key)
+            .and_then(|entry| entry.downcast_mut::<InFlightEntry<R>>())
+            .filter(|entry| entry.shared.ptr_eq(&self.shared))
+        else {
+            return;
+        };
+
+        entry.waiters -= 1;
+        if self.completed || entry.waiters == 0 {
+            in_flight.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::future::IntoFuture;
+
+    use futures::future::join_all;
+
+    use crate::{prelude::*, testing::FakeServer};
+
+    #[tokio::test]
+    async fn coalesces_concurrent_requests() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot().coalesce();
+
+        let responses = join_all((0..5).map(|_| bot.get_me().into_future())).await;
+        assert!(responses.iter().all(Result::is_ok));
+        assert_eq!(server.requests().len(), 1);
+
+        // Completed requests are not reused
+        bot.get_me().await.unwrap();
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_are_not_reused() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot().coalesce();
+
+        let mut first = bot.get_me().into_future();
+        let mut second = bot.get_me().into_future();
+        assert!(futures::poll!(&mut first).is_pending());
+        assert!(futures::poll!(&mut second).is_pending());
+        assert_eq!(bot.in_flight.lock().unwrap().len(), 1);
+
+        // The request is kept while someone waits for it
+        drop(first);
+        assert_eq!(bot.in_flight.lock().unwrap().len(), 1);
+        drop(second);
+        assert!(bot.in_flight.lock().unwrap().is_empty());
+
+        bot.get_me().await.unwrap();
+        assert!(bot.in_flight.lock().unwrap().is_empty());
+    }
+}
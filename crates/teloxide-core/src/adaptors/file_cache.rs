@@ -0,0 +1,632 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{Future, IntoFuture},
+    mem,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+};
+
+use futures::{
+    future::{ready, BoxFuture},
+    FutureExt,
+};
+use url::Url;
+
+use crate::{
+    errors::{ApiError, RequestError},
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Reuses `file_id`s of already uploaded files.
+///
+/// Every time a file is uploaded, Telegram returns a `file_id` which can be
+/// used to send the same file again without uploading it. This adaptor hashes
+/// the contents of files sent with [`InputFile::file`] and
+/// [`InputFile::memory`], remembers the `file_id` from the returned
+/// [`Message`] in a [`FileIdStorage`] and replaces later uploads of the same
+/// content with [`InputFile::file_id`].
+///
+/// Files are identified by a key of the form `{kind}:{hash}`, e.g.
+/// `photo:2cf24d…`, where `kind` is the name of the file parameter and `hash`
+/// is the hex-encoded SHA-256 of the file name and contents. The kind is a
+/// part of the key, because a `file_id` can't be used to send a file as a
+/// different type (e.g. a photo as a document).
+///
+/// If Telegram rejects a remembered `file_id`, the file is uploaded again and
+/// the new `file_id` replaces the old one.
+///
+/// Only the main file of [`send_photo`], [`send_audio`], [`send_document`],
+/// [`send_video`], [`send_animation`], [`send_voice`], [`send_video_note`] and
+/// [`send_sticker`] is cached. Files created with [`InputFile::read`] are
+/// always uploaded, since they can't be hashed without consuming them.
+///
+/// Note that files from the filesystem are read twice when they are uploaded:
+/// once to compute the hash and once to upload them.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{
+///     prelude::*,
+///     types::{ChatId, InputFile},
+/// };
+///
+/// # async {
+/// let bot = Bot::from_env().file_cache();
+///
+/// // The first request uploads the logo...
+/// bot.send_photo(ChatId(1), InputFile::file("logo.png")).await?;
+/// // ...the second one only sends its `file_id`
+/// bot.send_photo(ChatId(2), InputFile::file("logo.png")).await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`send_photo`]: crate::requests::Requester::send_photo
+/// [`send_audio`]: crate::requests::Requester::send_audio
+/// [`send_document`]: crate::requests::Requester::send_document
+/// [`send_video`]: crate::requests::Requester::send_video
+/// [`send_animation`]: crate::requests::Requester::send_animation
+/// [`send_voice`]: crate::requests::Requester::send_voice
+/// [`send_video_note`]: crate::requests::Requester::send_video_note
+/// [`send_sticker`]: crate::requests::Requester::send_sticker
+#[derive(Clone)]
+pub struct FileCache<B> {
+    inner: B,
+    storage: Arc<dyn FileIdStorage>,
+}
+
+/// Storage of `file_id`s of uploaded files, used by [`FileCache`].
+///
+/// By default `file_id`s are stored in memory, see [`InMemFileIdStorage`], but
+/// you can implement this trait to persist them, for example in a database.
+/// Note that `file_id`s are specific to a bot, so bots with different tokens
+/// shouldn't share a storage.
+pub trait FileIdStorage: Send + Sync {
+    /// Returns the `file_id` of a file with the given key, if it was uploaded
+    /// before.
+    fn get(&self, key: &str) -> BoxFuture<'_, Option<FileId>>;
+
+    /// Remembers the `file_id` of a file with the given key.
+    fn insert(&self, key: String, file_id: FileId) -> BoxFuture<'_, ()>;
+}
+
+/// A [`FileIdStorage`] which keeps `file_id`s in memory.
+#[derive(Debug, Default)]
+pub struct InMemFileIdStorage {
+    map: Mutex<HashMap<String, FileId>>,
+}
+
+impl<B> FileCache<B> {
+    /// Creates new adaptor which stores `file_id`s in memory.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::file_cache`] instead.
+    ///
+    /// [`RequesterExt::file_cache`]: crate::requests::RequesterExt::file_cache
+    pub fn new(inner: B) -> Self {
+        Self::with_storage(inner, InMemFileIdStorage::new())
+    }
+
+    /// Creates new adaptor which stores `file_id`s in `storage`.
+    pub fn with_storage<S>(inner: B, storage: S) -> Self
+    where
+        S: FileIdStorage + 'static,
+    {
+        Self { inner, storage: Arc::new(storage) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the storage of `file_id`s.
+    pub fn storage(&self) -> &Arc<dyn FileIdStorage> {
+        &self.storage
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for FileCache<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileCache").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl InMemFileIdStorage {
+    /// Creates an empty storage.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FileIdStorage for InMemFileIdStorage {
+    fn get(&self, key: &str) -> BoxFuture<'_, Option<FileId>> {
+        let map = self.map.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        ready(map.get(key).cloned()).boxed()
+    }
+
+    fn insert(&self, key: String, file_id: FileId) -> BoxFuture<'_, ()> {
+        let mut map = self.map.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        map.insert(key, file_id);
+        ready(()).boxed()
+    }
+}
+
+impl<S> FileIdStorage for Arc<S>
+where
+    S: FileIdStorage + ?Sized,
+{
+    fn get(&self, key: &str) -> BoxFuture<'_, Option<FileId>> {
+        S::get(self, key)
+    }
+
+    fn insert(&self, key: String, file_id: FileId) -> BoxFuture<'_, ()> {
+        S::insert(self, key, file_id)
+    }
+}
+
+/// The cached file parameter of a payload.
+struct FileParam<P> {
+    kind: &'static str,
+    get: fn(&P) -> &InputFile,
+    get_mut: fn(&mut P) -> &mut InputFile,
+    sent: fn(&Message) -> Option<&FileMeta>,
+}
+
+impl<P> Clone for FileParam<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for FileParam<P> {}
+
+macro_rules! file_param {
+    (send_photo) => {
+        // Any size can be used to resend the photo, but the largest is the most useful one
+        file_param!(@photo, |m| m.photo().and_then(|sizes| sizes.last()).map(|s| &s.file))
+    };
+    (send_audio) => {
+        file_param!(@audio, |m| m.audio().map(|a| &a.file))
+    };
+    (send_document) => {
+        file_param!(@document, |m| m.document().map(|d| &d.file))
+    };
+    (send_video) => {
+        file_param!(@video, |m| m.video().map(|v| &v.file))
+    };
+    (send_animation) => {
+        file_param!(@animation, |m| m.animation().map(|a| &a.file))
+    };
+    (send_voice) => {
+        file_param!(@voice, |m| m.voice().map(|v| &v.file))
+    };
+    (send_video_note) => {
+        file_param!(@video_note, |m| m.video_note().map(|v| &v.file))
+    };
+    (send_sticker) => {
+        file_param!(@sticker, |m| m.sticker().map(|s| &s.file))
+    };
+    (@$field:ident, $sent:expr) => {
+        FileParam {
+            kind: stringify!($field),
+            get: |p| &p.$field,
+            get_mut: |p| &mut p.$field,
+            sent: $sent,
+        }
+    };
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        FileCacheRequest {
+            inner: Arc::new($this.inner().$m($($arg),*)),
+            param: file_param!($m),
+            storage: Arc::clone(&$this.storage),
+        }
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        FileCacheRequest<B::$T>
+    };
+}
+
+macro_rules! fid {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        $this.inner().$m($($arg),*)
+    };
+}
+
+macro_rules! ftyid {
+    ($T:ident) => {
+        B::$T
+    };
+}
+
+impl<B> Requester for FileCache<B>
+where
+    B: Requester<Err = RequestError>,
+    B::SendPhoto: Clone + Send + Sync + 'static,
+    B::SendAudio: Clone + Send + Sync + 'static,
+    B::SendDocument: Clone + Send + Sync + 'static,
+    B::SendVideo: Clone + Send + Sync + 'static,
+    B::SendAnimation: Clone + Send + Sync + 'static,
+    B::SendVoice: Clone + Send + Sync + 'static,
+    B::SendVideoNote: Clone + Send + Sync + 'static,
+    B::SendSticker: Clone + Send + Sync + 'static,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_sticker
+        => f, fty
+    }
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fid, ftyid
+    }
+}
+
+download_forward! {
+    B
+    FileCache<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`FileCache`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+pub struct FileCacheRequest<R: HasPayload> {
+    inner: Arc<R>,
+    param: FileParam<R::Payload>,
+    storage: Arc<dyn FileIdStorage>,
+}
+
+impl<R: HasPayload> Clone for FileCacheRequest<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            param: self.param,
+            storage: Arc::clone(&self.storage),
+        }
+    }
+}
+
+impl<R> HasPayload for FileCacheRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    type Payload = R::Payload;
+
+    /// Note that if this request was already sent via `send_ref` and it
+    /// didn't yet complete, this method will clone the underlying request.
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        Arc::make_mut(&mut self.inner).payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for FileCacheRequest<R>
+where
+    R: Request<Err = RequestError> + Clone + Send + Sync + 'static,
+    R::Payload: Payload<Output = Message>,
+{
+    type Err = R::Err;
+
+    type Send = FileCacheSend<R>;
+
+    type SendRef = FileCacheSend<R>;
+
+    fn send(self) -> Self::Send {
+        FileCacheSend(send(self).boxed())
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        FileCacheSend(send(self.clone()).boxed())
+    }
+}
+
+impl<R> IntoFuture for FileCacheRequest<R>
+where
+    R: Request<Err = RequestError> + Clone + Send + Sync + 'static,
+    R::Payload: Payload<Output = Message>,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`FileCacheRequest`]s.
+#[pin_project::pin_project]
+pub struct FileCacheSend<R: Request>(#[pin] BoxFuture<'static, Result<Output<R>, R::Err>>);
+
+impl<R: Request> Future for FileCacheSend<R> {
+    type Output = Result<Output<R>, R::Err>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        self.project().0.poll(cx)
+    }
+}
+
+async fn send<R>(mut request: FileCacheRequest<R>) -> Result<Message, RequestError>
+where
+    R: Request<Err = RequestError> + Clone,
+    R::Payload: Payload<Output = Message>,
+{
+    let param = request.param;
+    let key = match (param.get)(request.payload_ref()).content_hash().await {
+        Some(Ok(hash)) => format!("{}:{}", param.kind, hex(&hash)),
+        Some(Err(err)) => {
+            log::warn!("Couldn't hash a file to look up its `file_id`: {err}");
+            return request.inner.send_ref().await;
+        }
+        None => return request.inner.send_ref().await,
+    };
+
+    if let Some(file_id) = request.storage.get(&key).await {
+        let file = InputFile::file_id(file_id);
+        let original = mem::replace((param.get_mut)(request.payload_mut()), file);
+
+        match request.inner.send_ref().await {
+            Err(RequestError::Api(
+                ApiError::WrongFileId | ApiError::WrongFileIdOrUrl | ApiError::FileIdInvalid,
+            )) => {
+                log::debug!("`file_id` of {key} was rejected, uploading the file again");
+                *(param.get_mut)(request.payload_mut()) = original;
+            }
+            res => return res,
+        }
+    }
+
+    let message = request.inner.send_ref().await?;
+    if let Some(file) = (param.sent)(&message) {
+        request.storage.insert(key, file.id.clone()).await;
+    }
+
+    Ok(message)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        payloads::SendDocument,
+        prelude::*,
+        testing::{ChatBuilder, FakeServer},
+        types::{ChatId, InputFile},
+    };
+
+    #[tokio::test]
+    async fn reuses_file_ids() {
+        let server = FakeServer::start().await.unwrap();
+        server.add_chat(ChatBuilder::group(ChatId(-1), "Files").build());
+        let bot = server.bot().file_cache();
+
+        let document = || InputFile::memory("logo").file_name("logo.pdf");
+        let first = bot.send_document(ChatId(-1), document()).await.unwrap();
+        let file_id = first.document().unwrap().file.id.clone();
+
+        bot.send_document(ChatId(-1), document()).await.unwrap();
+        // Different content is uploaded
+        bot.send_document(ChatId(-1), InputFile::memory("other").file_name("logo.pdf"))
+            .await
+            .unwrap();
+
+        // A rejected `file_id` is replaced by a new one
+        server.respond_raw::<SendDocument>(
+            400,
+            json!({
+                "ok": false,
+                "error_code": 400,
+                "description": "Bad Request: wrong file identifier/HTTP URL specified",
+            }),
+        );
+        bot.send_document(ChatId(-1), document()).await.unwrap();
+        bot.send_document(ChatId(-1), document()).await.unwrap();
+
+        let documents = server
+            .requests()
+            .iter()
+            .map(|r| r.payload["document"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert!(documents[0].starts_with("attach://"));
+        assert_eq!(documents[1], file_id.0);
+        assert!(documents[2].starts_with("attach://"));
+        assert_eq!(documents[3], file_id.0);
+        assert!(documents[4].starts_with("attach://"));
+        assert!(documents[5].starts_with("fake-file-"));
+        assert_ne!(documents[5], file_id.0);
+    }
+}
@@ -0,0 +1,756 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+};
+
+use futures::{
+    future::{ready, BoxFuture},
+    FutureExt,
+};
+use url::Url;
+
+use crate::{
+    errors::AsResponseParameters,
+    requests::{HasPayload, Output, Request, Requester},
+    types::*,
+};
+
+// Required to not trigger `clippy::type-complexity` lint
+type OnMigrate = Arc<dyn Fn(ChatId, ChatId) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Follows group to supergroup migrations.
+///
+/// When a group is upgraded to a supergroup, it gets a new id and all requests
+/// to the old id fail with [`RequestError::MigrateToChatId`]. This adaptor
+/// catches such errors, remembers the new id in a [`MigrationStorage`] and
+/// re-sends the request to the new chat. Later requests to the old id are sent
+/// to the new chat right away.
+///
+/// Use [`FollowMigrations::on_migrate`] to migrate your own data, for example
+/// to move dialogues from the old chat id to the new one.
+///
+/// Only requests with a `chat_id` parameter are affected. Known migrations
+/// are also applied to `from_chat_id` of forwarded and copied messages, but
+/// since the error doesn't say which chat was migrated, new migrations are not
+/// followed if `chat_id` and `from_chat_id` are different chats.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{adaptors::FollowMigrations, prelude::*, types::ChatId};
+///
+/// # async {
+/// let bot = Bot::from_env().follow_migrations().on_migrate(|old, new| async move {
+///     log::info!("Chat {old} was migrated to {new}");
+/// });
+///
+/// // If the group was migrated, this sends the message to the supergroup
+/// bot.send_message(ChatId(-1234), "Hi!").await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`RequestError::MigrateToChatId`]: crate::RequestError::MigrateToChatId
+#[derive(Clone)]
+pub struct FollowMigrations<B> {
+    inner: B,
+    storage: Arc<dyn MigrationStorage>,
+    on_migrate: Option<OnMigrate>,
+}
+
+/// Storage of chat migrations, used by [`FollowMigrations`].
+///
+/// By default migrations are stored in memory, see [`InMemMigrationStorage`],
+/// but you can implement this trait to persist them, for example in a
+/// database.
+pub trait MigrationStorage: Send + Sync {
+    /// Returns the new id of the chat with id `old`, if it was migrated.
+    fn get(&self, old: ChatId) -> BoxFuture<'_, Option<ChatId>>;
+
+    /// Remembers that the chat with id `old` was migrated to `new`.
+    fn insert(&self, old: ChatId, new: ChatId) -> BoxFuture<'_, ()>;
+}
+
+/// A [`MigrationStorage`] which keeps migrations in memory.
+#[derive(Debug, Default)]
+pub struct InMemMigrationStorage {
+    map: Mutex<HashMap<ChatId, ChatId>>,
+}
+
+impl<B> FollowMigrations<B> {
+    /// Creates new adaptor which stores migrations in memory.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::follow_migrations`]
+    /// instead.
+    ///
+    /// [`RequesterExt::follow_migrations`]: crate::requests::RequesterExt::follow_migrations
+    pub fn new(inner: B) -> Self {
+        Self::with_storage(inner, InMemMigrationStorage::new())
+    }
+
+    /// Creates new adaptor which stores migrations in `storage`.
+    pub fn with_storage<S>(inner: B, storage: S) -> Self
+    where
+        S: MigrationStorage + 'static,
+    {
+        Self { inner, storage: Arc::new(storage), on_migrate: None }
+    }
+
+    /// Sets a function, which is called whenever a migration is detected,
+    /// with old and new chat ids.
+    ///
+    /// The function is called before the request is re-sent to the new chat.
+    /// Note that if multiple requests to a migrated chat are sent
+    /// concurrently, the function may be called more than once for the same
+    /// chat.
+    #[must_use]
+    pub fn on_migrate<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(ChatId, ChatId) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.on_migrate = Some(Arc::new(move |old, new| f(old, new).boxed()));
+        self
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the storage of migrations.
+    pub fn storage(&self) -> &Arc<dyn MigrationStorage> {
+        &self.storage
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for FollowMigrations<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FollowMigrations").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl InMemMigrationStorage {
+    /// Creates an empty storage.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MigrationStorage for InMemMigrationStorage {
+    fn get(&self, old: ChatId) -> BoxFuture<'_, Option<ChatId>> {
+        let map = self.map.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        ready(map.get(&old).copied()).boxed()
+    }
+
+    fn insert(&self, old: ChatId, new: ChatId) -> BoxFuture<'_, ()> {
+        let mut map = self.map.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        map.insert(old, new);
+        ready(()).boxed()
+    }
+}
+
+impl<S> MigrationStorage for Arc<S>
+where
+    S: MigrationStorage + ?Sized,
+{
+    fn get(&self, old: ChatId) -> BoxFuture<'_, Option<ChatId>> {
+        S::get(self, old)
+    }
+
+    fn insert(&self, old: ChatId, new: ChatId) -> BoxFuture<'_, ()> {
+        S::insert(self, old, new)
+    }
+}
+
+/// Type of a `chat_id` parameter.
+trait ChatIdParam {
+    fn get(&self) -> Option<ChatId>;
+
+    fn set(&mut self, id: ChatId);
+}
+
+impl ChatIdParam for Recipient {
+    fn get(&self) -> Option<ChatId> {
+        match self {
+            Recipient::Id(id) => Some(*id),
+            Recipient::ChannelUsername(_) => None,
+        }
+    }
+
+    fn set(&mut self, id: ChatId) {
+        *self = Recipient::Id(id);
+    }
+}
+
+impl ChatIdParam for ChatId {
+    fn get(&self) -> Option<ChatId> {
+        Some(*self)
+    }
+
+    fn set(&mut self, id: ChatId) {
+        *self = id;
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        FollowMigrationsRequest {
+            inner: Arc::new($this.inner().$m($($arg),*)),
+            chat_id: |p| ChatIdParam::get(&p.chat_id),
+            set_chat_id: |p, id| ChatIdParam::set(&mut p.chat_id, id),
+            from_chat_id: |_| None,
+            set_from_chat_id: |_, _| {},
+            storage: Arc::clone(&$this.storage),
+            on_migrate: $this.on_migrate.clone(),
+        }
+    };
+}
+
+macro_rules! ffrom {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        FollowMigrationsRequest {
+            from_chat_id: |p| ChatIdParam::get(&p.from_chat_id),
+            set_from_chat_id: |p, id| ChatIdParam::set(&mut p.from_chat_id, id),
+            ..f!($m $this ($($arg: $T),*))
+        }
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        FollowMigrationsRequest<B::$T>
+    };
+}
+
+macro_rules! fid {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        $this.inner().$m($($arg),*)
+    };
+}
+
+macro_rules! ftyid {
+    ($T:ident) => {
+        B::$T
+    };
+}
+
+impl<B> Requester for FollowMigrations<B>
+where
+    B: Requester,
+    B::Err: AsResponseParameters,
+    B::ForwardMessage: Clone + Send + Sync + 'static,
+    B::ForwardMessages: Clone + Send + Sync + 'static,
+    B::CopyMessage: Clone + Send + Sync + 'static,
+    B::CopyMessages: Clone + Send + Sync + 'static,
+    B::SendMessage: Clone + Send + Sync + 'static,
+    B::SendPhoto: Clone + Send + Sync + 'static,
+    B::SendAudio: Clone + Send + Sync + 'static,
+    B::SendDocument: Clone + Send + Sync + 'static,
+    B::SendVideo: Clone + Send + Sync + 'static,
+    B::SendAnimation: Clone + Send + Sync + 'static,
+    B::SendVoice: Clone + Send + Sync + 'static,
+    B::SendVideoNote: Clone + Send + Sync + 'static,
+    B::SendPaidMedia: Clone + Send + Sync + 'static,
+    B::SendMediaGroup: Clone + Send + Sync + 'static,
+    B::SendLocation: Clone + Send + Sync + 'static,
+    B::EditMessageLiveLocation: Clone + Send + Sync + 'static,
+    B::StopMessageLiveLocation: Clone + Send + Sync + 'static,
+    B::EditMessageChecklist: Clone + Send + Sync + 'static,
+    B::SendVenue: Clone + Send + Sync + 'static,
+    B::SendContact: Clone + Send + Sync + 'static,
+    B::SendPoll: Clone + Send + Sync + 'static,
+    B::SendChecklist: Clone + Send + Sync + 'static,
+    B::SendDice: Clone + Send + Sync + 'static,
+    B::SendChatAction: Clone + Send + Sync + 'static,
+    B::SetMessageReaction: Clone + Send + Sync + 'static,
+    B::KickChatMember: Clone + Send + Sync + 'static,
+    B::BanChatMember: Clone + Send + Sync + 'static,
+    B::UnbanChatMember: Clone + Send + Sync + 'static,
+    B::RestrictChatMember: Clone + Send + Sync + 'static,
+    B::PromoteChatMember: Clone + Send + Sync + 'static,
+    B::SetChatAdministratorCustomTitle: Clone + Send + Sync + 'static,
+    B::BanChatSenderChat: Clone + Send + Sync + 'static,
+    B::UnbanChatSenderChat: Clone + Send + Sync + 'static,
+    B::SetChatPermissions: Clone + Send + Sync + 'static,
+    B::ExportChatInviteLink: Clone + Send + Sync + 'static,
+    B::CreateChatInviteLink: Clone + Send + Sync + 'static,
+    B::EditChatInviteLink: Clone + Send + Sync + 'static,
+    B::CreateChatSubscriptionInviteLink: Clone + Send + Sync + 'static,
+    B::EditChatSubscriptionInviteLink: Clone + Send + Sync + 'static,
+    B::RevokeChatInviteLink: Clone + Send + Sync + 'static,
+    B::SetChatPhoto: Clone + Send + Sync + 'static,
+    B::DeleteChatPhoto: Clone + Send + Sync + 'static,
+    B::SetChatTitle: Clone + Send + Sync + 'static,
+    B::SetChatDescription: Clone + Send + Sync + 'static,
+    B::PinChatMessage: Clone + Send + Sync + 'static,
+    B::UnpinChatMessage: Clone + Send + Sync + 'static,
+    B::UnpinAllChatMessages: Clone + Send + Sync + 'static,
+    B::LeaveChat: Clone + Send + Sync + 'static,
+    B::GetChat: Clone + Send + Sync + 'static,
+    B::GetChatAdministrators: Clone + Send + Sync + 'static,
+    B::GetChatMembersCount: Clone + Send + Sync + 'static,
+    B::GetChatMemberCount: Clone + Send + Sync + 'static,
+    B::GetChatMember: Clone + Send + Sync + 'static,
+    B::SetChatStickerSet: Clone + Send + Sync + 'static,
+    B::DeleteChatStickerSet: Clone + Send + Sync + 'static,
+    B::CreateForumTopic: Clone + Send + Sync + 'static,
+    B::EditForumTopic: Clone + Send + Sync + 'static,
+    B::CloseForumTopic: Clone + Send + Sync + 'static,
+    B::ReopenForumTopic: Clone + Send + Sync + 'static,
+    B::DeleteForumTopic: Clone + Send + Sync + 'static,
+    B::UnpinAllForumTopicMessages: Clone + Send + Sync + 'static,
+    B::EditGeneralForumTopic: Clone + Send + Sync + 'static,
+    B::CloseGeneralForumTopic: Clone + Send + Sync + 'static,
+    B::ReopenGeneralForumTopic: Clone + Send + Sync + 'static,
+    B::HideGeneralForumTopic: Clone + Send + Sync + 'static,
+    B::UnhideGeneralForumTopic: Clone + Send + Sync + 'static,
+    B::UnpinAllGeneralForumTopicMessages: Clone + Send + Sync + 'static,
+    B::GetUserChatBoosts: Clone + Send + Sync + 'static,
+    B::EditMessageText: Clone + Send + Sync + 'static,
+    B::EditMessageCaption: Clone + Send + Sync + 'static,
+    B::EditMessageMedia: Clone + Send + Sync + 'static,
+    B::EditMessageReplyMarkup: Clone + Send + Sync + 'static,
+    B::StopPoll: Clone + Send + Sync + 'static,
+    B::ApproveSuggestedPost: Clone + Send + Sync + 'static,
+    B::DeclineSuggestedPost: Clone + Send + Sync + 'static,
+    B::DeleteMessage: Clone + Send + Sync + 'static,
+    B::DeleteMessages: Clone + Send + Sync + 'static,
+    B::SendSticker: Clone + Send + Sync + 'static,
+    B::SendGiftChat: Clone + Send + Sync + 'static,
+    B::VerifyChat: Clone + Send + Sync + 'static,
+    B::RemoveChatVerification: Clone + Send + Sync + 'static,
+    B::ReadBusinessMessage: Clone + Send + Sync + 'static,
+    B::SendInvoice: Clone + Send + Sync + 'static,
+    B::SendGame: Clone + Send + Sync + 'static,
+    B::ApproveChatJoinRequest: Clone + Send + Sync + 'static,
+    B::DeclineChatJoinRequest: Clone + Send + Sync + 'static,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages
+        => ffrom, fty
+    }
+
+    requester_forward! {
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        stop_message_live_location,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        get_user_chat_boosts,
+        edit_message_text,
+        edit_message_caption,
+        edit_message_media,
+        edit_message_reply_markup,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        send_gift_chat,
+        verify_chat,
+        remove_chat_verification,
+        read_business_message,
+        send_invoice,
+        send_game,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => f, fty
+    }
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        edit_message_live_location_inline,
+        stop_message_live_location_inline,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        get_forum_topic_icon_stickers,
+        answer_callback_query,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text_inline,
+        edit_message_caption_inline,
+        edit_message_media_inline,
+        edit_message_reply_markup_inline,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        gift_premium_subscription,
+        verify_user,
+        remove_user_verification,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores
+        => fid, ftyid
+    }
+}
+
+download_forward! {
+    B
+    FollowMigrations<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`FollowMigrations`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+pub struct FollowMigrationsRequest<R: HasPayload> {
+    inner: Arc<R>,
+    chat_id: fn(&R::Payload) -> Option<ChatId>,
+    set_chat_id: fn(&mut R::Payload, ChatId),
+    from_chat_id: fn(&R::Payload) -> Option<ChatId>,
+    set_from_chat_id: fn(&mut R::Payload, ChatId),
+    storage: Arc<dyn MigrationStorage>,
+    on_migrate: Option<OnMigrate>,
+}
+
+impl<R: HasPayload> Clone for FollowMigrationsRequest<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            chat_id: self.chat_id,
+            set_chat_id: self.set_chat_id,
+            from_chat_id: self.from_chat_id,
+            set_from_chat_id: self.set_from_chat_id,
+            storage: Arc::clone(&self.storage),
+            on_migrate: self.on_migrate.clone(),
+        }
+    }
+}
+
+impl<R> HasPayload for FollowMigrationsRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    type Payload = R::Payload;
+
+    /// Note that if this request was already sent via `send_ref` and it
+    /// didn't yet complete, this method will clone the underlying request.
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        Arc::make_mut(&mut self.inner).payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for FollowMigrationsRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Err: AsResponseParameters,
+    Output<R>: Send,
+{
+    type Err = R::Err;
+
+    type Send = FollowMigrationsSend<R>;
+
+    type SendRef = FollowMigrationsSend<R>;
+
+    fn send(self) -> Self::Send {
+        FollowMigrationsSend(send(self).boxed())
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        FollowMigrationsSend(send(self.clone()).boxed())
+    }
+}
+
+impl<R> IntoFuture for FollowMigrationsRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Err: AsResponseParameters,
+    Output<R>: Send,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`FollowMigrationsRequest`]s.
+#[pin_project::pin_project]
+pub struct FollowMigrationsSend<R: Request>(#[pin] BoxFuture<'static, Result<Output<R>, R::Err>>);
+
+impl<R: Request> Future for FollowMigrationsSend<R> {
+    type Output = Result<Output<R>, R::Err>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        self.project().0.poll(cx)
+    }
+}
+
+async fn send<R>(mut request: FollowMigrationsRequest<R>) -> Result<Output<R>, R::Err>
+where
+    R: Request + Clone,
+    R::Err: AsResponseParameters,
+{
+    let chat_id = (request.chat_id)(request.payload_ref());
+    let from_chat_id = (request.from_chat_id)(request.payload_ref());
+    if chat_id.is_none() && from_chat_id.is_none() {
+        return request.inner.send_ref().await;
+    }
+
+    if let Some(new) = follow(&request, chat_id).await {
+        (request.set_chat_id)(request.payload_mut(), new);
+    }
+    if let Some(new) = follow(&request, from_chat_id).await {
+        (request.set_from_chat_id)(request.payload_mut(), new);
+    }
+
+    let error = match request.inner.send_ref().await {
+        Ok(output) => return Ok(output),
+        Err(error) => error,
+    };
+
+    let Some(new) = error.migrate_to_chat_id() else {
+        return Err(error);
+    };
+
+    // The error doesn't say which chat was migrated, so we can only follow it
+    // if there is one candidate
+    let old = match (chat_id, from_chat_id) {
+        (Some(chat_id), Some(from_chat_id)) if chat_id != from_chat_id => return Err(error),
+        (Some(old), _) | (None, Some(old)) => old,
+        (None, None) => return Err(error),
+    };
+
+    log::info!("Chat {old} was migrated to {new}");
+    request.storage.insert(old, new).await;
+    if let Some(on_migrate) = &request.on_migrate {
+        on_migrate(old, new).await;
+    }
+
+    if chat_id == Some(old) {
+        (request.set_chat_id)(request.payload_mut(), new);
+    }
+    if from_chat_id == Some(old) {
+        (request.set_from_chat_id)(request.payload_mut(), new);
+    }
+    request.inner.send_ref().await
+}
+
+async fn follow<R: HasPayload>(
+    request: &FollowMigrationsRequest<R>,
+    chat_id: Option<ChatId>,
+) -> Option<ChatId> {
+    request.storage.get(chat_id?).await
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use crate::{
+        adaptors::follow_migrations::MigrationStorage,
+        errors::RequestError,
+        payloads::{ForwardMessage, SendMessage},
+        prelude::*,
+        testing::{ChatBuilder, FakeServer},
+        types::{ChatId, MessageId},
+    };
+
+    #[tokio::test]
+    async fn follows_migrations() {
+        let (old, new) = (ChatId(-42), ChatId(-1001234567890));
+
+        let server = FakeServer::start().await.unwrap();
+        server.add_chat(ChatBuilder::supergroup(new, "Migrated").build());
+        server.respond_raw::<SendMessage>(
+            400,
+            json!({
+                "ok": false,
+                "error_code": 400,
+                "description": "Bad Request: group chat was upgraded to a supergroup chat",
+                "parameters": { "migrate_to_chat_id": new.0 },
+            }),
+        );
+
+        let migrations = Arc::new(Mutex::new(Vec::new()));
+        let bot = server.bot().follow_migrations().on_migrate({
+            let migrations = Arc::clone(&migrations);
+            move |old, new| {
+                migrations.lock().unwrap().push((old, new));
+                async {}
+            }
+        });
+
+        assert_eq!(bot.send_message(old, "first").await.unwrap().chat.id, new);
+        assert_eq!(bot.send_message(old, "second").await.unwrap().chat.id, new);
+
+        assert_eq!(*migrations.lock().unwrap(), [(old, new)]);
+        assert_eq!(bot.storage().get(old).await, Some(new));
+
+        let chat_ids =
+            server.requests().iter().map(|r| r.payload["chat_id"].clone()).collect::<Vec<_>>();
+        assert_eq!(chat_ids, [json!(old.0), json!(new.0), json!(new.0)]);
+    }
+
+    #[tokio::test]
+    async fn ignores_ambiguous_migrations() {
+        let (from, to, new) = (ChatId(-42), ChatId(-43), ChatId(-1001234567890));
+
+        let server = FakeServer::start().await.unwrap();
+        server.respond_raw::<ForwardMessage>(
+            400,
+            json!({
+                "ok": false,
+                "error_code": 400,
+                "description": "Bad Request: group chat was upgraded to a supergroup chat",
+                "parameters": { "migrate_to_chat_id": new.0 },
+            }),
+        );
+
+        let bot = server.bot().follow_migrations();
+
+        // Either of the chats could have been migrated, so the error is returned
+        let res = bot.forward_message(to, from, MessageId(1)).await;
+        assert!(matches!(res, Err(RequestError::MigrateToChatId(id)) if id == new));
+        assert_eq!(bot.storage().get(from).await, None);
+        assert_eq!(bot.storage().get(to).await, None);
+        assert_eq!(server.requests().len(), 1);
+
+        // Known migrations are applied to both chats
+        bot.storage().insert(from, new).await;
+        let _ = bot.forward_message(to, from, MessageId(1)).await;
+        let request = &server.requests()[1];
+        assert_eq!(request.payload["chat_id"], json!(to.0));
+        assert_eq!(request.payload["from_chat_id"], json!(new.0));
+    }
+}
@@ -0,0 +1,653 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::ready;
+use url::Url;
+
+use crate::{
+    errors::ErrorLabel,
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Collects statistics of requests.
+///
+/// For every method (identified by [`Payload::NAME`], e.g. `SendMessage`) this
+/// adaptor counts completed requests, errors (by [`ErrorLabel`]) and records
+/// request latencies in a histogram.
+///
+/// The statistics are shared between clones of the adaptor and can be
+/// inspected with [`Metrics::snapshot`]. A snapshot can be rendered in the
+/// Prometheus text format with [`MetricsSnapshot::to_prometheus`].
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{prelude::*, types::ChatId};
+///
+/// # async {
+/// let bot = Bot::from_env().metrics();
+///
+/// bot.send_message(ChatId(0), "Hi!").await?;
+///
+/// let snapshot = bot.snapshot();
+/// let send_message = &snapshot.methods["SendMessage"];
+/// println!("sent {} messages", send_message.requests);
+/// println!("{} users blocked the bot", send_message.errors.get("BotBlocked").unwrap_or(&0));
+///
+/// // Serve this on `/metrics` endpoint
+/// let _ = snapshot.to_prometheus();
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+#[derive(Clone, Debug)]
+pub struct Metrics<B> {
+    inner: B,
+    registry: Arc<Registry>,
+}
+
+/// Default upper bounds of latency histogram buckets.
+pub const DEFAULT_BUCKETS: &[Duration] = &[
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+];
+
+impl<B> Metrics<B> {
+    /// Creates new adaptor with [`DEFAULT_BUCKETS`].
+    ///
+    /// Note: it's recommended to use [`RequesterExt::metrics`] instead.
+    ///
+    /// [`RequesterExt::metrics`]: crate::requests::RequesterExt::metrics
+    pub fn new(inner: B) -> Self {
+        Self::with_buckets(inner, DEFAULT_BUCKETS.to_vec())
+    }
+
+    /// Creates new adaptor with the given upper bounds of latency histogram
+    /// buckets.
+    ///
+    /// The bounds are sorted and deduplicated.
+    pub fn with_buckets(inner: B, mut buckets: Vec<Duration>) -> Self {
+        buckets.sort_unstable();
+        buckets.dedup();
+
+        Self { inner, registry: Arc::new(Registry { buckets, methods: Mutex::default() }) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns statistics collected so far.
+    #[must_use]
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let methods = self.registry.lock();
+        MetricsSnapshot { methods: methods.iter().map(|(&k, v)| (k, v.clone())).collect() }
+    }
+
+    /// Resets all statistics.
+    ///
+    /// Note: this also resets statistics of clones of self.
+    pub fn reset(&self) {
+        self.registry.lock().clear();
+    }
+}
+
+/// Statistics collected by [`Metrics`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct MetricsSnapshot {
+    /// Statistics of methods, by [`Payload::NAME`].
+    pub methods: BTreeMap<&'static str, MethodMetrics>,
+}
+
+/// Statistics of a single method.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct MethodMetrics {
+    /// Number of completed requests, including failed ones.
+    pub requests: u64,
+
+    /// Number of failed requests, by [`ErrorLabel::label`].
+    pub errors: BTreeMap<&'static str, u64>,
+
+    /// Latencies of completed requests.
+    pub latency: Histogram,
+}
+
+/// Latency histogram.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Histogram {
+    /// Upper bounds of the buckets.
+    pub bounds: Vec<Duration>,
+
+    /// Number of observations in each bucket.
+    ///
+    /// `counts[i]` is the number of observations which are greater than
+    /// `bounds[i - 1]` and less than or equal to `bounds[i]`. The last element
+    /// is the number of observations greater than all bounds, so
+    /// `counts.len() == bounds.len() + 1`.
+    pub counts: Vec<u64>,
+
+    /// Sum of all observations.
+    pub sum: Duration,
+}
+
+impl MethodMetrics {
+    fn new(bounds: &[Duration]) -> Self {
+        Self { requests: 0, errors: BTreeMap::new(), latency: Histogram::new(bounds) }
+    }
+
+    /// Returns the total number of failed requests.
+    #[must_use]
+    pub fn errors_total(&self) -> u64 {
+        self.errors.values().sum()
+    }
+}
+
+impl Histogram {
+    fn new(bounds: &[Duration]) -> Self {
+        Self { bounds: bounds.to_vec(), counts: vec![0; bounds.len() + 1], sum: Duration::ZERO }
+    }
+
+    fn observe(&mut self, value: Duration) {
+        let bucket = self.bounds.partition_point(|&bound| bound < value);
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Returns the number of observations.
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+impl MetricsSnapshot {
+    /// Renders the statistics in the [Prometheus text format].
+    ///
+    /// The following metrics are rendered, all labeled with `method`:
+    ///
+    /// - `teloxide_requests_total` — counter of completed requests
+    /// - `teloxide_request_errors_total` — counter of failed requests, also
+    ///   labeled with `error`
+    /// - `teloxide_request_duration_seconds` — histogram of latencies
+    ///
+    /// [Prometheus text format]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP teloxide_requests_total Number of completed requests.\n");
+        out.push_str("# TYPE teloxide_requests_total counter\n");
+        for (method, metrics) in &self.methods {
+            let _ = writeln!(
+                out,
+                "teloxide_requests_total{{method=\"{method}\"}} {}",
+                metrics.requests
+            );
+        }
+
+        out.push_str("# HELP teloxide_request_errors_total Number of failed requests.\n");
+        out.push_str("# TYPE teloxide_request_errors_total counter\n");
+        for (method, metrics) in &self.methods {
+            for (error, count) in &metrics.errors {
+                let _ = writeln!(
+                    out,
+                    "teloxide_request_errors_total{{method=\"{method}\",error=\"{}\"}} {count}",
+                    escape_label(error),
+                );
+            }
+        }
+
+        out.push_str("# HELP teloxide_request_duration_seconds Latency of requests.\n");
+        out.push_str("# TYPE teloxide_request_duration_seconds histogram\n");
+        for (method, metrics) in &self.methods {
+            let Histogram { bounds, counts, sum } = &metrics.latency;
+
+            let mut cumulative = 0;
+            for (bound, count) in bounds.iter().zip(counts) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "teloxide_request_duration_seconds_bucket{{method=\"{method}\",le=\"{}\"}} \
+                     {cumulative}",
+                    bound.as_secs_f64(),
+                );
+            }
+            let count = metrics.latency.count();
+            let _ = writeln!(
+                out,
+                "teloxide_request_duration_seconds_bucket{{method=\"{method}\",le=\"+Inf\"}} \
+                 {count}"
+            );
+            let _ = writeln!(
+                out,
+                "teloxide_request_duration_seconds_sum{{method=\"{method}\"}} {}",
+                sum.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "teloxide_request_duration_seconds_count{{method=\"{method}\"}} {count}"
+            );
+        }
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\\', '"', '\n']) {
+        return value.into();
+    }
+
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").into()
+}
+
+#[derive(Debug)]
+struct Registry {
+    buckets: Vec<Duration>,
+    methods: Mutex<HashMap<&'static str, MethodMetrics>>,
+}
+
+impl Registry {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<&'static str, MethodMetrics>> {
+        self.methods.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record(&self, method: &'static str, latency: Duration, error: Option<&'static str>) {
+        let mut methods = self.lock();
+        let metrics = methods.entry(method).or_insert_with(|| MethodMetrics::new(&self.buckets));
+
+        metrics.requests += 1;
+        metrics.latency.observe(latency);
+        if let Some(error) = error {
+            *metrics.errors.entry(error).or_insert(0) += 1;
+        }
+    }
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        MetricsRequest<B::$T>
+    };
+}
+
+macro_rules! fwd_inner {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        MetricsRequest {
+            inner: $this.inner().$m($($arg),*),
+            registry: Arc::clone(&$this.registry),
+        }
+    };
+}
+
+impl<B> Requester for Metrics<B>
+where
+    B: Requester,
+    B::Err: ErrorLabel,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fwd_inner, fty
+    }
+}
+
+download_forward! {
+    B
+    Metrics<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`Metrics`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct MetricsRequest<R> {
+    inner: R,
+    registry: Arc<Registry>,
+}
+
+impl<R> HasPayload for MetricsRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for MetricsRequest<R>
+where
+    R: Request,
+    R::Err: ErrorLabel,
+{
+    type Err = R::Err;
+
+    type Send = MetricsSend<R::Send>;
+
+    type SendRef = MetricsSend<R::SendRef>;
+
+    fn send(self) -> Self::Send {
+        MetricsSend {
+            registry: self.registry,
+            method: R::Payload::NAME,
+            started: Instant::now(),
+            inner: self.inner.send(),
+        }
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        MetricsSend {
+            registry: Arc::clone(&self.registry),
+            method: R::Payload::NAME,
+            started: Instant::now(),
+            inner: self.inner.send_ref(),
+        }
+    }
+}
+
+impl<R> IntoFuture for MetricsRequest<R>
+where
+    R: Request,
+    R::Err: ErrorLabel,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`MetricsRequest`]s.
+#[pin_project::pin_project]
+pub struct MetricsSend<F> {
+    registry: Arc<Registry>,
+    method: &'static str,
+    started: Instant,
+    #[pin]
+    inner: F,
+}
+
+impl<F, T, E> Future for MetricsSend<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: ErrorLabel,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let ret = ready!(this.inner.poll(cx));
+        this.registry.record(
+            this.method,
+            this.started.elapsed(),
+            ret.as_ref().err().map(ErrorLabel::label),
+        );
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        adaptors::Metrics,
+        errors::{ApiError, RequestError},
+        payloads::SendMessage,
+        prelude::*,
+        testing::MockBot,
+        types::ChatId,
+    };
+
+    #[tokio::test]
+    async fn counts_requests_and_errors() {
+        let inner = MockBot::new();
+        inner.respond_error::<SendMessage>(RequestError::from(ApiError::BotBlocked));
+        let bot = inner.metrics();
+
+        bot.get_me().await.unwrap();
+        bot.send_message(ChatId(1), "blocked").await.unwrap_err();
+        bot.send_message(ChatId(1), "sent").await.unwrap();
+
+        let snapshot = bot.snapshot();
+        assert_eq!(snapshot.methods.len(), 2);
+        assert_eq!(snapshot.methods["GetMe"].requests, 1);
+        assert_eq!(snapshot.methods["GetMe"].errors_total(), 0);
+
+        let send_message = &snapshot.methods["SendMessage"];
+        assert_eq!(send_message.requests, 2);
+        assert_eq!(send_message.errors.len(), 1);
+        assert_eq!(send_message.errors["BotBlocked"], 1);
+        assert_eq!(send_message.latency.count(), 2);
+
+        bot.reset();
+        assert!(bot.snapshot().methods.is_empty());
+    }
+
+    #[tokio::test]
+    async fn prometheus() {
+        let inner = MockBot::new();
+        inner.respond_error::<SendMessage>(RequestError::from(ApiError::BotBlocked));
+        let bot =
+            Metrics::with_buckets(inner, vec![Duration::from_secs(60), Duration::from_secs(1)]);
+
+        bot.send_message(ChatId(1), "blocked").await.unwrap_err();
+
+        let text = bot.snapshot().to_prometheus();
+        let lines = text.lines().filter(|line| !line.starts_with('#')).collect::<Vec<_>>();
+        let sum =
+            lines.iter().find(|line| line.starts_with("teloxide_request_duration_seconds_sum"));
+        assert!(sum.is_some());
+
+        assert_eq!(
+            lines.into_iter().filter(|line| !line.contains("_sum")).collect::<Vec<_>>(),
+            [
+                r#"teloxide_requests_total{method="SendMessage"} 1"#,
+                r#"teloxide_request_errors_total{method="SendMessage",error="BotBlocked"} 1"#,
+                r#"teloxide_request_duration_seconds_bucket{method="SendMessage",le="1"} 1"#,
+                r#"teloxide_request_duration_seconds_bucket{method="SendMessage",le="60"} 1"#,
+                r#"teloxide_request_duration_seconds_bucket{method="SendMessage",le="+Inf"} 1"#,
+                r#"teloxide_request_duration_seconds_count{method="SendMessage"} 1"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,1357 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::{File, OpenOptions},
+    future::{Future, IntoFuture},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+    task::{self, Poll},
+    thread,
+};
+
+use futures::{future::BoxFuture, ready, FutureExt};
+use reqwest::Response;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+use url::Url;
+
+use crate::{
+    errors::{redact_token, ErrorResponse},
+    net::{HttpTransport, NoTransport, TelegramResponse, TransportError},
+    requests::{HasPayload, Output, Payload, Request, Requester, ResponseResult},
+    types::*,
+    ApiError, Bot, RequestError,
+};
+
+/// Records requests and responses.
+///
+/// Every completed request is written as a single line of JSON (an
+/// [`Exchange`]) to the given writer, for example a file. The recording can
+/// later be played back with [`Replay`] to reproduce bot's behaviour offline.
+///
+/// Responses are written in the format of the Bot API (i.e.
+/// `{"ok": true, "result": ...}`). Responses received through a
+/// [`RecordTransport`] passed to [`Record::with_raw_responses`] are written as
+/// they were received, other responses (e.g. of [`MockBot`]) are created from
+/// outputs of requests.
+///
+/// Exchanges are written by a separate thread, so that requests don't wait
+/// for the writer. Use [`Record::flush`] to wait until they are written.
+///
+/// Note that recordings contain all the data sent and received by the bot,
+/// including users' messages, so treat them accordingly.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{adaptors::record::Record, prelude::*, types::ChatId};
+///
+/// # async {
+/// let bot = Record::to_file(Bot::from_env(), "requests.jsonl")?;
+///
+/// bot.send_message(ChatId(0), "Hi!").await?;
+/// # Ok::<_, Box<dyn std::error::Error>>(()) };
+/// ```
+///
+/// Recording raw responses:
+///
+/// ```no_run
+/// use teloxide_core::{
+///     adaptors::record::{Record, RecordTransport},
+///     net,
+///     prelude::*,
+///     types::ChatId,
+/// };
+///
+/// # async {
+/// let transport = RecordTransport::new(net::default_reqwest_settings().build()?);
+/// let bot = Bot::with_transport("TOKEN", transport.clone());
+/// let bot = Record::to_file(bot, "requests.jsonl")?.with_raw_responses(&transport);
+///
+/// bot.send_message(ChatId(0), "Hi!").await?;
+/// # Ok::<_, Box<dyn std::error::Error>>(()) };
+/// ```
+///
+/// [`MockBot`]: crate::testing::MockBot
+#[derive(Clone)]
+pub struct Record<B> {
+    inner: B,
+    writer: mpsc::Sender<WriterMessage>,
+    raw: Option<RawResponses>,
+}
+
+enum WriterMessage {
+    Exchange(Exchange),
+    // Notifies the sender after all previous exchanges are written
+    Flush(oneshot::Sender<()>),
+}
+
+/// A single request and its response, recorded by [`Record`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Exchange {
+    /// Name of the method, see [`Payload::NAME`].
+    pub method: String,
+
+    /// The payload of the request, serialized to JSON.
+    pub payload: Value,
+
+    /// The response, in the format of the Bot API, e.g.
+    /// `{"ok": true, "result": ...}`.
+    ///
+    /// This is `None` if no response was received, see [`Exchange::error`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<Value>,
+
+    /// Kind of the error if no response was received.
+    ///
+    /// [`Replay`] returns recorded errors of the same kind. Errors recorded
+    /// without a kind are returned as [`RequestError::Io`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<ErrorKind>,
+
+    /// Description of the error if no response was received, for example
+    /// because of a network error.
+    ///
+    /// For [`ErrorKind::InvalidJson`] errors this is the raw response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Kind of an error recorded by [`Record`], see [`Exchange::error_kind`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// [`RequestError::Network`].
+    Network,
+
+    /// [`RequestError::InvalidJson`] caused by a response which is not JSON.
+    InvalidJson,
+
+    /// [`RequestError::Io`].
+    Io,
+
+    /// [`RequestError::Cancelled`].
+    Cancelled,
+
+    /// [`RequestError::CircuitOpen`].
+    CircuitOpen,
+
+    /// [`RequestError::TimedOut`].
+    TimedOut,
+}
+
+/// [`HttpTransport`] which keeps raw responses for [`Record`], see
+/// [`Record::with_raw_responses`].
+///
+/// Only responses to requests of Bot API methods are kept, responses to file
+/// downloads are passed through as is.
+#[derive(Clone)]
+pub struct RecordTransport<T> {
+    inner: T,
+    raw: RawResponses,
+}
+
+impl<T> RecordTransport<T> {
+    /// Wraps `inner`, which sends the requests.
+    pub fn new(inner: T) -> Self {
+        Self { inner, raw: RawResponses::default() }
+    }
+
+    /// Allows to access the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RecordTransport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordTransport").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl<T: HttpTransport> HttpTransport for RecordTransport<T> {
+    fn execute(
+        &self,
+        request: reqwest::Request,
+    ) -> BoxFuture<'static, Result<Response, TransportError>> {
+        let Some((method, token)) = method_of(request.url()) else {
+            return self.inner.execute(request);
+        };
+
+        let response = self.inner.execute(request);
+        let raw = self.raw.clone();
+        async move {
+            let response = response.await?;
+
+            let mut builder =
+                http::Response::builder().status(response.status()).version(response.version());
+            if let Some(headers) = builder.headers_mut() {
+                *headers = response.headers().clone();
+            }
+            let body = response.bytes().await?;
+
+            raw.push(method, redact_token(&String::from_utf8_lossy(&body), &token));
+            let response = builder.body(body).expect("parts of a valid response");
+            Ok(Response::from(response))
+        }
+        .boxed()
+    }
+}
+
+/// Returns the name of the method and the token, if `url` is a URL of a Bot API
+/// method (i.e. `.../bot<token>/<method>`).
+fn method_of(url: &Url) -> Option<(String, String)> {
+    let mut segments = url.path_segments()?.rev();
+    let method = segments.next()?;
+    let token = segments.next()?.strip_prefix("bot")?;
+    // Files are downloaded from `.../file/bot<token>/<path>`
+    if segments.next() == Some("file") {
+        return None;
+    }
+
+    Some((method.to_owned(), token.to_owned()))
+}
+
+/// Raw responses kept by [`RecordTransport`] until they are matched with
+/// requests by [`Record`].
+#[derive(Clone, Default)]
+struct RawResponses(Arc<Mutex<VecDeque<(String, String)>>>);
+
+impl RawResponses {
+    /// Number of kept responses, older ones are dropped, since responses to
+    /// requests which aren't recorded are never taken.
+    const CAPACITY: usize = 64;
+
+    fn push(&self, method: String, raw: String) {
+        let mut responses = self.lock();
+        if responses.len() == Self::CAPACITY {
+            responses.pop_front();
+        }
+        responses.push_back((method, raw));
+    }
+
+    /// Takes the raw response to a `method` request which resulted in
+    /// `result`.
+    ///
+    /// Requests may complete in a different order than their responses are
+    /// received, so the response has to be equal to `result`, after it's
+    /// parsed.
+    fn take<T>(&self, method: &str, result: &ResponseResult<T>) -> Option<String>
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let expected = created_response(result)?;
+        // See `net::request_json`
+        let method = method.trim_end_matches("Inline");
+
+        let mut responses = self.lock();
+        let i = responses.iter().position(|(m, raw)| {
+            m == method
+                && created_response(&parse_response::<T>(raw.clone())).as_ref() == Some(&expected)
+        })?;
+
+        responses.remove(i).map(|(_, raw)| raw)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<(String, String)>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<B> Record<B> {
+    /// Creates new adaptor which writes recorded requests to `writer`.
+    ///
+    /// The writer is used by a separate thread, which is stopped when all
+    /// clones of the adaptor and their requests are dropped. The writer is
+    /// flushed after every request.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::record`] instead.
+    ///
+    /// [`RequesterExt::record`]: crate::requests::RequesterExt::record
+    pub fn new<W>(inner: B, writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Self { inner, writer: spawn_writer(writer), raw: None }
+    }
+
+    /// Records raw responses received through `transport`, instead of
+    /// creating them from outputs of requests.
+    ///
+    /// `transport` should be the transport of the [`Bot`] wrapped by this
+    /// adaptor.
+    pub fn with_raw_responses<T>(self, transport: &RecordTransport<T>) -> Self {
+        Self { raw: Some(transport.raw.clone()), ..self }
+    }
+
+    /// Creates new adaptor which appends recorded requests to the file at
+    /// `path`, creating it if it doesn't exist.
+    pub fn to_file(inner: B, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(inner, file))
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Waits until all requests completed so far are written.
+    pub async fn flush(&self) {
+        let (tx, rx) = oneshot::channel();
+        // The writer thread only stops when all senders are dropped
+        if self.writer.send(WriterMessage::Flush(tx)).is_ok() {
+            let _ = rx.await;
+        }
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for Record<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Record").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl Exchange {
+    /// Creates an exchange of a `method` request, which received `response`.
+    ///
+    /// `response` is in the format of the Bot API, e.g.
+    /// `{"ok": true, "result": ...}`.
+    pub fn new(method: impl Into<String>, payload: Value, response: Value) -> Self {
+        let method = method.into();
+        Self { method, payload, response: Some(response), error_kind: None, error: None }
+    }
+
+    /// Creates an exchange of a `method` request, which failed without a
+    /// response.
+    pub fn failed(
+        method: impl Into<String>,
+        payload: Value,
+        kind: ErrorKind,
+        error: impl Into<String>,
+    ) -> Self {
+        let (method, error) = (method.into(), Some(error.into()));
+        Self { method, payload, response: None, error_kind: Some(kind), error }
+    }
+
+    fn from_result<T: Serialize>(
+        method: &str,
+        payload: Value,
+        result: &ResponseResult<T>,
+        raw: Option<&str>,
+    ) -> Self {
+        let recorded = |kind, err: &RequestError| (None, Some(kind), Some(err.to_string()));
+
+        let (response, error_kind, error) = match result {
+            Ok(_)
+            | Err(
+                RequestError::Api(..)
+                | RequestError::MigrateToChatId(_)
+                | RequestError::RetryAfter(_),
+            ) => {
+                // The raw response is preferred, since it has fields unknown to
+                // `teloxide` and is formatted by Telegram
+                let response = raw
+                    .and_then(|raw| serde_json::from_str(raw).ok())
+                    .or_else(|| created_response(result));
+                (response, None, None)
+            }
+            Err(RequestError::InvalidJson { raw, .. }) => match serde_json::from_str(raw) {
+                Ok(response) => (Some(response), None, None),
+                Err(_) => (None, Some(ErrorKind::InvalidJson), Some(raw.to_string())),
+            },
+            Err(err @ RequestError::Network(_)) => recorded(ErrorKind::Network, err),
+            Err(err @ RequestError::Io(_)) => recorded(ErrorKind::Io, err),
+            Err(err @ RequestError::Cancelled) => recorded(ErrorKind::Cancelled, err),
+            Err(err @ RequestError::CircuitOpen) => recorded(ErrorKind::CircuitOpen, err),
+            Err(err @ RequestError::TimedOut) => recorded(ErrorKind::TimedOut, err),
+        };
+
+        Self { method: method.to_owned(), payload, response, error_kind, error }
+    }
+
+    fn into_result<T>(self) -> ResponseResult<T>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let Some(response) = self.response else {
+            let error = self.error.unwrap_or_default();
+            return Err(match self.error_kind {
+                Some(ErrorKind::Network) => {
+                    TransportError::other(format!("recorded network error: {error}")).into()
+                }
+                Some(ErrorKind::InvalidJson) => return parse_response(error),
+                Some(ErrorKind::Cancelled) => RequestError::Cancelled,
+                Some(ErrorKind::CircuitOpen) => RequestError::CircuitOpen,
+                Some(ErrorKind::TimedOut) => RequestError::TimedOut,
+                Some(ErrorKind::Io) | None => {
+                    RequestError::Io(Arc::new(io::Error::other(format!("recorded error: {error}"))))
+                }
+            });
+        };
+
+        // `from_str` instead of `from_value` because of the `Update` parsing quirks
+        parse_response(response.to_string())
+    }
+}
+
+fn parse_response<T>(raw: String) -> ResponseResult<T>
+where
+    T: DeserializeOwned + 'static,
+{
+    serde_json::from_str::<TelegramResponse<T>>(&raw)
+        .map(Into::into)
+        .map_err(|source| RequestError::InvalidJson { source: source.into(), raw: raw.into() })?
+}
+
+/// Returns the response of the Bot API which would cause `result`.
+fn created_response<T: Serialize>(result: &ResponseResult<T>) -> Option<Value> {
+    let response = match result {
+        Ok(output) => json!({ "ok": true, "result": output }),
+        Err(RequestError::Api(ApiError::Unknown(response))) => json!({
+            "ok": false,
+            "error_code": response.error_code,
+            "description": response.description,
+            "parameters": response.parameters,
+        }),
+        Err(RequestError::Api(error)) => json!({
+            "ok": false,
+            "description": description_of(error),
+        }),
+        Err(RequestError::MigrateToChatId(id)) => json!({
+            "ok": false,
+            "error_code": 400,
+            "description": "Bad Request: group chat was upgraded to a supergroup chat",
+            "parameters": ResponseParameters::MigrateToChatId(*id),
+        }),
+        Err(RequestError::RetryAfter(secs)) => json!({
+            "ok": false,
+            "error_code": 429,
+            "description": format!("Too Many Requests: retry after {}", secs.seconds()),
+            "parameters": ResponseParameters::RetryAfter(*secs),
+        }),
+        Err(_) => return None,
+    };
+
+    Some(response)
+}
+
+/// Returns the description which Telegram sends for `error`.
+fn description_of(error: &ApiError) -> String {
+    match error {
+        ApiError::InvalidToken => "Unauthorized".to_owned(),
+        ApiError::CantGetUpdates => format!("Conflict: {error}"),
+        ApiError::CantParseEntities(description) => description.clone(),
+        ApiError::Unknown(response) => response.description.clone(),
+        error => error.to_string(),
+    }
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        RecordRequest<B::$T>
+    };
+}
+
+macro_rules! fwd_inner {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        RecordRequest {
+            inner: $this.inner().$m($($arg),*),
+            writer: $this.writer.clone(),
+            raw: $this.raw.clone(),
+        }
+    };
+}
+
+impl<B> Requester for Record<B>
+where
+    B: Requester<Err = RequestError>,
+{
+    type Err = RequestError;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fwd_inner, fty
+    }
+}
+
+download_forward! {
+    B
+    Record<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`Record`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct RecordRequest<R> {
+    inner: R,
+    writer: mpsc::Sender<WriterMessage>,
+    raw: Option<RawResponses>,
+}
+
+impl<R> HasPayload for RecordRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for RecordRequest<R>
+where
+    R: Request<Err = RequestError>,
+    R::Payload: Serialize,
+    Output<R>: Serialize + DeserializeOwned + 'static,
+{
+    type Err = RequestError;
+
+    type Send = RecordSend<R::Send>;
+
+    type SendRef = RecordSend<R::SendRef>;
+
+    fn send(self) -> Self::Send {
+        let payload = serialize_payload(self.inner.payload_ref());
+        RecordSend::new(self.writer, self.raw, R::Payload::NAME, payload, self.inner.send())
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let payload = serialize_payload(self.inner.payload_ref());
+        let (writer, raw) = (self.writer.clone(), self.raw.clone());
+        RecordSend::new(writer, raw, R::Payload::NAME, payload, self.inner.send_ref())
+    }
+}
+
+impl<R> IntoFuture for RecordRequest<R>
+where
+    R: Request<Err = RequestError>,
+    R::Payload: Serialize,
+    Output<R>: Serialize + DeserializeOwned + 'static,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`RecordRequest`]s.
+#[pin_project::pin_project]
+pub struct RecordSend<F> {
+    writer: mpsc::Sender<WriterMessage>,
+    raw: Option<RawResponses>,
+    method: &'static str,
+    payload: Option<Value>,
+    #[pin]
+    inner: F,
+}
+
+impl<F: Future> RecordSend<F> {
+    fn new(
+        writer: mpsc::Sender<WriterMessage>,
+        raw: Option<RawResponses>,
+        method: &'static str,
+        payload: Value,
+        inner: F,
+    ) -> Self {
+        Self { writer, raw, method, payload: Some(payload), inner }
+    }
+}
+
+impl<F, T> Future for RecordSend<F>
+where
+    F: Future<Output = ResponseResult<T>>,
+    T: Serialize + DeserializeOwned + 'static,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let ret = ready!(this.inner.poll(cx));
+        let payload = this.payload.take().expect("polled after completion");
+        let raw = this.raw.as_ref().and_then(|raw| raw.take(this.method, &ret));
+        let exchange = Exchange::from_result(this.method, payload, &ret, raw.as_deref());
+        if this.writer.send(WriterMessage::Exchange(exchange)).is_err() {
+            log::error!("Couldn't record `{}` request: the writer thread has stopped", this.method);
+        }
+
+        Poll::Ready(ret)
+    }
+}
+
+fn serialize_payload<P: Serialize>(payload: &P) -> Value {
+    serde_json::to_value(payload).expect("serialization of request to be infallible")
+}
+
+fn spawn_writer<W>(mut writer: W) -> mpsc::Sender<WriterMessage>
+where
+    W: Write + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+
+    thread::Builder::new()
+        .name("teloxide-record".to_owned())
+        .spawn(move || {
+            for message in rx {
+                match message {
+                    WriterMessage::Exchange(exchange) => {
+                        if let Err(err) = write_exchange(&mut writer, &exchange) {
+                            log::error!("Couldn't record `{}` request: {err}", exchange.method);
+                        }
+                    }
+                    WriterMessage::Flush(done) => {
+                        let _ = done.send(());
+                    }
+                }
+            }
+        })
+        .expect("failed to spawn the writer thread of `Record`");
+
+    tx
+}
+
+fn write_exchange(writer: &mut impl Write, exchange: &Exchange) -> io::Result<()> {
+    let mut line = serde_json::to_vec(exchange)?;
+    line.push(b'\n');
+
+    writer.write_all(&line)?;
+    writer.flush()
+}
+
+/// A [`Requester`] which plays back requests recorded by [`Record`].
+///
+/// Recorded responses are returned in order, one for each sent request. If
+/// the method of a request doesn't match the method of the next recorded
+/// request, or if there are no recorded requests left, the request fails
+/// with [`ApiError::Unknown`]. Payloads of requests are not compared, they
+/// are available via [`Replay::mismatches`] instead.
+///
+/// Recorded errors are returned as errors of the same kind, see
+/// [`Exchange::error_kind`]. Recorded network errors are returned as
+/// [`RequestError::Io`] errors, like errors of custom [`HttpTransport`]s,
+/// since [`reqwest::Error`]s can't be created.
+///
+/// Clones of a `Replay` share the recording.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{adaptors::record::Replay, prelude::*, types::ChatId};
+///
+/// # async {
+/// let bot = Replay::from_file("requests.jsonl")?;
+///
+/// // Returns the recorded response, without accessing Telegram
+/// bot.send_message(ChatId(0), "Hi!").await?;
+/// assert_eq!(bot.remaining(), 0);
+/// # Ok::<_, Box<dyn std::error::Error>>(()) };
+/// ```
+#[derive(Clone)]
+pub struct Replay {
+    // `Bot` is only used to construct requests, it never sends anything.
+    bot: Bot,
+    state: Arc<Mutex<ReplayState>>,
+}
+
+struct ReplayState {
+    exchanges: VecDeque<Exchange>,
+    mismatches: Vec<(Exchange, Value)>,
+}
+
+impl Replay {
+    /// Creates new `Replay` which plays back `exchanges`.
+    pub fn new(exchanges: impl IntoIterator<Item = Exchange>) -> Self {
+        let state =
+            ReplayState { exchanges: exchanges.into_iter().collect(), mismatches: Vec::new() };
+
+        Self {
+            bot: Bot::with_transport("0:replay", NoTransport),
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Reads a recording in the JSON Lines format from `reader`.
+    ///
+    /// Empty lines are skipped.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<Self> {
+        let mut exchanges = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            exchanges.push(serde_json::from_str(&line)?);
+        }
+
+        Ok(Self::new(exchanges))
+    }
+
+    /// Reads a recording from the file at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Returns the number of recorded requests which weren't played back yet.
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.state().exchanges.len()
+    }
+
+    /// Returns recorded requests whose payloads differ from payloads of the
+    /// played back requests, along with payloads of the latter.
+    #[must_use]
+    pub fn mismatches(&self) -> Vec<(Exchange, Value)> {
+        self.state().mismatches.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn execute<T>(&self, method: &str, payload: Value) -> ResponseResult<T>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let mut state = self.state();
+
+        let Some(next) = state.exchanges.front() else {
+            return Err(RequestError::from(ApiError::Unknown(ErrorResponse::new(format!(
+                "Replay has no recorded requests left, but `{method}` was sent"
+            )))));
+        };
+        if next.method != method {
+            return Err(RequestError::from(ApiError::Unknown(ErrorResponse::new(format!(
+                "Replay expected `{}` request, but `{method}` was sent",
+                next.method
+            )))));
+        }
+
+        let exchange = state.exchanges.pop_front().expect("checked above");
+        if exchange.payload != payload {
+            state.mismatches.push((exchange.clone(), payload));
+        }
+
+        exchange.into_result()
+    }
+}
+
+impl fmt::Debug for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replay").field("remaining", &self.remaining()).finish_non_exhaustive()
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        ReplayRequest { replay: $this.clone(), inner: $this.bot.$m($($arg),*) }
+    };
+}
+
+macro_rules! fty_replay {
+    ($T:ident) => {
+        ReplayRequest<<Bot as Requester>::$T>
+    };
+}
+
+impl Requester for Replay {
+    type Err = RequestError;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => f, fty_replay
+    }
+}
+
+/// Request returned by [`Replay`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct ReplayRequest<R> {
+    replay: Replay,
+    inner: R,
+}
+
+impl<R> HasPayload for ReplayRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for ReplayRequest<R>
+where
+    R: HasPayload,
+    R::Payload: Serialize,
+    Output<R>: DeserializeOwned + Send + 'static,
+{
+    type Err = RequestError;
+
+    type Send = BoxFuture<'static, ResponseResult<Output<R>>>;
+
+    type SendRef = BoxFuture<'static, ResponseResult<Output<R>>>;
+
+    fn send(self) -> Self::Send {
+        self.send_ref()
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let replay = self.replay.clone();
+        let payload = serialize_payload(self.inner.payload_ref());
+
+        async move { replay.execute(R::Payload::NAME, payload) }.boxed()
+    }
+}
+
+impl<R> IntoFuture for ReplayRequest<R>
+where
+    R: HasPayload,
+    R::Payload: Serialize,
+    Output<R>: DeserializeOwned + Send + 'static,
+{
+    type Output = ResponseResult<Output<R>>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use super::{ErrorKind, Exchange, RecordTransport};
+    use crate::{
+        adaptors::Replay,
+        errors::ErrorResponse,
+        payloads::{DeleteMessage, GetMe, SendMessage},
+        prelude::*,
+        testing::{FakeServer, MockBot},
+        types::{ChatId, MessageId, Seconds},
+        ApiError, RequestError,
+    };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn record_and_replay() {
+        let mock = MockBot::new();
+        mock.respond_error::<SendMessage>(RequestError::from(ApiError::BotBlocked));
+        mock.respond_error::<SendMessage>(RequestError::RetryAfter(Seconds::from_seconds(3)));
+        mock.respond_error::<SendMessage>(RequestError::from(ApiError::Unknown(
+            ErrorResponse::new("Oops"),
+        )));
+
+        let buffer = Buffer::default();
+        let bot = mock.record(buffer.clone());
+
+        let me = bot.get_me().await.unwrap();
+        bot.send_message(ChatId(1), "blocked").await.unwrap_err();
+        bot.send_message(ChatId(1), "flood").await.unwrap_err();
+        bot.send_message(ChatId(1), "unknown").await.unwrap_err();
+        let message = bot.send_message(ChatId(1), "sent").await.unwrap();
+        bot.flush().await;
+
+        let recording = buffer.0.lock().unwrap().clone();
+        assert_eq!(recording.iter().filter(|&&b| b == b'\n').count(), 5);
+
+        let replay = Replay::from_reader(&recording[..]).unwrap();
+        assert_eq!(replay.remaining(), 5);
+
+        assert_eq!(replay.get_me().await.unwrap(), me);
+        assert_matches!(
+            replay.send_message(ChatId(1), "blocked").await,
+            Err(RequestError::Api(ApiError::BotBlocked))
+        );
+        assert_matches!(
+            replay.send_message(ChatId(1), "flood").await,
+            Err(RequestError::RetryAfter(secs)) => assert_eq!(secs.seconds(), 3)
+        );
+        assert_matches!(
+            replay.send_message(ChatId(1), "unknown").await,
+            Err(RequestError::Api(ApiError::Unknown(response))) => assert_eq!(response.description, "Oops")
+        );
+        assert_eq!(replay.send_message(ChatId(1), "different").await.unwrap(), message);
+
+        assert_eq!(replay.remaining(), 0);
+        let mismatches = replay.mismatches();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].0.payload["text"], "sent");
+        assert_eq!(mismatches[0].1["text"], "different");
+
+        assert_matches!(replay.get_me().await, Err(RequestError::Api(ApiError::Unknown(_))));
+    }
+
+    #[tokio::test]
+    async fn method_mismatch() {
+        let replay = Replay::from_reader(
+            &br#"{"method":"GetMe","payload":{},"error":"A network error"}"#[..],
+        )
+        .unwrap();
+
+        assert_matches!(
+            replay.send_message(ChatId(1), "Hi!").await,
+            Err(RequestError::Api(ApiError::Unknown(_)))
+        );
+        assert_eq!(replay.remaining(), 1);
+        assert_matches!(replay.get_me().await, Err(RequestError::Io(_)));
+    }
+
+    #[tokio::test]
+    async fn records_raw_responses() {
+        let server = FakeServer::start().await.unwrap();
+        server.respond_raw::<DeleteMessage>(200, json!({ "ok": true, "result": true, "new": 1 }));
+
+        let transport = RecordTransport::new(reqwest::Client::new());
+        let bot = Bot::with_transport(server.token(), transport.clone()).set_api_url(server.url());
+        // `Throttle` sends requests from a separate task
+        #[cfg(feature = "throttle")]
+        let bot = bot.throttle(crate::adaptors::throttle::Limits::default());
+
+        let buffer = Buffer::default();
+        let bot = bot.record(buffer.clone()).with_raw_responses(&transport);
+        bot.delete_message(ChatId(1), MessageId(1)).await.unwrap();
+        bot.flush().await;
+
+        let recording = buffer.0.lock().unwrap().clone();
+        let exchange: serde_json::Value = serde_json::from_slice(&recording).unwrap();
+        assert_eq!(exchange["response"], json!({ "ok": true, "result": true, "new": 1 }));
+    }
+
+    #[tokio::test]
+    async fn replays_created_exchanges() {
+        let replay = Replay::new([
+            Exchange::new("GetMe", json!({}), json!({ "ok": false, "description": "Oops" })),
+            Exchange::failed("GetMe", json!({}), ErrorKind::Network, "connection reset"),
+        ]);
+
+        assert_matches!(replay.get_me().await, Err(RequestError::Api(ApiError::Unknown(_))));
+        assert_matches!(
+            replay.get_me().await,
+            Err(RequestError::Io(err)) => assert!(err.to_string().contains("connection reset"))
+        );
+    }
+
+    #[tokio::test]
+    async fn replays_errors_of_the_same_kind() {
+        let page = "<html>Bad Gateway</html>";
+        let source = serde_json::from_str::<serde_json::Value>(page).unwrap_err();
+
+        let mock = MockBot::new();
+        mock.respond_error::<GetMe>(RequestError::TimedOut);
+        mock.respond_error::<GetMe>(RequestError::CircuitOpen);
+        mock.respond_error::<GetMe>(RequestError::InvalidJson {
+            source: source.into(),
+            raw: page.into(),
+        });
+        mock.respond_error::<GetMe>(RequestError::RetryAfter(Seconds::from_seconds(3)));
+
+        let buffer = Buffer::default();
+        let bot = mock.record(buffer.clone());
+        for _ in 0..4 {
+            bot.get_me().await.unwrap_err();
+        }
+        bot.flush().await;
+
+        let recording = buffer.0.lock().unwrap().clone();
+        let flood = serde_json::from_slice::<serde_json::Value>(
+            recording.split(|&b| b == b'\n').nth(3).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(flood["response"]["error_code"], 429);
+
+        let replay = Replay::from_reader(&recording[..]).unwrap();
+        assert_matches!(replay.get_me().await, Err(RequestError::TimedOut));
+        assert_matches!(replay.get_me().await, Err(RequestError::CircuitOpen));
+        assert_matches!(
+            replay.get_me().await,
+            Err(RequestError::InvalidJson { raw, .. }) => assert_eq!(&*raw, page)
+        );
+        assert_matches!(replay.get_me().await, Err(RequestError::RetryAfter(_)));
+
+        let network = Replay::from_reader(
+            &br#"{"method":"GetMe","payload":{},"error_kind":"network","error":"A network error"}"#
+                [..],
+        )
+        .unwrap();
+        assert_matches!(network.get_me().await, Err(RequestError::Io(_)));
+    }
+}
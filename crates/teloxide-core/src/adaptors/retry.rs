@@ -0,0 +1,850 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+    time::Duration,
+};
+
+use futures::ready;
+use url::Url;
+
+use crate::{
+    errors::RequestError,
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+// Required to not trigger `clippy::type-complexity` lint
+type Backoff = Arc<dyn Fn(u32) -> Duration + Send + Sync>;
+type RetryIf = Arc<dyn Fn(&RequestError) -> bool + Send + Sync>;
+
+/// Retries failed requests according to a [`RetryPolicy`].
+///
+/// By default network errors, server errors and [`RequestError::RetryAfter`]
+/// are retried up to 3 times, see [`RetryPolicy`] for the details and other
+/// options.
+///
+/// Note that requests which upload files from a stream (see
+/// [`InputFile::read`]) can't be sent more than once and will fail on retry.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{
+///     adaptors::retry::RetryPolicy, errors::RequestError, payloads::SendMessage, prelude::*,
+/// };
+///
+/// # async {
+/// let bot = Bot::from_env().retry(
+///     RetryPolicy::default()
+///         .max_retries(5)
+///         // Also retry timeouts and responses of proxies which are not JSON
+///         .retry_if(RequestError::is_retryable)
+///         // Sending a message twice is worse than not sending it at all
+///         .no_retry::<SendMessage>(),
+/// );
+///
+/// let me = bot.get_me().await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+#[derive(Clone, Debug)]
+pub struct Retry<B> {
+    inner: B,
+    policy: Arc<RetryPolicy>,
+}
+
+/// Policy used by [`Retry`] adaptor to decide whether and when to retry a
+/// failed request.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use teloxide_core::{adaptors::retry::RetryPolicy, payloads::SendPoll};
+///
+/// let policy = RetryPolicy::default()
+///     .max_retries(10)
+///     .backoff(|retry| Duration::from_millis(100) * retry)
+///     .no_retry::<SendPoll>();
+/// # let _ = policy;
+/// ```
+#[must_use]
+#[non_exhaustive]
+pub struct RetryPolicy {
+    /// Maximum number of retries of a single request.
+    pub max_retries: u32,
+
+    /// Delay before a retry, called with the number of the retry, starting
+    /// from 1.
+    ///
+    /// This is not used for [`RequestError::RetryAfter`], which specifies the
+    /// delay itself.
+    pub backoff: Backoff,
+
+    /// Retry [`RequestError::Network`] errors.
+    pub network: bool,
+
+    /// Retry [`RequestError::RetryAfter`] errors.
+    ///
+    /// These are retried even for payloads in [`RetryPolicy::no_retry`],
+    /// since Telegram doesn't handle requests which hit the flood control.
+    pub retry_after: bool,
+
+    /// Retry [`RequestError::Api`] errors with `5xx` error codes.
+    pub server_errors: bool,
+
+    /// Additionally retries errors for which this function returns `true`.
+    pub retry_if: Option<RetryIf>,
+
+    /// Names of payloads (see [`Payload::NAME`]) that should never be retried,
+    /// except for [`RequestError::RetryAfter`] errors.
+    pub no_retry: HashSet<&'static str>,
+}
+
+impl<B> Retry<B> {
+    /// Creates new retrying adaptor.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::retry`] instead.
+    ///
+    /// [`RequesterExt::retry`]: crate::requests::RequesterExt::retry
+    pub fn new(inner: B, policy: RetryPolicy) -> Self {
+        Self { inner, policy: Arc::new(policy) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the policy used by this adaptor.
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl RetryPolicy {
+    pub fn max_retries(mut self, val: u32) -> Self {
+        self.max_retries = val;
+        self
+    }
+
+    /// Sets a function, which returns a delay before a retry, given the number
+    /// of the retry, starting from 1.
+    ///
+    /// `teloxide::backoff::exponential_backoff_strategy` can be used here, for
+    /// example.
+    pub fn backoff<F>(mut self, val: F) -> Self
+    where
+        F: Fn(u32) -> Duration + Send + Sync + 'static,
+    {
+        self.backoff = Arc::new(val);
+        self
+    }
+
+    pub fn no_network(mut self) -> Self {
+        self.network = false;
+        self
+    }
+
+    pub fn no_retry_after(mut self) -> Self {
+        self.retry_after = false;
+        self
+    }
+
+    pub fn no_server_errors(mut self) -> Self {
+        self.server_errors = false;
+        self
+    }
+
+    /// Sets a function, which returns `true` for errors which should be
+    /// retried in addition to the ones enabled by other options.
+    ///
+    /// For example, [`RequestError::is_retryable`] can be used to retry all
+    /// transient errors, and [`RequestError::Api`] errors can be matched by
+    /// their [`ApiError`] or error code.
+    ///
+    /// [`ApiError`]: crate::ApiError
+    pub fn retry_if<F>(mut self, val: F) -> Self
+    where
+        F: Fn(&RequestError) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Some(Arc::new(val));
+        self
+    }
+
+    /// Disables retries for requests with payload `P`.
+    ///
+    /// This is useful for requests that are not idempotent, for example
+    /// [`SendMessage`] may send the message twice, if a network error
+    /// happened after Telegram received the request.
+    ///
+    /// [`RequestError::RetryAfter`] errors are still retried, since Telegram
+    /// doesn't handle requests which hit the flood control.
+    ///
+    /// [`SendMessage`]: crate::payloads::SendMessage
+    pub fn no_retry<P: Payload>(mut self) -> Self {
+        self.no_retry.insert(P::NAME);
+        self
+    }
+
+    /// Returns delay before the next retry of a request with payload `P`, or
+    /// `None` if it shouldn't be retried.
+    ///
+    /// `retry` is the number of the next retry, starting from 1.
+    pub fn retry_delay<P: Payload>(&self, error: &RequestError, retry: u32) -> Option<Duration> {
+        if retry > self.max_retries {
+            return None;
+        }
+
+        // Telegram didn't handle the request, so it's safe to send it again
+        if let RequestError::RetryAfter(seconds) = error {
+            return self.retry_after.then(|| seconds.duration());
+        }
+
+        if self.no_retry.contains(P::NAME) {
+            return None;
+        }
+
+        let retry_if = self.retry_if.as_ref().is_some_and(|retry_if| retry_if(error));
+        let retryable = match error {
+            RequestError::Network(_) => self.network,
+            RequestError::Api(_) => {
+                self.server_errors && error.error_code().is_some_and(|code| code >= 500)
+            }
+            _ => false,
+        };
+
+        (retryable || retry_if).then(|| (self.backoff)(retry))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            // 1s, 2s, 4s, ..., 64s, 64s, ...
+            backoff: Arc::new(|retry| Duration::from_secs(1 << retry.saturating_sub(1).min(6))),
+            network: true,
+            retry_after: true,
+            server_errors: true,
+            retry_if: None,
+            no_retry: HashSet::new(),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("network", &self.network)
+            .field("retry_after", &self.retry_after)
+            .field("server_errors", &self.server_errors)
+            .field("no_retry", &self.no_retry)
+            .finish_non_exhaustive()
+    }
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        RetryRequest<B::$T>
+    };
+}
+
+macro_rules! fwd_inner {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        RetryRequest {
+            inner: Arc::new($this.inner().$m($($arg),*)),
+            policy: Arc::clone(&$this.policy),
+        }
+    };
+}
+
+impl<B> Requester for Retry<B>
+where
+    B: Requester<Err = RequestError>,
+
+    B::GetUpdates: Clone + Send + Sync,
+    B::SetWebhook: Clone + Send + Sync,
+    B::DeleteWebhook: Clone + Send + Sync,
+    B::GetWebhookInfo: Clone + Send + Sync,
+    B::GetMe: Clone + Send + Sync,
+    B::LogOut: Clone + Send + Sync,
+    B::Close: Clone + Send + Sync,
+    B::SendMessage: Clone + Send + Sync,
+    B::ForwardMessage: Clone + Send + Sync,
+    B::ForwardMessages: Clone + Send + Sync,
+    B::CopyMessage: Clone + Send + Sync,
+    B::CopyMessages: Clone + Send + Sync,
+    B::SendPhoto: Clone + Send + Sync,
+    B::SendAudio: Clone + Send + Sync,
+    B::SendDocument: Clone + Send + Sync,
+    B::SendVideo: Clone + Send + Sync,
+    B::SendAnimation: Clone + Send + Sync,
+    B::SendVoice: Clone + Send + Sync,
+    B::SendVideoNote: Clone + Send + Sync,
+    B::SendPaidMedia: Clone + Send + Sync,
+    B::SendMediaGroup: Clone + Send + Sync,
+    B::SendLocation: Clone + Send + Sync,
+    B::EditMessageLiveLocation: Clone + Send + Sync,
+    B::EditMessageLiveLocationInline: Clone + Send + Sync,
+    B::StopMessageLiveLocation: Clone + Send + Sync,
+    B::StopMessageLiveLocationInline: Clone + Send + Sync,
+    B::EditMessageChecklist: Clone + Send + Sync,
+    B::SendVenue: Clone + Send + Sync,
+    B::SendContact: Clone + Send + Sync,
+    B::SendPoll: Clone + Send + Sync,
+    B::SendChecklist: Clone + Send + Sync,
+    B::SendDice: Clone + Send + Sync,
+    B::SendChatAction: Clone + Send + Sync,
+    B::SetMessageReaction: Clone + Send + Sync,
+    B::GetUserProfilePhotos: Clone + Send + Sync,
+    B::SetUserEmojiStatus: Clone + Send + Sync,
+    B::GetFile: Clone + Send + Sync,
+    B::BanChatMember: Clone + Send + Sync,
+    B::KickChatMember: Clone + Send + Sync,
+    B::UnbanChatMember: Clone + Send + Sync,
+    B::RestrictChatMember: Clone + Send + Sync,
+    B::PromoteChatMember: Clone + Send + Sync,
+    B::SetChatAdministratorCustomTitle: Clone + Send + Sync,
+    B::BanChatSenderChat: Clone + Send + Sync,
+    B::UnbanChatSenderChat: Clone + Send + Sync,
+    B::SetChatPermissions: Clone + Send + Sync,
+    B::ExportChatInviteLink: Clone + Send + Sync,
+    B::CreateChatInviteLink: Clone + Send + Sync,
+    B::EditChatInviteLink: Clone + Send + Sync,
+    B::CreateChatSubscriptionInviteLink: Clone + Send + Sync,
+    B::EditChatSubscriptionInviteLink: Clone + Send + Sync,
+    B::RevokeChatInviteLink: Clone + Send + Sync,
+    B::ApproveChatJoinRequest: Clone + Send + Sync,
+    B::DeclineChatJoinRequest: Clone + Send + Sync,
+    B::SetChatPhoto: Clone + Send + Sync,
+    B::DeleteChatPhoto: Clone + Send + Sync,
+    B::SetChatTitle: Clone + Send + Sync,
+    B::SetChatDescription: Clone + Send + Sync,
+    B::PinChatMessage: Clone + Send + Sync,
+    B::UnpinChatMessage: Clone + Send + Sync,
+    B::UnpinAllChatMessages: Clone + Send + Sync,
+    B::LeaveChat: Clone + Send + Sync,
+    B::GetChat: Clone + Send + Sync,
+    B::GetChatAdministrators: Clone + Send + Sync,
+    B::GetChatMemberCount: Clone + Send + Sync,
+    B::GetChatMembersCount: Clone + Send + Sync,
+    B::GetChatMember: Clone + Send + Sync,
+    B::SetChatStickerSet: Clone + Send + Sync,
+    B::DeleteChatStickerSet: Clone + Send + Sync,
+    B::GetForumTopicIconStickers: Clone + Send + Sync,
+    B::CreateForumTopic: Clone + Send + Sync,
+    B::EditForumTopic: Clone + Send + Sync,
+    B::CloseForumTopic: Clone + Send + Sync,
+    B::ReopenForumTopic: Clone + Send + Sync,
+    B::DeleteForumTopic: Clone + Send + Sync,
+    B::UnpinAllForumTopicMessages: Clone + Send + Sync,
+    B::EditGeneralForumTopic: Clone + Send + Sync,
+    B::CloseGeneralForumTopic: Clone + Send + Sync,
+    B::ReopenGeneralForumTopic: Clone + Send + Sync,
+    B::HideGeneralForumTopic: Clone + Send + Sync,
+    B::UnhideGeneralForumTopic: Clone + Send + Sync,
+    B::UnpinAllGeneralForumTopicMessages: Clone + Send + Sync,
+    B::AnswerCallbackQuery: Clone + Send + Sync,
+    B::GetUserChatBoosts: Clone + Send + Sync,
+    B::SetMyCommands: Clone + Send + Sync,
+    B::GetBusinessConnection: Clone + Send + Sync,
+    B::GetMyCommands: Clone + Send + Sync,
+    B::SetMyName: Clone + Send + Sync,
+    B::GetMyName: Clone + Send + Sync,
+    B::SetMyDescription: Clone + Send + Sync,
+    B::GetMyDescription: Clone + Send + Sync,
+    B::SetMyShortDescription: Clone + Send + Sync,
+    B::GetMyShortDescription: Clone + Send + Sync,
+    B::SetChatMenuButton: Clone + Send + Sync,
+    B::GetChatMenuButton: Clone + Send + Sync,
+    B::SetMyDefaultAdministratorRights: Clone + Send + Sync,
+    B::GetMyDefaultAdministratorRights: Clone + Send + Sync,
+    B::DeleteMyCommands: Clone + Send + Sync,
+    B::AnswerInlineQuery: Clone + Send + Sync,
+    B::AnswerWebAppQuery: Clone + Send + Sync,
+    B::SavePreparedInlineMessage: Clone + Send + Sync,
+    B::EditMessageText: Clone + Send + Sync,
+    B::EditMessageTextInline: Clone + Send + Sync,
+    B::EditMessageCaption: Clone + Send + Sync,
+    B::EditMessageCaptionInline: Clone + Send + Sync,
+    B::EditMessageMedia: Clone + Send + Sync,
+    B::EditMessageMediaInline: Clone + Send + Sync,
+    B::EditMessageReplyMarkup: Clone + Send + Sync,
+    B::EditMessageReplyMarkupInline: Clone + Send + Sync,
+    B::StopPoll: Clone + Send + Sync,
+    B::ApproveSuggestedPost: Clone + Send + Sync,
+    B::DeclineSuggestedPost: Clone + Send + Sync,
+    B::DeleteMessage: Clone + Send + Sync,
+    B::DeleteMessages: Clone + Send + Sync,
+    B::SendSticker: Clone + Send + Sync,
+    B::GetStickerSet: Clone + Send + Sync,
+    B::GetCustomEmojiStickers: Clone + Send + Sync,
+    B::UploadStickerFile: Clone + Send + Sync,
+    B::CreateNewStickerSet: Clone + Send + Sync,
+    B::AddStickerToSet: Clone + Send + Sync,
+    B::SetStickerPositionInSet: Clone + Send + Sync,
+    B::DeleteStickerFromSet: Clone + Send + Sync,
+    B::ReplaceStickerInSet: Clone + Send + Sync,
+    B::SetStickerSetThumbnail: Clone + Send + Sync,
+    B::SetCustomEmojiStickerSetThumbnail: Clone + Send + Sync,
+    B::SetStickerSetTitle: Clone + Send + Sync,
+    B::DeleteStickerSet: Clone + Send + Sync,
+    B::SetStickerEmojiList: Clone + Send + Sync,
+    B::SetStickerKeywords: Clone + Send + Sync,
+    B::SetStickerMaskPosition: Clone + Send + Sync,
+    B::GetAvailableGifts: Clone + Send + Sync,
+    B::SendGift: Clone + Send + Sync,
+    B::SendGiftChat: Clone + Send + Sync,
+    B::GiftPremiumSubscription: Clone + Send + Sync,
+    B::VerifyUser: Clone + Send + Sync,
+    B::VerifyChat: Clone + Send + Sync,
+    B::RemoveUserVerification: Clone + Send + Sync,
+    B::RemoveChatVerification: Clone + Send + Sync,
+    B::ReadBusinessMessage: Clone + Send + Sync,
+    B::DeleteBusinessMessages: Clone + Send + Sync,
+    B::SetBusinessAccountName: Clone + Send + Sync,
+    B::SetBusinessAccountUsername: Clone + Send + Sync,
+    B::SetBusinessAccountBio: Clone + Send + Sync,
+    B::SetBusinessAccountProfilePhoto: Clone + Send + Sync,
+    B::RemoveBusinessAccountProfilePhoto: Clone + Send + Sync,
+    B::SetBusinessAccountGiftSettings: Clone + Send + Sync,
+    B::GetBusinessAccountStarBalance: Clone + Send + Sync,
+    B::TransferBusinessAccountStars: Clone + Send + Sync,
+    B::GetBusinessAccountGifts: Clone + Send + Sync,
+    B::ConvertGiftToStars: Clone + Send + Sync,
+    B::UpgradeGift: Clone + Send + Sync,
+    B::TransferGift: Clone + Send + Sync,
+    B::PostStory: Clone + Send + Sync,
+    B::EditStory: Clone + Send + Sync,
+    B::DeleteStory: Clone + Send + Sync,
+    B::SendInvoice: Clone + Send + Sync,
+    B::CreateInvoiceLink: Clone + Send + Sync,
+    B::AnswerShippingQuery: Clone + Send + Sync,
+    B::AnswerPreCheckoutQuery: Clone + Send + Sync,
+    B::GetMyStarBalance: Clone + Send + Sync,
+    B::GetStarTransactions: Clone + Send + Sync,
+    B::RefundStarPayment: Clone + Send + Sync,
+    B::EditUserStarSubscription: Clone + Send + Sync,
+    B::SetPassportDataErrors: Clone + Send + Sync,
+    B::SendGame: Clone + Send + Sync,
+    B::SetGameScore: Clone + Send + Sync,
+    B::SetGameScoreInline: Clone + Send + Sync,
+    B::GetGameHighScores: Clone + Send + Sync,
+{
+    type Err = RequestError;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fwd_inner, fty
+    }
+}
+
+download_forward! {
+    B
+    Retry<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`Retry`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct RetryRequest<R> {
+    // `Arc` allows to resend the request without cloning it
+    inner: Arc<R>,
+    policy: Arc<RetryPolicy>,
+}
+
+impl<R> HasPayload for RetryRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    type Payload = R::Payload;
+
+    /// Note that if this request was already sent via `send_ref` and it
+    /// didn't yet complete, this method will clone the underlying request.
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        Arc::make_mut(&mut self.inner).payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for RetryRequest<R>
+where
+    R: Request<Err = RequestError> + Clone + Send + Sync,
+{
+    type Err = RequestError;
+
+    type Send = RetrySend<R>;
+
+    type SendRef = RetrySend<R>;
+
+    fn send(self) -> Self::Send {
+        RetrySend::new(self.inner, self.policy)
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        RetrySend::new(Arc::clone(&self.inner), Arc::clone(&self.policy))
+    }
+}
+
+impl<R> IntoFuture for RetryRequest<R>
+where
+    R: Request<Err = RequestError> + Clone + Send + Sync,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`RetryRequest`]s.
+#[pin_project::pin_project]
+pub struct RetrySend<R: Request> {
+    request: Arc<R>,
+    policy: Arc<RetryPolicy>,
+    retry: u32,
+    #[pin]
+    state: SendState<R::SendRef>,
+}
+
+#[pin_project::pin_project(project = SendStateProj)]
+enum SendState<F> {
+    Sending(#[pin] F),
+    Waiting(#[pin] tokio::time::Sleep),
+}
+
+impl<R: Request> RetrySend<R> {
+    fn new(request: Arc<R>, policy: Arc<RetryPolicy>) -> Self {
+        let state = SendState::Sending(request.send_ref());
+        Self { request, policy, retry: 0, state }
+    }
+}
+
+impl<R> Future for RetrySend<R>
+where
+    R: Request<Err = RequestError>,
+{
+    type Output = Result<Output<R>, RequestError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+
+        loop {
+            match this.state.as_mut().project() {
+                SendStateProj::Sending(fut) => {
+                    let error = match ready!(fut.poll(cx)) {
+                        Ok(output) => return Poll::Ready(Ok(output)),
+                        Err(error) => error,
+                    };
+
+                    *this.retry += 1;
+                    let Some(delay) = this.policy.retry_delay::<R::Payload>(&error, *this.retry)
+                    else {
+                        return Poll::Ready(Err(error));
+                    };
+
+                    log::warn!(
+                        "`{}` request failed: {error}; retrying in {delay:?} ({}/{})",
+                        R::Payload::NAME,
+                        this.retry,
+                        this.policy.max_retries,
+                    );
+                    this.state.set(SendState::Waiting(tokio::time::sleep(delay)));
+                }
+                SendStateProj::Waiting(sleep) => {
+                    ready!(sleep.poll(cx));
+                    this.state.set(SendState::Sending(this.request.send_ref()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::time::Duration;
+
+    use cool_asserts::assert_matches;
+    use serde_json::json;
+
+    use crate::{
+        adaptors::retry::RetryPolicy,
+        errors::{ApiError, ErrorResponse, RequestError},
+        payloads::{GetMe, SendMessage},
+        prelude::*,
+        testing::FakeServer,
+        types::ChatId,
+    };
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default().backoff(|_| Duration::ZERO)
+    }
+
+    fn error(code: u16, description: &str) -> serde_json::Value {
+        json!({ "ok": false, "error_code": code, "description": description })
+    }
+
+    fn retry_after_0() -> serde_json::Value {
+        json!({
+            "ok": false,
+            "error_code": 429,
+            "description": "Too Many Requests: retry after 0",
+            "parameters": { "retry_after": 0 },
+        })
+    }
+
+    #[tokio::test]
+    async fn retries_errors() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server
+            .bot()
+            .retry(policy().retry_if(|err| {
+                matches!(err, RequestError::Api(ApiError::Unknown(response)) if response.description == "Bad Request: flaky")
+            }));
+
+        server.respond_raw::<GetMe>(429, retry_after_0());
+        server.respond_raw::<GetMe>(400, error(400, "Bad Request: flaky"));
+
+        assert_eq!(bot.get_me().await.unwrap().id, server.me().id);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot().retry(policy().max_retries(1).no_retry::<SendMessage>());
+
+        server.respond_raw::<GetMe>(400, error(400, "Bad Request: flaky"));
+        assert_matches!(bot.get_me().await, Err(RequestError::Api(ApiError::Unknown(_))));
+        assert_eq!(server.requests().len(), 1);
+
+        for _ in 0..2 {
+            server.respond_raw::<GetMe>(429, retry_after_0());
+        }
+        assert_matches!(bot.get_me().await, Err(RequestError::RetryAfter(_)));
+        assert_eq!(server.requests().len(), 3);
+
+        // Requests which hit the flood control are retried even if they
+        // shouldn't be retried otherwise
+        server.clear_requests();
+        server.respond_raw::<SendMessage>(429, retry_after_0());
+        assert_matches!(
+            bot.send_message(ChatId(1), "hi").await,
+            Err(RequestError::Api(ApiError::ChatNotFound))
+        );
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[test]
+    fn default_backoff() {
+        let policy = RetryPolicy::default().max_retries(10);
+        let io = RequestError::Io(std::sync::Arc::new(std::io::Error::other("")));
+        assert_eq!(policy.retry_delay::<GetMe>(&io, 1), None);
+
+        let api = RequestError::from(ApiError::BotBlocked);
+        assert_eq!(policy.retry_delay::<GetMe>(&api, 1), None);
+
+        let policy = policy.retry_if(|err| matches!(err, RequestError::Api(ApiError::BotBlocked)));
+        let delays = [1, 2, 3, 8, 10, 11]
+            .map(|retry| policy.retry_delay::<GetMe>(&api, retry).map(|d| d.as_secs()));
+        assert_eq!(delays, [Some(1), Some(2), Some(4), Some(64), Some(64), None]);
+    }
+
+    #[test]
+    fn server_errors() {
+        let policy = RetryPolicy::default().no_retry::<SendMessage>();
+        let response = ErrorResponse {
+            error_code: Some(502),
+            description: "Bad Gateway".to_owned(),
+            parameters: None,
+        };
+        let bad_gateway = RequestError::from(ApiError::Unknown(response));
+
+        assert_eq!(policy.retry_delay::<GetMe>(&bad_gateway, 1), Some(Duration::from_secs(1)));
+        assert_eq!(policy.retry_delay::<SendMessage>(&bad_gateway, 1), None);
+        assert_eq!(policy.no_server_errors().retry_delay::<GetMe>(&bad_gateway, 1), None);
+    }
+}
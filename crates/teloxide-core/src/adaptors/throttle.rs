@@ -1,3 +1,14 @@
+/// `Backend` trait and `InMemBackend`
+mod backend;
+/// Per-chat queues of the worker
+mod fair_queue;
+/// `Priority` and `QueueSizes` structures
+mod priority;
+/// Queues of the worker and the state shared with `Throttle`
+mod queues;
+/// `RedisBackend`
+#[cfg(feature = "throttle_redis")]
+mod redis;
 /// `ThrottlingRequest` and `ThrottlingSend` structures
 mod request;
 /// Lock that allows requests to wait until they are allowed to be sent
@@ -10,8 +21,12 @@ mod settings;
 mod worker;
 
 use std::{
+    collections::HashMap,
     future::Future,
     hash::{Hash, Hasher},
+    mem,
+    sync::Arc,
+    time::Duration,
 };
 
 use tokio::sync::{
@@ -22,10 +37,15 @@ use tokio::sync::{
 use crate::{errors::AsResponseParameters, requests::Requester, types::*};
 
 use self::{
-    request_lock::{channel, RequestLock},
-    worker::{worker, FreezeUntil, InfoMessage},
+    queues::Queues,
+    request_lock::channel,
+    worker::{worker, FreezeUntil, InfoMessage, QueuedRequest},
 };
 
+pub use backend::{Acquired, Backend, InMemBackend, PendingRequest};
+pub use priority::{Priority, QueueSizes};
+#[cfg(feature = "throttle_redis")]
+pub use redis::RedisBackend;
 pub use request::{ThrottlingRequest, ThrottlingSend};
 pub use settings::{Limits, Settings};
 
@@ -37,8 +57,12 @@ pub use settings::{Limits, Settings};
 /// order.
 ///
 /// This bot wrapper automatically checks for limits, suspending requests until
-/// they could be sent without exceeding limits (request order in chats is not
-/// changed).
+/// they could be sent without exceeding limits. Requests to a chat are sent in
+/// the order they were made, unless they have different [priorities].
+///
+/// Waiting requests are queued separately for every chat and chats take turns
+/// in a round-robin order, so a long burst of requests to one chat doesn't
+/// delay requests to other chats.
 ///
 /// It's recommended to use this wrapper before other wrappers (i.e.:
 /// `SomeWrapper<Throttle<Bot>>` not `Throttle<SomeWrapper<Bot>>`) because if
@@ -46,6 +70,7 @@ pub use settings::{Limits, Settings};
 /// usage.
 ///
 /// [limits]: https://core.telegram.org/bots/faq#my-bot-is-hitting-limits-how-do-i-avoid-this
+/// [priorities]: Priority
 ///
 /// ## Examples
 ///
@@ -70,11 +95,45 @@ pub use settings::{Limits, Settings};
 ///
 /// As such, we encourage not to use `ChatId::ChannelUsername(u)` with this bot
 /// wrapper.
+///
+/// ## Throttled methods
+///
+/// Only requests which send messages (e.g. `send_message`, `copy_message` or
+/// `forward_messages`) are throttled, by default they all share the same
+/// [`Limits`]. Requests with `allow_paid_broadcast` use the
+/// [`messages_per_sec_paid_broadcast`] limit instead of
+/// `messages_per_sec_overall`. A method can have its own limits, see
+/// [`Settings::method_limits`]. Other requests are sent immediately.
+///
+/// [`messages_per_sec_paid_broadcast`]: Limits::messages_per_sec_paid_broadcast
+///
+/// ## Priorities
+///
+/// Requests can have different [`Priority`]s, e.g. to send replies to users
+/// before broadcast messages. Requests of higher priority are sent first, lower
+/// priority requests only use the budget left by them. See [`Priority`] for
+/// more.
+///
+/// ## Inspecting and cancelling queued requests
+///
+/// [`Throttle::queue_sizes`] and [`Throttle::chat_queue_sizes`] return the
+/// number of requests waiting to be sent, [`Throttle::frozen_for`] tells if
+/// the bot is frozen because of a `RetryAfter` error. Requests waiting to be
+/// sent to a chat can be cancelled with [`Throttle::cancel_chat`], e.g. when
+/// the bot was blocked by the user.
+///
+/// ## Sharing limits between processes
+///
+/// By default every [`Throttle`] keeps the history of sent requests in memory
+/// ([`InMemBackend`]), so several processes using the same bot token don't
+/// know about each other's requests. To respect the limits together, they can
+/// share a [`Backend`] (e.g. [`RedisBackend`]), see [`Settings::backend`].
+///
+/// [`RedisBackend`]: crate::adaptors::throttle::RedisBackend
 #[derive(Clone, Debug)]
 pub struct Throttle<B> {
     bot: B,
-    // `RequestLock` allows to unlock requests (allowing them to be sent).
-    queue: mpsc::Sender<(ChatIdHash, RequestLock)>,
+    queues: Arc<Queues>,
     info_tx: mpsc::Sender<InfoMessage>,
 }
 
@@ -96,16 +155,29 @@ impl<B> Throttle<B> {
     ///
     /// Note: [`Throttle`] will only send requests if returned worker is
     /// polled/spawned/awaited.
-    pub fn with_settings(bot: B, settings: Settings) -> (Self, impl Future<Output = ()>)
+    pub fn with_settings(bot: B, mut settings: Settings) -> (Self, impl Future<Output = ()>)
     where
         B: Requester + Clone,
         B::Err: AsResponseParameters,
     {
-        let (tx, rx) = mpsc::channel(settings.limits.messages_per_sec_overall as usize);
+        let capacity = settings.limits.messages_per_sec_overall as usize;
+        let (high_tx, high_rx) = mpsc::channel(capacity);
+        let (normal_tx, normal_rx) = mpsc::channel(capacity);
+        let (low_tx, low_rx) = mpsc::channel(capacity);
         let (info_tx, info_rx) = mpsc::channel(2);
 
-        let worker = worker(settings, rx, info_rx, bot.clone());
-        let this = Self { bot, queue: tx, info_tx };
+        let method_priorities = mem::take(&mut settings.method_priorities);
+        let queues = Arc::new(Queues::new([high_tx, normal_tx, low_tx], method_priorities));
+
+        // The worker must not keep the queues alive, otherwise it would never stop
+        let worker = worker(
+            settings,
+            [high_rx, normal_rx, low_rx],
+            info_rx,
+            Arc::downgrade(&queues),
+            bot.clone(),
+        );
+        let this = Self { bot, queues, info_tx };
 
         (this, worker)
     }
@@ -151,6 +223,39 @@ impl<B> Throttle<B> {
         self.bot
     }
 
+    /// Returns the number of requests of each [`Priority`] which are waiting
+    /// to be sent.
+    pub fn queue_sizes(&self) -> QueueSizes {
+        self.queues.sizes()
+    }
+
+    /// Returns the number of requests waiting to be sent to each chat.
+    ///
+    /// Chats without waiting requests are not included.
+    pub fn chat_queue_sizes(&self) -> HashMap<ChatIdHash, usize> {
+        self.queues.chat_sizes()
+    }
+
+    /// Returns how long the bot is going to be frozen for, because Telegram
+    /// returned [`RetryAfter`] error, or `None` if it's not frozen.
+    ///
+    /// [`RetryAfter`]: crate::RequestError::RetryAfter
+    pub fn frozen_for(&self) -> Option<Duration> {
+        self.queues.frozen_for()
+    }
+
+    /// Cancels all requests to `chat` which are waiting to be sent.
+    ///
+    /// Cancelled requests return [`RequestError::Cancelled`] error. Requests
+    /// which have already been allowed to be sent are not affected.
+    ///
+    /// Returns the number of cancelled requests.
+    ///
+    /// [`RequestError::Cancelled`]: crate::RequestError::Cancelled
+    pub fn cancel_chat(&self, chat: impl Into<Recipient>) -> usize {
+        self.queues.cancel((&chat.into()).into())
+    }
+
     /// Returns currently used [`Limits`].
     pub async fn limits(&self) -> Limits {
         const WORKER_DIED: &str = "worker died before last `Throttle` instance";
@@ -174,18 +279,27 @@ impl<B> Throttle<B> {
     }
 }
 
-/// An ID used in the worker.
+/// An ID used in the worker and [`Backend`]s.
 ///
 /// It is used instead of `ChatId` to make copying cheap even in case of
 /// usernames. (It is just a hashed username.)
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
-enum ChatIdHash {
+pub enum ChatIdHash {
+    /// A chat identified by its ID.
     Id(ChatId),
+
+    /// A channel identified by its `@username`, this is a hash of the
+    /// username.
+    ///
+    /// The hash is only guaranteed to be the same for binaries built with the
+    /// same Rust version.
     ChannelUsernameHash(u64),
 }
 
 impl ChatIdHash {
-    fn is_channel_or_supergroup(&self) -> bool {
+    /// Returns `true` if this is an ID of a channel or a supergroup.
+    #[must_use]
+    pub fn is_channel_or_supergroup(&self) -> bool {
         match self {
             &Self::Id(id) => id.is_channel_or_supergroup(),
             Self::ChannelUsernameHash(_) => true,
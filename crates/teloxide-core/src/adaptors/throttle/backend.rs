@@ -0,0 +1,329 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future::{ready, BoxFuture};
+
+use crate::adaptors::throttle::{ChatIdHash, Limits};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const SECOND: Duration = Duration::from_secs(1);
+
+/// Bookkeeping of sent requests used by the [`Throttle`] worker to check the
+/// limits.
+///
+/// The default backend, [`InMemBackend`], keeps the history of requests in
+/// memory, so it only knows about requests sent by a single [`Throttle`]. If
+/// several processes use the same bot token, they can share a backend (for
+/// example [`RedisBackend`]) to respect the limits together.
+///
+/// Implementations must never allow exceeding the [`Limits`]. If a backend
+/// can fail (e.g. because of a network error), it should log the error and
+/// deny all requests, the worker will ask it again soon.
+///
+/// [`Throttle`]: crate::adaptors::Throttle
+/// [`RedisBackend`]: crate::adaptors::throttle::RedisBackend
+pub trait Backend: Send + Sync {
+    /// Decides which requests can be sent right now and records them as sent.
+    ///
+    /// `requests` are the pending requests, in the order they should be
+    /// granted. The same chat may appear several times. Requests must be
+    /// granted in order: once a request is denied, all the following requests
+    /// to the same chat must be denied too, so that the order of requests in
+    /// a chat is not changed.
+    ///
+    /// Paid broadcasts are counted together with the other requests which
+    /// share the limits, they are only allowed to exceed
+    /// [`Limits::messages_per_sec_overall`] up to
+    /// [`Limits::messages_per_sec_paid_broadcast`].
+    fn acquire<'a>(&'a self, requests: &'a [PendingRequest<'a>]) -> BoxFuture<'a, Acquired>;
+
+    /// Forbids sending any requests for `after`, because Telegram returned
+    /// [`RetryAfter`] error.
+    ///
+    /// [`RetryAfter`]: crate::RequestError::RetryAfter
+    fn freeze(&self, after: Duration) -> BoxFuture<'_, ()>;
+}
+
+/// A request waiting to be sent, see [`Backend::acquire`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct PendingRequest<'a> {
+    /// The chat the request is sent to.
+    pub chat: ChatIdHash,
+
+    /// Name of the method (e.g. `"CopyMessage"`), if it has its own limits
+    /// (see [`Settings::method_limits`]). Requests of such methods are counted
+    /// separately from other requests.
+    ///
+    /// `None` for messages, which share the same limits.
+    ///
+    /// [`Settings::method_limits`]: crate::adaptors::throttle::Settings::method_limits
+    pub method: Option<&'static str>,
+
+    /// `true` if the request is a paid broadcast, see
+    /// [`Limits::messages_per_sec_paid_broadcast`].
+    pub paid_broadcast: bool,
+
+    /// Limits of the request.
+    pub limits: &'a Limits,
+}
+
+/// Result of [`Backend::acquire`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Acquired {
+    /// The bot is frozen for the given duration, no requests can be sent.
+    Frozen(Duration),
+
+    /// `granted[i]` is `true` if `requests[i]` can be sent.
+    Granted(Vec<bool>),
+}
+
+impl PendingRequest<'_> {
+    /// Returns the number of requests per second allowed for all chats,
+    /// including the ones already sent.
+    #[must_use]
+    pub fn overall_limit(&self) -> u32 {
+        if self.paid_broadcast {
+            self.limits.messages_per_sec_paid_broadcast
+        } else {
+            self.limits.messages_per_sec_overall
+        }
+    }
+
+    /// Returns the number of requests per minute allowed for the chat.
+    #[must_use]
+    pub fn per_min_limit(&self) -> u32 {
+        if self.chat.is_channel_or_supergroup() {
+            self.limits.messages_per_min_channel_or_supergroup
+        } else {
+            self.limits.messages_per_min_chat
+        }
+    }
+}
+
+/// A [`Backend`] which keeps the history of requests in memory.
+#[derive(Debug, Default)]
+pub struct InMemBackend {
+    state: Mutex<InMemState>,
+}
+
+type RequestsSent = u32;
+
+#[derive(Debug, Default)]
+struct InMemState {
+    messages: History,
+    methods: HashMap<&'static str, History>,
+    frozen_until: Option<Instant>,
+}
+
+// History of requests which share limits
+#[derive(Debug, Default)]
+struct History {
+    // Chat and time of the request
+    sent: VecDeque<(ChatIdHash, Instant)>,
+    // Number of requests sent to chats in the last minute, kept in sync with
+    // `sent`
+    per_min: HashMap<ChatIdHash, RequestsSent>,
+}
+
+// Requests sent in the last second
+#[derive(Default)]
+struct Window {
+    overall: RequestsSent,
+    per_chat: HashMap<ChatIdHash, RequestsSent>,
+}
+
+impl InMemBackend {
+    /// Creates new empty backend.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Backend for InMemBackend {
+    fn acquire<'a>(&'a self, requests: &'a [PendingRequest<'a>]) -> BoxFuture<'a, Acquired> {
+        let mut state = self.state.lock().unwrap();
+        Box::pin(ready(state.acquire(requests)))
+    }
+
+    fn freeze(&self, after: Duration) -> BoxFuture<'_, ()> {
+        let mut state = self.state.lock().unwrap();
+        let until = Instant::now() + after;
+        state.frozen_until = Some(state.frozen_until.map_or(until, |prev| prev.max(until)));
+        Box::pin(ready(()))
+    }
+}
+
+impl InMemState {
+    fn acquire(&mut self, requests: &[PendingRequest<'_>]) -> Acquired {
+        let now = Instant::now();
+
+        if let Some(until) = self.frozen_until {
+            if until > now {
+                return Acquired::Frozen(until - now);
+            }
+            self.frozen_until = None;
+        }
+
+        let min_back = now.checked_sub(MINUTE).unwrap_or(now);
+        let sec_back = now.checked_sub(SECOND).unwrap_or(now);
+
+        let mut windows = HashMap::<Option<&'static str>, Window>::new();
+        // Chats with a denied request, the following requests to them must
+        // wait, so that they are not sent out of order
+        let mut blocked = HashSet::new();
+
+        let granted = requests
+            .iter()
+            .map(|request| {
+                if blocked.contains(&request.chat) {
+                    return false;
+                }
+
+                let history = match request.method {
+                    None => &mut self.messages,
+                    Some(method) => self.methods.entry(method).or_default(),
+                };
+                let window = windows
+                    .entry(request.method)
+                    .or_insert_with(|| history.window(min_back, sec_back));
+
+                let requests_sent_per_sec_count =
+                    window.per_chat.get(&request.chat).copied().unwrap_or(0);
+                let requests_sent_per_min_count =
+                    history.per_min.get(&request.chat).copied().unwrap_or(0);
+
+                let limits_not_exceeded = window.overall < request.overall_limit()
+                    && requests_sent_per_sec_count < request.limits.messages_per_sec_chat
+                    && requests_sent_per_min_count < request.per_min_limit();
+
+                if limits_not_exceeded {
+                    window.overall += 1;
+                    *window.per_chat.entry(request.chat).or_insert(0) += 1;
+                    *history.per_min.entry(request.chat).or_insert(0) += 1;
+                    history.sent.push_back((request.chat, Instant::now()));
+                } else {
+                    blocked.insert(request.chat);
+                }
+
+                limits_not_exceeded
+            })
+            .collect();
+
+        Acquired::Granted(granted)
+    }
+}
+
+impl History {
+    fn window(&mut self, min_back: Instant, sec_back: Instant) -> Window {
+        // make history and per_min up-to-date
+        while let Some((_, time)) = self.sent.front() {
+            // history is sorted, we found first up-to-date thing
+            if time >= &min_back {
+                break;
+            }
+
+            if let Some((chat, _)) = self.sent.pop_front() {
+                let entry = self.per_min.entry(chat).and_modify(|count| {
+                    *count -= 1;
+                });
+
+                if let Entry::Occupied(entry) = entry {
+                    if *entry.get() == 0 {
+                        entry.remove_entry();
+                    }
+                }
+            }
+        }
+
+        // It's easier to just recompute last second stats, instead of keeping
+        // track of it alongside with minute stats.
+        let mut window = Window::default();
+        for &(chat, _) in self.sent.iter().rev().take_while(|(_, time)| time > &sec_back) {
+            window.overall += 1;
+            *window.per_chat.entry(chat).or_insert(0) += 1;
+        }
+
+        window
+    }
+}
+
+impl<B> Backend for Arc<B>
+where
+    B: Backend + ?Sized,
+{
+    fn acquire<'a>(&'a self, requests: &'a [PendingRequest<'a>]) -> BoxFuture<'a, Acquired> {
+        (**self).acquire(requests)
+    }
+
+    fn freeze(&self, after: Duration) -> BoxFuture<'_, ()> {
+        (**self).freeze(after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        adaptors::throttle::{Acquired, Backend, ChatIdHash, InMemBackend, Limits, PendingRequest},
+        types::ChatId,
+    };
+
+    fn message(chat: i64, limits: &Limits) -> PendingRequest<'_> {
+        PendingRequest {
+            chat: ChatIdHash::Id(ChatId(chat)),
+            method: None,
+            paid_broadcast: false,
+            limits,
+        }
+    }
+
+    #[tokio::test]
+    async fn in_mem() {
+        let backend = InMemBackend::new();
+        let limits = Limits { messages_per_sec_overall: 3, ..Limits::default() };
+        let [a, b, c, d] = [1, 2, 3, 4].map(|chat| message(chat, &limits));
+
+        // One request per chat per second, three overall
+        assert_eq!(
+            backend.acquire(&[a, a, b, c, d]).await,
+            Acquired::Granted(vec![true, false, true, true, false])
+        );
+        assert_eq!(backend.acquire(&[a, d]).await, Acquired::Granted(vec![false, false]));
+
+        // Paid broadcasts may exceed the overall limit, methods with their own
+        // limits are counted separately
+        let paid = PendingRequest { paid_broadcast: true, ..d };
+        let copy = PendingRequest { method: Some("CopyMessage"), ..a };
+        assert_eq!(backend.acquire(&[paid, copy]).await, Acquired::Granted(vec![true, true]));
+
+        // Paid broadcasts are counted in the overall limit too
+        let paid_limit = Limits { messages_per_sec_paid_broadcast: 4, ..limits };
+        let paid = PendingRequest { paid_broadcast: true, ..message(5, &paid_limit) };
+        assert_eq!(backend.acquire(&[paid]).await, Acquired::Granted(vec![false]));
+
+        backend.freeze(Duration::from_secs(10)).await;
+        assert!(matches!(backend.acquire(&[d]).await, Acquired::Frozen(_)));
+    }
+
+    #[tokio::test]
+    async fn in_mem_keeps_order() {
+        let backend = InMemBackend::new();
+        let limits = Limits::default();
+        let copy_limits = Limits { messages_per_sec_overall: 0, ..limits };
+        let [a, b] = [1, 2].map(|chat| message(chat, &limits));
+        let copy = PendingRequest { method: Some("CopyMessage"), ..message(1, &copy_limits) };
+
+        // `a` would be granted, but it must not be sent before `copy`
+        assert_eq!(
+            backend.acquire(&[copy, a, b]).await,
+            Acquired::Granted(vec![false, false, true])
+        );
+        assert_eq!(backend.acquire(&[a]).await, Acquired::Granted(vec![true]));
+    }
+}
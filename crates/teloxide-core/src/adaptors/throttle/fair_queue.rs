@@ -0,0 +1,164 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::adaptors::throttle::{ChatIdHash, QueuedRequest};
+
+/// Queue of the worker which keeps a separate queue for every chat and
+/// offers requests of different chats in a round-robin order.
+///
+/// Requests to one chat are always offered in the order they were queued. A
+/// long burst of requests to one chat doesn't delay requests to other chats,
+/// since every chat gets its turn in every round.
+pub(super) struct FairQueue {
+    chats: HashMap<ChatIdHash, VecDeque<QueuedRequest>>,
+    // Chats with queued requests, the ones at the front are the next to be
+    // offered
+    round: VecDeque<ChatIdHash>,
+    len: usize,
+    capacity: usize,
+}
+
+/// Position of an offered request in a [`FairQueue`].
+#[derive(Clone, Copy)]
+pub(super) struct Offered {
+    chat: ChatIdHash,
+    index: usize,
+}
+
+impl FairQueue {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self { chats: HashMap::new(), round: VecDeque::new(), len: 0, capacity }
+    }
+
+    /// Returns the number of queued requests, including the dropped ones which
+    /// were not yet removed.
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(super) fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    pub(super) fn push(&mut self, request: QueuedRequest) {
+        // Chats are in the round as long as they are in `chats`, even if their
+        // queues became empty
+        let chat = request.chat;
+        self.chats
+            .entry(chat)
+            .or_insert_with(|| {
+                self.round.push_back(chat);
+                VecDeque::new()
+            })
+            .push_back(request);
+        self.len += 1;
+    }
+
+    /// Offers requests for sending, starting with chats which didn't have
+    /// their turn for the longest time.
+    ///
+    /// At most `per_chat` requests are offered from every chat, chats for
+    /// which `skip` returns `true` are not offered at all. Chats are visited
+    /// until `max` requests are offered. Returns the number of visited chats,
+    /// which should be passed to [`FairQueue::next_round`].
+    pub(super) fn offer(
+        &mut self,
+        per_chat: usize,
+        max: usize,
+        mut skip: impl FnMut(&ChatIdHash) -> bool,
+        mut f: impl FnMut(Offered, &QueuedRequest),
+    ) -> usize {
+        let mut offered = 0;
+        let mut visited = 0;
+
+        for chat in &self.round {
+            if offered >= max {
+                break;
+            }
+            visited += 1;
+
+            let queue = self.chats.get_mut(chat).expect("chats in the round have queues");
+
+            // Dropped requests don't need to be sent, so they shouldn't use up the limits
+            while queue.front().is_some_and(|queued| queued.lock.is_closed()) {
+                queue.pop_front();
+                self.len -= 1;
+            }
+
+            if skip(chat) {
+                continue;
+            }
+
+            let queued = queue.iter().enumerate().filter(|(_, queued)| !queued.lock.is_closed());
+            for (index, queued) in queued.take(per_chat) {
+                f(Offered { chat: *chat, index }, queued);
+                offered += 1;
+            }
+        }
+
+        visited
+    }
+
+    /// Removes offered request from the queue.
+    ///
+    /// Requests of a chat must be removed in the reverse order of their
+    /// offering, so that the positions of the remaining ones don't change.
+    pub(super) fn remove(&mut self, Offered { chat, index }: Offered) -> QueuedRequest {
+        let queue = self.chats.get_mut(&chat).expect("offered requests are queued");
+        let queued = queue.remove(index).expect("offered requests are queued");
+        self.len -= 1;
+        queued
+    }
+
+    /// Moves `visited` chats from the front to the back of the round and
+    /// forgets chats which don't have queued requests anymore.
+    pub(super) fn next_round(&mut self, visited: usize) {
+        for _ in 0..visited {
+            let Some(chat) = self.round.pop_front() else { break };
+
+            if self.chats[&chat].is_empty() {
+                self.chats.remove(&chat);
+            } else {
+                self.round.push_back(chat);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{future::IntoFuture, time::Duration};
+
+    use crate::{
+        adaptors::{throttle::Limits, Throttle},
+        requests::Requester,
+        testing::MockBot,
+        types::ChatId,
+    };
+
+    #[tokio::test]
+    async fn burst_does_not_delay_other_chats() {
+        let bot = MockBot::new();
+        let limits = Limits { messages_per_sec_overall: 20, ..Limits::default() };
+        let (throttle, worker) = Throttle::new(bot.clone(), limits);
+
+        for _ in 0..10 {
+            tokio::spawn(throttle.send_message(ChatId(1), "burst").into_future());
+        }
+        while throttle.queue_sizes().total() < 10 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let other = tokio::spawn(throttle.send_message(ChatId(2), "other").into_future());
+
+        tokio::spawn(worker);
+
+        // Only one message per second can be sent to the first chat, so the
+        // burst would take 10 seconds if it was sent first
+        tokio::time::timeout(Duration::from_secs(3), other).await.unwrap().unwrap().unwrap();
+        let burst = bot.requests().iter().filter(|r| r.payload["chat_id"] == 1).count();
+        assert!(burst <= 3, "{burst} messages of the burst were sent first");
+    }
+}
@@ -0,0 +1,109 @@
+/// Priority of a request sent through [`Throttle`].
+///
+/// Requests with different priorities are queued separately. In every
+/// iteration the worker first sends [`High`] priority requests, then
+/// [`Normal`] and only then [`Low`] priority requests use the budget which is
+/// left. Note that requests with different priorities may be sent in a
+/// different order than they were made, even if they are sent to the same
+/// chat.
+///
+/// Priority can be set for a single request with
+/// [`ThrottlingRequest::priority`] or for all requests of a method with
+/// [`Settings::method_priority`].
+///
+/// [`Throttle`]: crate::adaptors::Throttle
+/// [`High`]: Priority::High
+/// [`Normal`]: Priority::Normal
+/// [`Low`]: Priority::Low
+/// [`ThrottlingRequest::priority`]: crate::adaptors::throttle::ThrottlingRequest::priority
+/// [`Settings::method_priority`]: crate::adaptors::throttle::Settings::method_priority
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Interactive requests, for example replies to users.
+    High,
+
+    /// Default priority.
+    #[default]
+    Normal,
+
+    /// Bulk requests, for example broadcasts.
+    Low,
+}
+
+/// Number of requests of each [`Priority`] waiting in [`Throttle`] queues.
+///
+/// Returned by [`Throttle::queue_sizes`].
+///
+/// [`Throttle`]: crate::adaptors::Throttle
+/// [`Throttle::queue_sizes`]: crate::adaptors::Throttle::queue_sizes
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct QueueSizes {
+    /// Number of [`Priority::High`] requests.
+    pub high: usize,
+
+    /// Number of [`Priority::Normal`] requests.
+    pub normal: usize,
+
+    /// Number of [`Priority::Low`] requests.
+    pub low: usize,
+}
+
+impl Priority {
+    pub(super) const COUNT: usize = 3;
+
+    pub(super) fn lane(self) -> usize {
+        self as usize
+    }
+}
+
+impl QueueSizes {
+    /// Returns the total number of waiting requests.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.high + self.normal + self.low
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{future::IntoFuture, time::Duration};
+
+    use crate::{
+        adaptors::{
+            throttle::{Limits, Priority},
+            Throttle,
+        },
+        requests::Requester,
+        testing::MockBot,
+        types::ChatId,
+    };
+
+    #[tokio::test]
+    async fn high_priority_first() {
+        let bot = MockBot::new();
+        let limits = Limits { messages_per_sec_overall: 1, ..Limits::default() };
+        let (throttle, worker) = Throttle::new(bot.clone(), limits);
+
+        for (chat, text, priority) in
+            [(1, "low", Priority::Low), (2, "low", Priority::Low), (3, "high", Priority::High)]
+        {
+            let request = throttle.send_message(ChatId(chat), text).priority(priority);
+            tokio::spawn(request.into_future());
+        }
+
+        while throttle.queue_sizes().total() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let sizes = throttle.queue_sizes();
+        assert_eq!((sizes.high, sizes.normal, sizes.low), (1, 0, 2));
+
+        tokio::spawn(worker);
+
+        while bot.requests().is_empty() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(bot.requests()[0].payload["text"], "high");
+        assert_eq!(throttle.queue_sizes().high, 0);
+    }
+}
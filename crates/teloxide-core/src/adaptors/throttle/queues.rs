@@ -0,0 +1,192 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{mpsc, watch};
+
+use crate::adaptors::throttle::{ChatIdHash, Priority, QueueSizes, QueuedRequest};
+
+/// Queues of the worker, one per priority, and the state shared by
+/// [`Throttle`], its requests and the worker.
+///
+/// [`Throttle`]: crate::adaptors::Throttle
+#[derive(Debug)]
+pub(super) struct Queues {
+    queues: [mpsc::Sender<QueuedRequest>; Priority::COUNT],
+    pending: [AtomicUsize; Priority::COUNT],
+    chats: Mutex<HashMap<ChatIdHash, ChatQueue>>,
+    frozen_until: Mutex<Option<Instant>>,
+    method_priorities: HashMap<String, Priority>,
+}
+
+// Requests to a chat which are waiting to be sent
+#[derive(Debug)]
+struct ChatQueue {
+    pending: usize,
+    // Notifies pending requests that they were cancelled
+    cancel: watch::Sender<()>,
+}
+
+/// Marks a request as waiting in a queue, while alive.
+pub(super) struct Pending<'a> {
+    queues: &'a Queues,
+    priority: Priority,
+    chat: ChatIdHash,
+    cancelled: watch::Receiver<()>,
+}
+
+impl Queues {
+    pub(super) fn new(
+        queues: [mpsc::Sender<QueuedRequest>; Priority::COUNT],
+        method_priorities: HashMap<String, Priority>,
+    ) -> Self {
+        Self {
+            queues,
+            pending: <_>::default(),
+            chats: <_>::default(),
+            frozen_until: <_>::default(),
+            method_priorities,
+        }
+    }
+
+    /// Returns the default priority of requests of the method `name`.
+    pub(super) fn method_priority(&self, name: &str) -> Priority {
+        // Method names are case-insensitive
+        self.method_priorities
+            .iter()
+            .find(|(method, _)| method.eq_ignore_ascii_case(name))
+            .map(|(_, &priority)| priority)
+            .unwrap_or_default()
+    }
+
+    pub(super) fn queue(&self, priority: Priority) -> &mpsc::Sender<QueuedRequest> {
+        &self.queues[priority.lane()]
+    }
+
+    pub(super) fn pending(&self, priority: Priority, chat: ChatIdHash) -> Pending<'_> {
+        self.pending[priority.lane()].fetch_add(1, Ordering::Relaxed);
+
+        let mut chats = self.chats.lock().unwrap();
+        let queue = chats
+            .entry(chat)
+            .or_insert_with(|| ChatQueue { pending: 0, cancel: watch::Sender::new(()) });
+        queue.pending += 1;
+        let cancelled = queue.cancel.subscribe();
+
+        Pending { queues: self, priority, chat, cancelled }
+    }
+
+    pub(super) fn sizes(&self) -> QueueSizes {
+        let [high, normal, low] = &self.pending;
+        QueueSizes {
+            high: high.load(Ordering::Relaxed),
+            normal: normal.load(Ordering::Relaxed),
+            low: low.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(super) fn chat_sizes(&self) -> HashMap<ChatIdHash, usize> {
+        let chats = self.chats.lock().unwrap();
+        chats.iter().map(|(&chat, queue)| (chat, queue.pending)).collect()
+    }
+
+    /// Cancels all requests to `chat` which are waiting to be sent, returns
+    /// the number of cancelled requests.
+    pub(super) fn cancel(&self, chat: ChatIdHash) -> usize {
+        let chats = self.chats.lock().unwrap();
+        match chats.get(&chat) {
+            Some(queue) => {
+                queue.cancel.send_replace(());
+                queue.pending
+            }
+            None => 0,
+        }
+    }
+
+    pub(super) fn frozen_for(&self) -> Option<Duration> {
+        let frozen_until = *self.frozen_until.lock().unwrap();
+        frozen_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero())
+    }
+
+    pub(super) fn set_frozen_until(&self, until: Option<Instant>) {
+        *self.frozen_until.lock().unwrap() = until;
+    }
+}
+
+impl Pending<'_> {
+    /// Waits until the request is cancelled with [`Queues::cancel`].
+    pub(super) async fn cancelled(&mut self) {
+        // The sender lives while there are pending requests to the chat, so
+        // an error can't happen
+        if self.cancelled.changed().await.is_err() {
+            futures::future::pending::<()>().await;
+        }
+    }
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        self.queues.pending[self.priority.lane()].fetch_sub(1, Ordering::Relaxed);
+
+        let mut chats = self.queues.chats.lock().unwrap();
+        if let Entry::Occupied(mut entry) = chats.entry(self.chat) {
+            entry.get_mut().pending -= 1;
+            if entry.get().pending == 0 {
+                entry.remove();
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{future::IntoFuture, time::Duration};
+
+    use crate::{
+        adaptors::{
+            throttle::{ChatIdHash, Limits},
+            Throttle,
+        },
+        errors::RequestError,
+        requests::Requester,
+        testing::MockBot,
+        types::ChatId,
+    };
+
+    #[tokio::test]
+    async fn cancel_chat() {
+        let bot = MockBot::new();
+        // The worker is not spawned, so requests wait in the queues
+        let (throttle, _worker) = Throttle::new(bot.clone(), Limits::default());
+
+        let cancelled: Vec<_> = (0..2)
+            .map(|_| tokio::spawn(throttle.send_message(ChatId(1), "a").into_future()))
+            .collect();
+        let other = tokio::spawn(throttle.send_message(ChatId(2), "b").into_future());
+
+        while throttle.queue_sizes().total() < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let sizes = throttle.chat_queue_sizes();
+        assert_eq!(sizes[&ChatIdHash::Id(ChatId(1))], 2);
+        assert_eq!(sizes[&ChatIdHash::Id(ChatId(2))], 1);
+        assert_eq!(throttle.frozen_for(), None);
+
+        assert_eq!(throttle.cancel_chat(ChatId(1)), 2);
+        for request in cancelled {
+            assert!(matches!(request.await.unwrap(), Err(RequestError::Cancelled)));
+        }
+
+        assert_eq!(throttle.queue_sizes().total(), 1);
+        assert!(!throttle.chat_queue_sizes().contains_key(&ChatIdHash::Id(ChatId(1))));
+        assert_eq!(throttle.cancel_chat(ChatId(1)), 0);
+        assert!(!other.is_finished());
+    }
+}
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use deadpool_redis::{redis, CreatePoolError, Pool, Runtime};
+use futures::future::BoxFuture;
+
+use crate::adaptors::throttle::{Acquired, Backend, ChatIdHash, PendingRequest};
+
+/// A [`Backend`] which stores the history of requests in [Redis].
+///
+/// All [`Throttle`]s which use the same Redis server and the same key prefix
+/// share the limits, so you can run several instances of a bot with one
+/// token (e.g. behind a load balancer) without hitting Telegram limits.
+/// Bots with different tokens should use different prefixes, see
+/// [`RedisBackend::prefix`].
+///
+/// Requests are granted by a Lua script which uses the server time, so clocks
+/// of the bot instances don't need to be synchronized. Since the script
+/// accesses keys which are not known in advance, Redis Cluster is not
+/// supported.
+///
+/// If Redis is not available, no requests are sent until it becomes available
+/// again.
+///
+/// Note that requests with `@channelusername`s are identified by a hash of
+/// the username, which is only guaranteed to be the same if all the instances
+/// are built with the same Rust version.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{
+///     adaptors::{
+///         throttle::{RedisBackend, Settings},
+///         Throttle,
+///     },
+///     Bot,
+/// };
+///
+/// # async {
+/// let backend = RedisBackend::open("redis://127.0.0.1:6379")?.prefix("my_bot:throttle");
+/// let bot = Throttle::spawn_with_settings(Bot::from_env(), Settings::default().backend(backend));
+/// # Ok::<_, Box<dyn std::error::Error>>(()) };
+/// ```
+///
+/// [Redis]: https://redis.io/
+/// [`Throttle`]: crate::adaptors::Throttle
+#[derive(Clone)]
+pub struct RedisBackend {
+    pool: Pool,
+    prefix: String,
+}
+
+// `ARGV`: prefix and then for every request: key prefix of its method (empty
+// for messages), chat key, overall limit (which depends on whether it's a paid
+// broadcast), limit per second of the chat and limit per minute of the chat.
+//
+// Returns the number of milliseconds the bot is frozen for (`0` if it's not
+// frozen), followed by `1` for every granted request and `0` for every denied
+// one. Once a request to a chat is denied, the following requests to the chat
+// are denied too.
+const ACQUIRE: &str = r"
+local prefix = ARGV[1]
+
+local frozen = redis.call('PTTL', prefix .. ':freeze')
+if frozen > 0 then
+    return {frozen}
+end
+
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local second_ago = now - 1000
+local minute_ago = now - 60000
+
+-- Number of requests sent in the last second, by key
+local sent = {}
+-- Chats with a denied request
+local blocked = {}
+
+local res = {0}
+for i = 2, #ARGV, 5 do
+    local group = prefix .. ARGV[i]
+    local overall = group .. ':overall'
+
+    if sent[overall] == nil then
+        redis.call('ZREMRANGEBYSCORE', overall, '-inf', second_ago)
+        sent[overall] = redis.call('ZCARD', overall)
+    end
+
+    local granted = 0
+
+    if not blocked[ARGV[i + 1]] and sent[overall] < tonumber(ARGV[i + 2]) then
+        local chat = group .. ':chat:' .. ARGV[i + 1]
+
+        redis.call('ZREMRANGEBYSCORE', chat, '-inf', '(' .. minute_ago)
+        if redis.call('ZCOUNT', chat, '(' .. second_ago, '+inf') < tonumber(ARGV[i + 3])
+            and redis.call('ZCARD', chat) < tonumber(ARGV[i + 4]) then
+            local id = redis.call('INCR', prefix .. ':seq')
+
+            redis.call('ZADD', chat, now, id)
+            redis.call('PEXPIRE', chat, 60000)
+            redis.call('ZADD', overall, now, id)
+            redis.call('PEXPIRE', overall, 1000)
+
+            sent[overall] = sent[overall] + 1
+            granted = 1
+        end
+    end
+
+    if granted == 0 then
+        blocked[ARGV[i + 1]] = true
+    end
+    table.insert(res, granted)
+end
+
+return res
+";
+
+// Freezes the bot, unless it's already frozen for longer.
+const FREEZE: &str = r"
+if redis.call('PTTL', KEYS[1]) < tonumber(ARGV[1]) then
+    redis.call('SET', KEYS[1], 1, 'PX', ARGV[1])
+end
+";
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+impl RedisBackend {
+    /// Creates new backend which uses connections from `pool`.
+    #[must_use]
+    pub fn new(pool: Pool) -> Self {
+        Self { pool, prefix: "teloxide:throttle".to_owned() }
+    }
+
+    /// Creates new backend connected to the Redis server at `url`.
+    pub fn open(url: &str) -> Result<Self, CreatePoolError> {
+        let pool = deadpool_redis::Config::from_url(url).create_pool(Some(Runtime::Tokio1))?;
+        Ok(Self::new(pool))
+    }
+
+    /// Sets the prefix of the keys used by this backend.
+    ///
+    /// Default prefix is `teloxide:throttle`.
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    async fn try_acquire(&self, requests: &[PendingRequest<'_>]) -> Result<Acquired, Error> {
+        let mut cmd = redis::cmd("EVAL");
+        cmd.arg(ACQUIRE).arg(0).arg(&self.prefix);
+
+        for request in requests {
+            let group = match request.method {
+                Some(method) => format!(":method:{method}"),
+                None => String::new(),
+            };
+            let chat = match request.chat {
+                ChatIdHash::Id(id) => id.to_string(),
+                ChatIdHash::ChannelUsernameHash(hash) => format!("@{hash:x}"),
+            };
+
+            cmd.arg(group)
+                .arg(chat)
+                .arg(request.overall_limit())
+                .arg(request.limits.messages_per_sec_chat)
+                .arg(request.per_min_limit());
+        }
+
+        let mut conn = self.pool.get().await?;
+        let res: Vec<i64> = cmd.query_async(&mut conn).await?;
+
+        match res.split_first() {
+            Some((&frozen, _)) if frozen > 0 => {
+                Ok(Acquired::Frozen(Duration::from_millis(frozen as u64)))
+            }
+            Some((_, granted)) if granted.len() == requests.len() => {
+                Ok(Acquired::Granted(granted.iter().map(|&g| g == 1).collect()))
+            }
+            _ => Err(format!("unexpected response from the acquire script: {res:?}").into()),
+        }
+    }
+
+    async fn try_freeze(&self, after: Duration) -> Result<(), Error> {
+        let ms = after.as_millis() as u64;
+        if ms == 0 {
+            return Ok(());
+        }
+
+        let mut conn = self.pool.get().await?;
+        () = redis::cmd("EVAL")
+            .arg(FREEZE)
+            .arg(1)
+            .arg(format!("{}:freeze", self.prefix))
+            .arg(ms)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Backend for RedisBackend {
+    fn acquire<'a>(&'a self, requests: &'a [PendingRequest<'a>]) -> BoxFuture<'a, Acquired> {
+        Box::pin(async move {
+            self.try_acquire(requests).await.unwrap_or_else(|err| {
+                log::error!("couldn't check throttle limits in Redis: {err}");
+                Acquired::Granted(vec![false; requests.len()])
+            })
+        })
+    }
+
+    fn freeze(&self, after: Duration) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            if let Err(err) = self.try_freeze(after).await {
+                log::error!("couldn't freeze throttle in Redis: {err}");
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        adaptors::throttle::{Acquired, Backend, ChatIdHash, Limits, PendingRequest, RedisBackend},
+        types::ChatId,
+    };
+
+    #[tokio::test]
+    #[cfg_attr(not(CI_REDIS), ignore)]
+    async fn shared_limits() {
+        let prefix = format!("teloxide:test:{}", uuid::Uuid::new_v4());
+        let a = RedisBackend::open("redis://127.0.0.1:7777").unwrap().prefix(&prefix);
+        let b = RedisBackend::open("redis://127.0.0.1:7777").unwrap().prefix(&prefix);
+        let limits = Limits { messages_per_sec_overall: 3, ..Limits::default() };
+        let [x, y, z, w] = [1, 2, 3, 4].map(|chat| PendingRequest {
+            chat: ChatIdHash::Id(ChatId(chat)),
+            method: None,
+            paid_broadcast: false,
+            limits: &limits,
+        });
+
+        assert_eq!(a.acquire(&[x, x, y]).await, Acquired::Granted(vec![true, false, true]));
+        // Limits are shared with the other "instance"
+        assert_eq!(b.acquire(&[x, z, w]).await, Acquired::Granted(vec![false, true, false]));
+
+        // Paid broadcasts may exceed the overall limit, methods with their own
+        // limits are counted separately
+        let paid = PendingRequest { paid_broadcast: true, ..w };
+        let copy = PendingRequest { method: Some("CopyMessage"), ..x };
+        assert_eq!(a.acquire(&[paid, copy]).await, Acquired::Granted(vec![true, true]));
+
+        // Requests to a chat are not granted after a denied one
+        let copy_limits = Limits { messages_per_sec_overall: 0, ..limits };
+        let [u, v] =
+            [5, 6].map(|chat| PendingRequest { chat: ChatIdHash::Id(ChatId(chat)), ..paid });
+        let copy = PendingRequest { method: Some("CopyMessage"), limits: &copy_limits, ..u };
+        assert_eq!(b.acquire(&[copy, u, v]).await, Acquired::Granted(vec![false, false, true]));
+
+        b.freeze(Duration::from_secs(10)).await;
+        assert!(matches!(a.acquire(&[w]).await, Acquired::Frozen(_)));
+    }
+}
@@ -1,21 +1,19 @@
 use std::{
     future::{Future, IntoFuture},
-    pin::Pin,
+    pin::{pin, Pin},
     sync::Arc,
     time::Instant,
 };
 
+use crate::{
+    adaptors::throttle::{channel, ChatIdHash, FreezeUntil, Priority, QueuedRequest, Queues},
+    errors::{AsResponseParameters, RequestError},
+    requests::{HasPayload, Output, Payload, Request},
+};
 use futures::{
-    future::BoxFuture,
+    future::{self, BoxFuture, Either},
     task::{Context, Poll},
 };
-use tokio::sync::mpsc;
-
-use crate::{
-    adaptors::throttle::{channel, ChatIdHash, FreezeUntil, RequestLock},
-    errors::AsResponseParameters,
-    requests::{HasPayload, Output, Request},
-};
 
 /// Request returned by [`Throttling`](crate::adaptors::Throttle) methods.
 #[must_use = "Requests are lazy and do nothing unless sent"]
@@ -23,19 +21,48 @@ use crate::{
 pub struct ThrottlingRequest<R: HasPayload> {
     pub(super) request: Arc<R>,
     pub(super) chat_id: fn(&R::Payload) -> ChatIdHash,
-    pub(super) worker: mpsc::Sender<(ChatIdHash, RequestLock)>,
+    pub(super) paid_broadcast: fn(&R::Payload) -> bool,
+    pub(super) priority: Priority,
+    pub(super) queues: Arc<Queues>,
 }
 
 /// Future returned by [`ThrottlingRequest`]s.
 #[pin_project::pin_project]
 pub struct ThrottlingSend<R: Request>(#[pin] BoxFuture<'static, Result<Output<R>, R::Err>>);
 
+struct RequestInfo {
+    chat: ChatIdHash,
+    method: &'static str,
+    paid_broadcast: bool,
+    priority: Priority,
+}
+
 enum ShareableRequest<R> {
     Shared(Arc<R>),
     // Option is used to `take` ownership
     Owned(Option<R>),
 }
 
+impl<R: HasPayload> ThrottlingRequest<R> {
+    /// Sets the [`Priority`] of this request.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<R: HasPayload> ThrottlingRequest<R> {
+    fn info(&self) -> RequestInfo {
+        let payload = self.request.payload_ref();
+        RequestInfo {
+            chat: (self.chat_id)(payload),
+            method: R::Payload::NAME,
+            paid_broadcast: (self.paid_broadcast)(payload),
+            priority: self.priority,
+        }
+    }
+}
+
 impl<R: HasPayload + Clone> HasPayload for ThrottlingRequest<R> {
     type Payload = R::Payload;
 
@@ -53,7 +80,7 @@ impl<R: HasPayload + Clone> HasPayload for ThrottlingRequest<R> {
 impl<R> Request for ThrottlingRequest<R>
 where
     R: Request + Clone + Send + Sync + 'static, // TODO: rem static
-    R::Err: AsResponseParameters + Send,
+    R::Err: AsResponseParameters + From<RequestError> + Send,
     Output<R>: Send,
 {
     type Err = R::Err;
@@ -61,20 +88,20 @@ where
     type SendRef = ThrottlingSend<R>;
 
     fn send(self) -> Self::Send {
-        let chat = (self.chat_id)(self.payload_ref());
+        let info = self.info();
         let request = match Arc::try_unwrap(self.request) {
             Ok(owned) => ShareableRequest::Owned(Some(owned)),
             Err(shared) => ShareableRequest::Shared(shared),
         };
-        let fut = send(request, chat, self.worker);
+        let fut = send(request, info, self.queues);
 
         ThrottlingSend(Box::pin(fut))
     }
 
     fn send_ref(&self) -> Self::SendRef {
-        let chat = (self.chat_id)(self.payload_ref());
+        let info = self.info();
         let request = ShareableRequest::Shared(Arc::clone(&self.request));
-        let fut = send(request, chat, self.worker.clone());
+        let fut = send(request, info, Arc::clone(&self.queues));
 
         ThrottlingSend(Box::pin(fut))
     }
@@ -83,7 +110,7 @@ where
 impl<R> IntoFuture for ThrottlingRequest<R>
 where
     R: Request + Clone + Send + Sync + 'static,
-    R::Err: AsResponseParameters + Send,
+    R::Err: AsResponseParameters + From<RequestError> + Send,
     Output<R>: Send,
 {
     type Output = Result<Output<Self>, <Self as Request>::Err>;
@@ -155,12 +182,12 @@ where
 /// Actual implementation of the `ThrottlingSend` future
 async fn send<R>(
     mut request: ShareableRequest<R>,
-    chat: ChatIdHash,
-    worker: mpsc::Sender<(ChatIdHash, RequestLock)>,
+    RequestInfo { chat, method, paid_broadcast, priority }: RequestInfo,
+    queues: Arc<Queues>,
 ) -> Result<Output<R>, R::Err>
 where
     R: Request + Send + Sync + 'static,
-    R::Err: AsResponseParameters + Send,
+    R::Err: AsResponseParameters + From<RequestError> + Send,
     Output<R>: Send,
 {
     // We use option in `ShareableRequest` to `take` when sending by value.
@@ -170,22 +197,39 @@ where
 
     loop {
         let (lock, wait) = channel();
+        let mut pending = queues.pending(priority, chat);
 
-        // The worker is unlikely to drop queue before sending all requests,
-        // but just in case it has dropped the queue, we want to just send the
-        // request.
-        if worker.send((chat, lock)).await.is_err() {
-            log::error!("Worker dropped the queue before sending all requests");
+        let queued = QueuedRequest { chat, method, paid_broadcast, lock };
+        let wait = async {
+            // The worker is unlikely to drop queue before sending all requests,
+            // but just in case it has dropped the queue, we want to just send the
+            // request.
+            match queues.queue(priority).send(queued).await {
+                Ok(()) => Some(wait.await),
+                Err(_) => None,
+            }
+        };
+        let waited = match future::select(pin!(wait), pin!(pending.cancelled())).await {
+            Either::Left((waited, _)) => Some(waited),
+            Either::Right(((), _)) => None,
+        };
+        drop(pending);
 
-            let res = match &mut request {
-                ShareableRequest::Shared(shared) => shared.send_ref().await,
-                ShareableRequest::Owned(owned) => owned.take().unwrap().await,
-            };
+        let (retry, freeze) = match waited {
+            Some(Some(unlocked)) => unlocked,
+            Some(None) => {
+                log::error!("Worker dropped the queue before sending all requests");
 
-            return res;
-        };
+                let res = match &mut request {
+                    ShareableRequest::Shared(shared) => shared.send_ref().await,
+                    ShareableRequest::Owned(owned) => owned.take().unwrap().await,
+                };
 
-        let (retry, freeze) = wait.await;
+                return res;
+            }
+            // Dropping the waiter makes the worker remove the request from the queue
+            None => return Err(RequestError::Cancelled.into()),
+        };
 
         let res = match (retry, &mut request) {
             // Retries are turned on, use `send_ref` even if we have owned access
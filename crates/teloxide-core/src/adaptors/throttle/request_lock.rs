@@ -29,6 +29,12 @@ impl RequestLock {
     pub(super) fn unlock(self, retry: bool, freeze: mpsc::Sender<FreezeUntil>) -> Result<(), ()> {
         self.0.send((retry, freeze)).map_err(drop)
     }
+
+    /// Returns `true` if the request was dropped and doesn't need to be
+    /// unlocked.
+    pub(super) fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
 }
 
 impl Future for RequestWaiter {
@@ -3,19 +3,36 @@ use std::sync::Arc;
 use url::Url;
 
 use crate::{
-    adaptors::{throttle::ThrottlingRequest, Throttle},
-    errors::AsResponseParameters,
-    requests::{HasPayload, Requester},
+    adaptors::{
+        throttle::{ChatIdHash, ThrottlingRequest},
+        Throttle,
+    },
+    errors::{AsResponseParameters, RequestError},
+    requests::{HasPayload, Payload, Requester},
     types::*,
 };
 
+// Messages which can be sent as paid broadcasts
 macro_rules! f {
     ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
-        ThrottlingRequest {
-            request: Arc::new($this.inner().$m($($arg),*)),
-            chat_id: |p| (&p.payload_ref().chat_id).into(),
-            worker: $this.queue.clone(),
-        }
+        throttling_request(
+            $this,
+            $this.inner().$m($($arg),*),
+            |p| (&p.chat_id).into(),
+            |p| p.allow_paid_broadcast == Some(true),
+        )
+    };
+}
+
+// Other messages
+macro_rules! fm {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        throttling_request(
+            $this,
+            $this.inner().$m($($arg),*),
+            |p| (&p.chat_id).into(),
+            |_| false,
+        )
     };
 }
 
@@ -39,7 +56,7 @@ macro_rules! ftyid {
 
 impl<B: Requester> Requester for Throttle<B>
 where
-    B::Err: AsResponseParameters,
+    B::Err: AsResponseParameters + From<RequestError>,
 
     B::SendMessage: Clone + Send + Sync + 'static,
     B::ForwardMessage: Clone + Send + Sync + 'static,
@@ -69,10 +86,7 @@ where
 
     requester_forward! {
         send_message,
-        forward_message,
-        forward_messages,
         copy_message,
-        copy_messages,
         send_photo,
         send_audio,
         send_document,
@@ -86,7 +100,6 @@ where
         send_venue,
         send_contact,
         send_poll,
-        send_checklist,
         send_dice,
         send_sticker,
         send_invoice,
@@ -94,6 +107,14 @@ where
         => f, fty
     }
 
+    requester_forward! {
+        forward_message,
+        forward_messages,
+        copy_messages,
+        send_checklist
+        => fm, fty
+    }
+
     requester_forward! {
         get_me,
         log_out,
@@ -249,3 +270,56 @@ download_forward! {
     Throttle<B>
     { this => this.inner() }
 }
+
+fn throttling_request<B, R>(
+    this: &Throttle<B>,
+    request: R,
+    chat_id: fn(&R::Payload) -> ChatIdHash,
+    paid_broadcast: fn(&R::Payload) -> bool,
+) -> ThrottlingRequest<R>
+where
+    R: HasPayload,
+{
+    ThrottlingRequest {
+        priority: this.queues.method_priority(payload_name(&request)),
+        request: Arc::new(request),
+        chat_id,
+        paid_broadcast,
+        queues: Arc::clone(&this.queues),
+    }
+}
+
+fn payload_name<R: HasPayload>(_: &R) -> &'static str {
+    R::Payload::NAME
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{future::IntoFuture, time::Duration};
+
+    use crate::{
+        adaptors::{
+            throttle::{Limits, Settings},
+            Throttle,
+        },
+        requests::Requester,
+        testing::MockBot,
+        types::{ChatId, MessageId},
+    };
+
+    #[tokio::test]
+    async fn only_messages_are_throttled() {
+        let bot = MockBot::new();
+        let settings = Settings::default().method_limits("copyMessage", Limits::default());
+        // The worker is not spawned, so throttled requests are never sent
+        let (throttle, _worker) = Throttle::with_settings(bot.clone(), settings);
+
+        throttle.edit_message_text(ChatId(1), MessageId(1), "edited").await.unwrap();
+        throttle.set_message_reaction(ChatId(1), MessageId(1)).await.unwrap();
+        assert_eq!(bot.requests().len(), 2);
+
+        let copy = throttle.copy_message(ChatId(1), ChatId(2), MessageId(1)).into_future();
+        assert!(tokio::time::timeout(Duration::from_millis(100), copy).await.is_err());
+        assert_eq!(bot.requests().len(), 2);
+    }
+}
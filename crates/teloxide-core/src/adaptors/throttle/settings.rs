@@ -1,7 +1,9 @@
-use std::pin::Pin;
+use std::{collections::HashMap, pin::Pin, sync::Arc};
 
 use futures::{future::ready, Future};
 
+use crate::adaptors::throttle::{Backend, InMemBackend, Priority};
+
 // Required to not trigger `clippy::type-complexity` lint
 type BoxedFnMut<I, O> = Box<dyn FnMut(I) -> O + Send>;
 type BoxedFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
@@ -28,6 +30,9 @@ pub struct Settings {
     pub on_queue_full: BoxedFnMut<usize, BoxedFuture>,
     pub retry: bool,
     pub check_slow_mode: bool,
+    pub backend: Arc<dyn Backend>,
+    pub method_priorities: HashMap<String, Priority>,
+    pub method_limits: HashMap<String, Limits>,
 }
 
 /// Telegram request limits.
@@ -52,6 +57,15 @@ pub struct Limits {
 
     /// Allowed messages per second.
     pub messages_per_sec_overall: u32,
+
+    /// Allowed paid broadcast messages per second.
+    ///
+    /// This limit is used instead of `messages_per_sec_overall` for requests
+    /// with `allow_paid_broadcast` set to `true`. Paid broadcasts are counted
+    /// together with other messages, so the total number of messages sent per
+    /// second never exceeds this limit. Limits of individual chats apply to
+    /// paid broadcasts as well.
+    pub messages_per_sec_paid_broadcast: u32,
 }
 
 impl Settings {
@@ -78,6 +92,65 @@ impl Settings {
         self.check_slow_mode = true;
         self
     }
+
+    /// Sets the [`Backend`] used to keep track of sent requests.
+    ///
+    /// By default the history of requests is kept in memory, see
+    /// [`InMemBackend`].
+    pub fn backend<Bk>(mut self, val: Bk) -> Self
+    where
+        Bk: Backend + 'static,
+    {
+        self.backend = Arc::new(val);
+        self
+    }
+
+    /// Sets the default [`Priority`] of requests of a method.
+    ///
+    /// `method` is the name of the method in the Bot API, e.g.
+    /// `"sendMessage"` (case-insensitive).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use teloxide_core::adaptors::throttle::{Priority, Settings};
+    ///
+    /// // Broadcasts are sent with `copy_message`
+    /// let settings = Settings::default().method_priority("copyMessage", Priority::Low);
+    /// # let _ = settings;
+    /// ```
+    pub fn method_priority(mut self, method: impl Into<String>, priority: Priority) -> Self {
+        self.method_priorities.insert(method.into(), priority);
+        self
+    }
+
+    /// Sets limits of a method.
+    ///
+    /// Only requests which send messages (e.g. `sendMessage` or
+    /// `copyMessage`) are throttled and by default they all share
+    /// [`Settings::limits`]. With this function one of these methods gets its
+    /// own limits: its requests are counted separately from the other
+    /// requests, the "messages" in [`Limits`] fields refer to the requests of
+    /// the method. Limits of methods which don't send messages have no effect.
+    ///
+    /// `method` is the name of the method in the Bot API, e.g.
+    /// `"copyMessage"` (case-insensitive).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use teloxide_core::adaptors::throttle::{Limits, Settings};
+    ///
+    /// let settings = Settings::default().method_limits(
+    ///     "copyMessage",
+    ///     Limits { messages_per_sec_chat: 1, messages_per_sec_overall: 20, ..Limits::default() },
+    /// );
+    /// # let _ = settings;
+    /// ```
+    pub fn method_limits(mut self, method: impl Into<String>, limits: Limits) -> Self {
+        self.method_limits.insert(method.into(), limits);
+        self
+    }
 }
 
 impl Default for Settings {
@@ -90,14 +163,19 @@ impl Default for Settings {
             }),
             retry: true,
             check_slow_mode: false,
+            backend: Arc::new(InMemBackend::new()),
+            method_priorities: HashMap::new(),
+            method_limits: HashMap::new(),
         }
     }
 }
 
 /// Defaults are taken from [telegram documentation][tgdoc] (except for
-/// `messages_per_min_channel`).
+/// `messages_per_min_channel`) and the documentation of
+/// [`allow_paid_broadcast`][paid].
 ///
 /// [tgdoc]: https://core.telegram.org/bots/faq#my-bot-is-hitting-limits-how-do-i-avoid-this
+/// [paid]: https://core.telegram.org/bots/api#sendmessage
 impl Default for Limits {
     fn default() -> Self {
         Self {
@@ -105,6 +183,7 @@ impl Default for Limits {
             messages_per_sec_overall: 30,
             messages_per_min_chat: 20,
             messages_per_min_channel_or_supergroup: 10,
+            messages_per_sec_paid_broadcast: 1000,
         }
     }
 }
@@ -1,23 +1,24 @@
 use std::{
-    collections::{hash_map::Entry, HashMap, VecDeque},
+    collections::HashMap,
     pin::pin,
+    sync::Weak,
+    task::Poll,
     time::{Duration, Instant},
 };
 
 use either::Either;
 use futures::{future, FutureExt as _};
 use tokio::sync::{mpsc, mpsc::error::TryRecvError, oneshot::Sender};
-use vecrem::VecExt;
 
 use crate::{
-    adaptors::throttle::{request_lock::RequestLock, ChatIdHash, Limits, Settings},
+    adaptors::throttle::{
+        fair_queue::FairQueue, request_lock::RequestLock, Acquired, Backend, ChatIdHash, Limits,
+        PendingRequest, Priority, Queues, Settings,
+    },
     errors::AsResponseParameters,
     requests::Requester,
 };
 
-const MINUTE: Duration = Duration::from_secs(60);
-const SECOND: Duration = Duration::from_secs(1);
-
 // Delay between worker iterations.
 //
 // For now it's `second/4`, but that number is chosen pretty randomly, we may
@@ -33,15 +34,13 @@ pub(super) enum InfoMessage {
     SetLimits { new: Limits, response: Sender<()> },
 }
 
-type RequestsSent = u32;
-
-// I wish there was special data structure for history which removed the
-// need in 2 hashmaps
-// (waffle)
-#[derive(Default)]
-struct RequestsSentToChats {
-    per_min: HashMap<ChatIdHash, RequestsSent>,
-    per_sec: HashMap<ChatIdHash, RequestsSent>,
+/// A request waiting in a queue of the worker.
+pub(super) struct QueuedRequest {
+    pub(super) chat: ChatIdHash,
+    pub(super) method: &'static str,
+    pub(super) paid_broadcast: bool,
+    // `RequestLock` allows to unlock requests (allowing them to be sent).
+    pub(super) lock: RequestLock,
 }
 
 pub(super) struct FreezeUntil {
@@ -64,65 +63,86 @@ pub(super) struct FreezeUntil {
 // The worker does the most important job -- it ensures that the limits are
 // never exceeded.
 //
-// The worker stores a history of requests sent in the last minute (and to which
-// chats they were sent) and a queue of pending updates.
+// The worker stores queues of pending updates, one for every `Priority`, while
+// the history of requests
+// sent in the last minute (and to which chats they were sent) is stored by a
+// `Backend`. The backend may be shared by several workers (possibly in
+// different processes).
+//
+// Every queue is a `FairQueue`, which keeps a separate queue for every chat and
+// a "round" of chats. Chats are offered in the order of the round and the
+// visited chats are then moved to its back, so every chat gets its turn
+// regardless of how many requests other chats have queued.
 //
 // The worker does the following algorithm loop:
 //
-// 1. If the queue is empty, wait for the first message in incoming channel (and
-// add it to the queue).
+// 1. If the queues are empty, wait for the first message in any of incoming
+// channels (and add it to the corresponding queue).
 //
-// 2. Read all present messages from an incoming channel and transfer them to
-// the queue.
+// 2. Read all present messages from incoming channels and transfer them to
+// the queues.
 //
-// 3. Record the current time.
+// 3. Collect the first requests of the chats at the front of the rounds, which
+// are not delayed by the slow mode, alongside with their limits, higher
+// priority queues first. Requests which were dropped are removed instead. Only
+// a few times more requests than can be sent at once are collected, to avoid
+// going through the whole queues in every iteration.
 //
-// 4. Clear the history from records whose time < (current time - minute).
+// 4. Ask the backend which of the requests can be sent. If the bot is frozen
+// because of a `RetryAfter` error, wait and `continue` to the next iteration.
 //
-// 5. Count all requests which were sent last second, `allowed =
-// limit.messages_per_sec_overall - count`.
+// 5. Notify the granted requests that they can be now executed and move the
+// visited chats to the back of the rounds.
+//
+// ### Backend
+//
+// `InMemBackend` decides which requests can be sent as follows:
 //
-// 6. If `allowed == 0` wait a bit and `continue` to the next iteration.
+// 1. Record the current time.
 //
-// 7. Count how many requests were sent to which chats (i.e.: create
+// 2. Clear the history from records whose time < (current time - minute).
+//
+// 3. Count all requests which were sent last second, `allowed =
+// limit.messages_per_sec_overall - count`.
+//
+// 4. Count how many requests were sent to which chats (i.e.: create
 // `Map<ChatId, Count>`). (Note: the same map, but for last minute also exists,
 // but it's updated, instead of recreation.)
 //
-// 8. While `allowed >= 0` search for requests which chat haven't exceed the
-// limits (i.e.: map[chat] < limit), if one is found, decrease `allowed`, notify
-// the request that it can be now executed, increase counts, add record to the
-// history.
+// 5. While `allowed >= 0` search for requests which chat haven't exceed the
+// limits (i.e.: map[chat] < limit), if one is found, decrease `allowed`, grant
+// the request, increase counts, add record to the history.
 pub(super) async fn worker<B>(
-    Settings { mut limits, mut on_queue_full, retry, check_slow_mode }: Settings,
-    mut rx: mpsc::Receiver<(ChatIdHash, RequestLock)>,
+    Settings {
+        mut limits, mut on_queue_full, retry, check_slow_mode, backend, method_limits, ..
+    }: Settings,
+    mut rx: [mpsc::Receiver<QueuedRequest>; Priority::COUNT],
     mut info_rx: mpsc::Receiver<InfoMessage>,
+    shared: Weak<Queues>,
     bot: B,
 ) where
     B: Requester,
     B::Err: AsResponseParameters,
 {
-    // FIXME(waffle): Make an research about data structures for this queue.
-    //                Currently this is O(n) removing (n = number of elements
-    //                stayed), amortized O(1) push (vec+vecrem).
-    let mut queue: Vec<(ChatIdHash, RequestLock)> =
-        Vec::with_capacity(limits.messages_per_sec_overall as usize);
-
-    let mut history: VecDeque<(ChatIdHash, Instant)> = VecDeque::new();
-    let mut requests_sent = RequestsSentToChats::default();
+    let mut queues: [FairQueue; Priority::COUNT] =
+        std::array::from_fn(|_| FairQueue::new(limits.messages_per_sec_overall as usize));
 
     let mut slow_mode: Option<HashMap<ChatIdHash, (Duration, Instant)>> =
         check_slow_mode.then(HashMap::new);
 
-    let mut rx_is_closed = false;
+    let mut rx_is_closed = [false; Priority::COUNT];
+
+    // Limits of methods which have their own, by `Payload::NAME`
+    let mut limits_of_method: HashMap<&'static str, Option<&Limits>> = HashMap::new();
 
     let mut last_queue_full =
         Instant::now().checked_sub(QUEUE_FULL_DELAY).unwrap_or_else(Instant::now);
 
     let (freeze_tx, mut freeze_rx) = mpsc::channel::<FreezeUntil>(1);
 
-    while !rx_is_closed || !queue.is_empty() {
+    while !rx_is_closed.iter().all(|&closed| closed) || queues.iter().any(|q| !q.is_empty()) {
         // FIXME(waffle):
-        // 1. If the `queue` is empty, `read_from_rx` call down below will 'block'
+        // 1. If the `queues` are empty, `read_from_rx` call down below will 'block'
         //    execution until a request is sent. While the execution is 'blocked' no
         //    `InfoMessage`s could be answered.
         //
@@ -134,7 +154,7 @@ pub(super) async fn worker<B>(
         loop {
             let res = future::select(
                 pin!(freeze_rx.recv()),
-                pin!(read_from_rx(&mut rx, &mut queue, &mut rx_is_closed)),
+                pin!(read_from_rx(&mut rx, &mut queues, &mut rx_is_closed)),
             )
             .map(either)
             .await
@@ -142,16 +162,16 @@ pub(super) async fn worker<B>(
 
             match res {
                 Either::Left(freeze_until) => {
-                    freeze(&mut freeze_rx, slow_mode.as_mut(), &bot, freeze_until).await;
+                    freeze(&mut freeze_rx, slow_mode.as_mut(), &bot, &*backend, freeze_until).await;
                 }
                 Either::Right(()) => break,
             }
         }
-        //debug_assert_eq!(queue.capacity(), limits.messages_per_sec_overall as usize);
 
-        if queue.len() == queue.capacity() && last_queue_full.elapsed() > QUEUE_FULL_DELAY {
+        let queue_is_full = queues.iter().any(FairQueue::is_full);
+        if queue_is_full && last_queue_full.elapsed() > QUEUE_FULL_DELAY {
             last_queue_full = Instant::now();
-            tokio::spawn(on_queue_full(queue.len()));
+            tokio::spawn(on_queue_full(queues.iter().map(FairQueue::len).sum()));
         }
 
         // _Maybe_ we need to use `spawn_blocking` here, because there is
@@ -188,97 +208,108 @@ pub(super) async fn worker<B>(
         // (waffle)
 
         let now = Instant::now();
-        let min_back = now.checked_sub(MINUTE).unwrap_or(now);
-        let sec_back = now.checked_sub(SECOND).unwrap_or(now);
-
-        // make history and requests_sent up-to-date
-        while let Some((_, time)) = history.front() {
-            // history is sorted, we found first up-to-date thing
-            if time >= &min_back {
-                break;
-            }
+        let in_slow_mode = |chat: &ChatIdHash| {
+            slow_mode
+                .as_ref()
+                .and_then(|sm| sm.get(chat))
+                .is_some_and(|&(delay, last)| last + delay > now)
+        };
 
-            if let Some((chat, _)) = history.pop_front() {
-                let entry = requests_sent.per_min.entry(chat).and_modify(|count| {
-                    *count -= 1;
+        // There is no point in offering much more requests than can be granted
+        let max_offered = 2
+            * (limits.messages_per_sec_overall as usize
+                + limits.messages_per_sec_paid_broadcast as usize);
+        let per_chat = limits.messages_per_sec_chat.max(1) as usize;
+
+        // Higher priority requests are first, so they are granted first
+        let mut offered = Vec::new();
+        let mut requests: Vec<PendingRequest<'_>> = Vec::new();
+        let mut visited = [0; Priority::COUNT];
+        for (lane, queue) in queues.iter_mut().enumerate() {
+            let max = max_offered.saturating_sub(requests.len());
+            visited[lane] = queue.offer(per_chat, max, in_slow_mode, |position, queued| {
+                // Methods with their own limits are counted separately
+                let own_limits = *limits_of_method.entry(queued.method).or_insert_with(|| {
+                    // Method names are case-insensitive
+                    method_limits
+                        .iter()
+                        .find(|(method, _)| method.eq_ignore_ascii_case(queued.method))
+                        .map(|(_, limits)| limits)
                 });
-
-                if let Entry::Occupied(entry) = entry {
-                    if *entry.get() == 0 {
-                        entry.remove_entry();
-                    }
-                }
-            }
+                let (method, request_limits) = match own_limits {
+                    Some(own_limits) => (Some(queued.method), own_limits),
+                    None => (None, &limits),
+                };
+
+                offered.push((lane, position));
+                requests.push(PendingRequest {
+                    chat: queued.chat,
+                    method,
+                    paid_broadcast: queued.paid_broadcast,
+                    limits: request_limits,
+                });
+            });
         }
 
-        // as truncates which is ok since in case of truncation it would always be >=
-        // limits.overall_s
-        let used = history.iter().rev().take_while(|(_, time)| time > &sec_back).count() as u32;
-        let mut allowed = limits.messages_per_sec_overall.saturating_sub(used);
+        if requests.is_empty() {
+            for (queue, visited) in queues.iter_mut().zip(visited) {
+                queue.next_round(visited);
+            }
 
-        if allowed == 0 {
-            requests_sent.per_sec.clear();
             tokio::time::sleep(DELAY).await;
             continue;
         }
 
-        for (chat, _) in history.iter().rev().take_while(|(_, time)| time > &sec_back) {
-            *requests_sent.per_sec.entry(*chat).or_insert(0) += 1;
-        }
+        let acquired = backend.acquire(&requests).await;
+        drop(requests);
 
-        let mut queue_removing = queue.removing();
+        let granted = match acquired {
+            Acquired::Granted(granted) => granted,
+            Acquired::Frozen(after) => {
+                log::warn!(
+                    "freezing the bot for approximately {after:?} due to `RetryAfter` error from \
+                     telegram"
+                );
 
-        while let Some(entry) = queue_removing.next() {
-            let chat = &entry.value().0;
+                let set_frozen_until = |until| {
+                    if let Some(shared) = shared.upgrade() {
+                        shared.set_frozen_until(until);
+                    }
+                };
 
-            let slow_mode = slow_mode.as_mut().and_then(|sm| sm.get_mut(chat));
+                set_frozen_until(Some(Instant::now() + after));
+                tokio::time::sleep(after).await;
+                set_frozen_until(None);
 
-            if let Some(&mut (delay, last)) = slow_mode {
-                if last + delay > Instant::now() {
-                    continue;
-                }
+                log::warn!("unfreezing the bot");
+                continue;
             }
+        };
 
-            let requests_sent_per_sec_count = requests_sent.per_sec.get(chat).copied().unwrap_or(0);
-            let requests_sent_per_min_count = requests_sent.per_min.get(chat).copied().unwrap_or(0);
-
-            let messages_per_min_limit = if chat.is_channel_or_supergroup() {
-                limits.messages_per_min_channel_or_supergroup
-            } else {
-                limits.messages_per_min_chat
-            };
-
-            let limits_not_exceeded = requests_sent_per_sec_count < limits.messages_per_sec_chat
-                && requests_sent_per_min_count < messages_per_min_limit;
-
-            if limits_not_exceeded {
-                // Unlock the associated request.
-
-                let chat = *chat;
-                let (_, lock) = entry.remove();
-
-                // Only count request as sent if the request wasn't dropped before unlocked
-                if lock.unlock(retry, freeze_tx.clone()).is_ok() {
-                    *requests_sent.per_sec.entry(chat).or_insert(0) += 1;
-                    *requests_sent.per_min.entry(chat).or_insert(0) += 1;
-                    history.push_back((chat, Instant::now()));
-
-                    if let Some((_, last)) = slow_mode {
-                        *last = Instant::now();
-                    }
-
-                    // We have "sent" one request, so now we can send one less.
-                    allowed -= 1;
-                    if allowed == 0 {
-                        break;
-                    }
+        // Requests are removed in the reverse order, so that positions of the
+        // other offered requests don't change
+        let mut unlocked: Vec<QueuedRequest> = offered
+            .into_iter()
+            .zip(granted)
+            .rev()
+            .filter(|&(_, granted)| granted)
+            .map(|((lane, position), _)| queues[lane].remove(position))
+            .collect();
+        unlocked.reverse();
+
+        for QueuedRequest { chat, lock, .. } in unlocked {
+            // Unlock the associated request.
+            if lock.unlock(retry, freeze_tx.clone()).is_ok() {
+                if let Some((_, last)) = slow_mode.as_mut().and_then(|sm| sm.get_mut(&chat)) {
+                    *last = Instant::now();
                 }
             }
         }
 
-        // It's easier to just recompute last second stats, instead of keeping
-        // track of it alongside with minute stats, so we just throw this away.
-        requests_sent.per_sec.clear();
+        for (queue, visited) in queues.iter_mut().zip(visited) {
+            queue.next_round(visited);
+        }
+
         tokio::time::sleep(DELAY).await;
     }
 }
@@ -303,6 +334,7 @@ async fn freeze(
     rx: &mut mpsc::Receiver<FreezeUntil>,
     mut slow_mode: Option<&mut HashMap<ChatIdHash, (Duration, Instant)>>,
     bot: &impl Requester,
+    backend: &dyn Backend,
     mut imm: Option<FreezeUntil>,
 ) {
     while let Some(freeze_until) = imm.take().or_else(|| rx.try_recv().ok()) {
@@ -340,41 +372,64 @@ async fn freeze(
             .and_then(|m| m.get(&chat).map(|(delay, _)| delay <= &after))
             .unwrap_or(false);
 
-        // Do not sleep if slow mode is enabled since the freeze is most likely caused
+        // Do not freeze if slow mode is enabled since the freeze is most likely caused
         // by the said slow mode and not by the global limits.
         if !slow_mode_enabled_and_likely_the_cause {
-            log::warn!(
-                "freezing the bot for approximately {after:?} due to `RetryAfter` error from \
-                 telegram"
-            );
-
-            tokio::time::sleep_until(until.into()).await;
-
-            log::warn!("unfreezing the bot");
+            backend.freeze(until.saturating_duration_since(Instant::now())).await;
         }
     }
 }
 
-async fn read_from_rx<T>(rx: &mut mpsc::Receiver<T>, queue: &mut Vec<T>, rx_is_closed: &mut bool) {
-    if queue.is_empty() {
+async fn read_from_rx(
+    rx: &mut [mpsc::Receiver<QueuedRequest>],
+    queues: &mut [FairQueue],
+    rx_is_closed: &mut [bool],
+) {
+    if queues.iter().all(FairQueue::is_empty) {
         log::debug!("blocking on queue");
 
-        match rx.recv().await {
-            Some(req) => queue.push(req),
-            None => *rx_is_closed = true,
-        }
+        // Wait for the first request of any priority
+        future::poll_fn(|cx| {
+            let mut all_closed = true;
+
+            for ((rx, queue), rx_is_closed) in
+                rx.iter_mut().zip(&mut *queues).zip(&mut *rx_is_closed)
+            {
+                if *rx_is_closed {
+                    continue;
+                }
+
+                match rx.poll_recv(cx) {
+                    Poll::Ready(Some(req)) => {
+                        queue.push(req);
+                        return Poll::Ready(());
+                    }
+                    Poll::Ready(None) => *rx_is_closed = true,
+                    Poll::Pending => all_closed = false,
+                }
+            }
+
+            if all_closed {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
     }
 
-    // Don't grow queue bigger than the capacity to limit DOS possibility
-    while queue.len() < queue.capacity() {
-        match rx.try_recv() {
-            Ok(req) => queue.push(req),
-            Err(TryRecvError::Disconnected) => {
-                *rx_is_closed = true;
-                break;
+    for ((rx, queue), rx_is_closed) in rx.iter_mut().zip(queues).zip(rx_is_closed) {
+        // Don't grow queue bigger than the capacity to limit DOS possibility
+        while !queue.is_full() {
+            match rx.try_recv() {
+                Ok(req) => queue.push(req),
+                Err(TryRecvError::Disconnected) => {
+                    *rx_is_closed = true;
+                    break;
+                }
+                // There are no items in queue.
+                Err(TryRecvError::Empty) => break,
             }
-            // There are no items in queue.
-            Err(TryRecvError::Empty) => break,
         }
     }
 }
@@ -388,6 +443,8 @@ fn either<L, R>(x: future::Either<L, R>) -> Either<L, R> {
 
 #[cfg(test)]
 mod tests {
+    use crate::adaptors::throttle::fair_queue::FairQueue;
+
     #[tokio::test]
     async fn issue_535() {
         let (tx, mut rx) = tokio::sync::mpsc::channel(1);
@@ -396,6 +453,7 @@ mod tests {
         drop(tx);
 
         // Previously this caused an infinite loop
-        super::read_from_rx::<()>(&mut rx, &mut Vec::new(), &mut false).await;
+        super::read_from_rx(std::slice::from_mut(&mut rx), &mut [FairQueue::new(1)], &mut [false])
+            .await;
     }
 }
@@ -0,0 +1,513 @@
+use std::{
+    collections::HashMap,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll},
+    time::Duration,
+};
+
+use futures::FutureExt;
+use tokio::time::Sleep;
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
+use url::Url;
+
+use crate::{
+    errors::RequestError,
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Limits the time requests can take and allows to cancel them.
+///
+/// Requests which don't complete in time fail with
+/// [`RequestError::TimedOut`]. The timeout is set per method with
+/// [`Settings`] and can be overridden for a single request with
+/// [`TimeoutRequest::timeout`].
+///
+/// Requests can also be cancelled with a [`CancellationToken`] set with
+/// [`TimeoutRequest::cancel_token`]. A cancelled request fails with
+/// [`RequestError::Cancelled`], so the code which awaits it doesn't need to
+/// handle cancellation separately.
+///
+/// Note that this adaptor can only make requests shorter: the timeout of the
+/// [`reqwest::Client`] used by the bot still applies. To use longer timeouts
+/// for some methods (e.g. `getUpdates` with a long polling timeout), build the
+/// client with a long timeout and set shorter timeouts for other methods here.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use teloxide_core::{adaptors::timeout::Settings, prelude::*};
+/// use tokio_util::sync::CancellationToken;
+///
+/// # async {
+/// let bot = Bot::from_env().timeout(
+///     Settings::default()
+///         .default_timeout(Duration::from_secs(10))
+///         .method_timeout("answerCallbackQuery", Duration::from_secs(2)),
+/// );
+///
+/// let me = bot.get_me().timeout(Duration::from_secs(1)).await?;
+///
+/// let token = CancellationToken::new();
+/// let request = bot.get_me().cancel_token(token.clone());
+/// token.cancel();
+/// assert!(request.await.is_err());
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`reqwest::Client`]: reqwest::Client
+#[derive(Clone, Debug)]
+pub struct Timeout<B> {
+    inner: B,
+    settings: Arc<Settings>,
+}
+
+/// Settings of the [`Timeout`] adaptor.
+#[derive(Clone, Debug, Default)]
+#[must_use]
+#[non_exhaustive]
+pub struct Settings {
+    /// Timeout of requests of methods which don't have their own timeout.
+    ///
+    /// `None` means no timeout.
+    pub default_timeout: Option<Duration>,
+
+    /// Timeouts of methods, by the name of the method in the Bot API (e.g.
+    /// `"sendMessage"`, case-insensitive).
+    pub method_timeouts: HashMap<String, Duration>,
+}
+
+impl<B> Timeout<B> {
+    /// Creates new timeout adaptor.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::timeout`] instead.
+    ///
+    /// [`RequesterExt::timeout`]: crate::requests::RequesterExt::timeout
+    pub fn new(inner: B, settings: Settings) -> Self {
+        let Settings { default_timeout, method_timeouts } = settings;
+        let method_timeouts = method_timeouts
+            .into_iter()
+            .map(|(method, timeout)| (method.to_ascii_lowercase(), timeout))
+            .collect();
+
+        Self { inner, settings: Arc::new(Settings { default_timeout, method_timeouts }) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the settings used by this adaptor.
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+impl Settings {
+    /// Sets the timeout of requests of methods which don't have their own
+    /// timeout, see [`Settings::method_timeout`].
+    pub fn default_timeout(mut self, val: Duration) -> Self {
+        self.default_timeout = Some(val);
+        self
+    }
+
+    /// Sets the timeout of a method.
+    ///
+    /// `method` is the name of the method in the Bot API, e.g.
+    /// `"sendMessage"` (case-insensitive).
+    pub fn method_timeout(mut self, method: impl Into<String>, timeout: Duration) -> Self {
+        self.method_timeouts.insert(method.into().to_ascii_lowercase(), timeout);
+        self
+    }
+
+    /// Returns the timeout of requests of the method `name`.
+    fn timeout_of(&self, name: &str) -> Option<Duration> {
+        let name = name.trim_end_matches("Inline").to_ascii_lowercase();
+        self.method_timeouts.get(&name).copied().or(self.default_timeout)
+    }
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        TimeoutRequest<B::$T>
+    };
+}
+
+macro_rules! fwd_inner {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        TimeoutRequest::new($this.inner().$m($($arg),*), &$this.settings)
+    };
+}
+
+impl<B> Requester for Timeout<B>
+where
+    B: Requester,
+    B::Err: From<RequestError>,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fwd_inner, fty
+    }
+}
+
+download_forward! {
+    B
+    Timeout<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`Timeout`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct TimeoutRequest<R> {
+    inner: R,
+    timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+}
+
+impl<R> TimeoutRequest<R> {
+    fn new(inner: R, settings: &Settings) -> Self
+    where
+        R: HasPayload,
+    {
+        let timeout = settings.timeout_of(R::Payload::NAME);
+        Self { inner, timeout, cancel: None }
+    }
+
+    /// Sets the timeout of this request, overriding the one set in
+    /// [`Settings`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Removes the timeout of this request.
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets a token which cancels this request.
+    ///
+    /// When the token is cancelled, the request is dropped and fails with
+    /// [`RequestError::Cancelled`]. Note that Telegram might have already
+    /// received and handled the request at that point.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+impl<R> HasPayload for TimeoutRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for TimeoutRequest<R>
+where
+    R: Request,
+    R::Err: From<RequestError>,
+{
+    type Err = R::Err;
+
+    type Send = TimeoutSend<R::Send>;
+
+    type SendRef = TimeoutSend<R::SendRef>;
+
+    fn send(self) -> Self::Send {
+        TimeoutSend::new(self.inner.send(), self.timeout, self.cancel)
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        TimeoutSend::new(self.inner.send_ref(), self.timeout, self.cancel.clone())
+    }
+}
+
+impl<R> IntoFuture for TimeoutRequest<R>
+where
+    R: Request,
+    R::Err: From<RequestError>,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`TimeoutRequest`]s.
+#[pin_project::pin_project]
+pub struct TimeoutSend<F> {
+    #[pin]
+    inner: F,
+    sleep: Option<Pin<Box<Sleep>>>,
+    cancelled: Option<Pin<Box<WaitForCancellationFutureOwned>>>,
+}
+
+impl<F> TimeoutSend<F> {
+    fn new(inner: F, timeout: Option<Duration>, cancel: Option<CancellationToken>) -> Self {
+        Self {
+            inner,
+            sleep: timeout.map(|timeout| Box::pin(tokio::time::sleep(timeout))),
+            cancelled: cancel.map(|token| Box::pin(token.cancelled_owned())),
+        }
+    }
+}
+
+impl<F, T, E> Future for TimeoutSend<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: From<RequestError>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if let Some(cancelled) = this.cancelled {
+            if cancelled.poll_unpin(cx).is_ready() {
+                return Poll::Ready(Err(RequestError::Cancelled.into()));
+            }
+        }
+
+        if let Poll::Ready(ret) = this.inner.poll(cx) {
+            return Poll::Ready(ret);
+        }
+
+        if let Some(sleep) = this.sleep {
+            if sleep.poll_unpin(cx).is_ready() {
+                return Poll::Ready(Err(RequestError::TimedOut.into()));
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{net::TcpListener, time::Duration};
+
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        adaptors::timeout::{Settings, Timeout},
+        errors::RequestError,
+        prelude::*,
+    };
+
+    #[tokio::test]
+    async fn times_out_and_cancels() {
+        // Connections are never accepted, so requests never complete
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap()).parse().unwrap();
+        let bot = Timeout::new(
+            Bot::new("0:token").set_api_url(url),
+            Settings::default()
+                .default_timeout(Duration::from_secs(60))
+                .method_timeout("getMe", Duration::from_millis(50)),
+        );
+
+        assert!(matches!(bot.get_me().await, Err(RequestError::TimedOut)));
+        let res = bot.get_webhook_info().timeout(Duration::from_millis(50)).await;
+        assert!(matches!(res, Err(RequestError::TimedOut)));
+
+        let token = CancellationToken::new();
+        let request = tokio::spawn(bot.get_me().no_timeout().cancel_token(token.clone()).send());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        token.cancel();
+        assert!(matches!(request.await.unwrap(), Err(RequestError::Cancelled)));
+
+        drop(listener);
+    }
+}
@@ -0,0 +1,557 @@
+use std::{
+    fmt::Display,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::atomic::{AtomicU32, Ordering},
+    task::{self, Poll},
+};
+
+use futures::ready;
+use tracing::{field, Span};
+use url::Url;
+
+use crate::{
+    errors::ErrorLabel,
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Opens a [`tracing`] span for every request.
+///
+/// Unlike [`Trace`], which logs requests with the `log` facade, this adaptor
+/// creates an `INFO` span named `request` for every sent request, with the
+/// following fields:
+///
+/// - `method` — name of the method, e.g. `SendMessage`
+/// - `chat_id` — the `chat_id` parameter, if the method has one
+/// - `attempt` — number of the attempt to send this request, starting from 1
+/// - `result` — `ok` or `error`, recorded when the request is completed
+/// - `error` — [label] of the error, if the request failed
+///
+/// The span is a child of the span which is current when the request is sent,
+/// so if your handlers are instrumented (for example, with
+/// `teloxide::dispatching::UpdateHandlerTracingExt`), requests show up
+/// inside spans of the updates which caused them.
+///
+/// Every `send`/`send_ref` call is a new attempt, so to get a span for every
+/// retry of the [`Retry`] adaptor, put this adaptor inside of it:
+/// `bot.trace_spans().retry(policy)`.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use teloxide_core::{prelude::*, types::ChatId};
+/// use tracing::Instrument;
+///
+/// # async {
+/// let bot = Bot::from_env().trace_spans();
+///
+/// // Shows up as `update{update_id=1}:request{method="SendMessage" chat_id=0 attempt=1 result="ok"}`
+/// async { bot.send_message(ChatId(0), "Hi!").await }
+///     .instrument(tracing::info_span!("update", update_id = 1))
+///     .await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`Trace`]: crate::adaptors::Trace
+/// [label]: ErrorLabel
+/// [`Retry`]: crate::adaptors::Retry
+#[derive(Clone, Debug)]
+pub struct TraceSpans<B> {
+    inner: B,
+}
+
+impl<B> TraceSpans<B> {
+    /// Creates new adaptor.
+    ///
+    /// Note: it's recommended to use [`RequesterExt::trace_spans`] instead.
+    ///
+    /// [`RequesterExt::trace_spans`]: crate::requests::RequesterExt::trace_spans
+    pub fn new(inner: B) -> Self {
+        Self { inner }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        TraceSpansRequest::new(
+            $this.inner().$m($($arg),*),
+            |p| Some(&p.chat_id as &dyn Display),
+        )
+    };
+}
+
+macro_rules! fid {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        TraceSpansRequest::new($this.inner().$m($($arg),*), |_| None)
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        TraceSpansRequest<B::$T>
+    };
+}
+
+impl<B> Requester for TraceSpans<B>
+where
+    B: Requester,
+    B::Err: ErrorLabel,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        stop_message_live_location,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        get_user_chat_boosts,
+        edit_message_text,
+        edit_message_caption,
+        edit_message_media,
+        edit_message_reply_markup,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        send_gift_chat,
+        verify_chat,
+        remove_chat_verification,
+        read_business_message,
+        send_invoice,
+        send_game,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => f, fty
+    }
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        edit_message_live_location_inline,
+        stop_message_live_location_inline,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        get_forum_topic_icon_stickers,
+        answer_callback_query,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text_inline,
+        edit_message_caption_inline,
+        edit_message_media_inline,
+        edit_message_reply_markup_inline,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        gift_premium_subscription,
+        verify_user,
+        remove_user_verification,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores
+        => fid, fty
+    }
+}
+
+download_forward! {
+    B
+    TraceSpans<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`TraceSpans`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+pub struct TraceSpansRequest<R: HasPayload> {
+    inner: R,
+    chat_id: fn(&R::Payload) -> Option<&dyn Display>,
+    attempts: AtomicU32,
+}
+
+impl<R: HasPayload> TraceSpansRequest<R> {
+    fn new(inner: R, chat_id: fn(&R::Payload) -> Option<&dyn Display>) -> Self {
+        Self { inner, chat_id, attempts: AtomicU32::new(0) }
+    }
+
+    fn span(&self) -> Span {
+        let attempt = self.attempts.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let span = tracing::info_span!(
+            "request",
+            method = R::Payload::NAME,
+            chat_id = field::Empty,
+            attempt,
+            result = field::Empty,
+            error = field::Empty,
+        );
+        if let Some(chat_id) = (self.chat_id)(self.inner.payload_ref()) {
+            span.record("chat_id", field::display(chat_id));
+        }
+
+        span
+    }
+}
+
+impl<R> Clone for TraceSpansRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            chat_id: self.chat_id,
+            attempts: AtomicU32::new(self.attempts.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl<R> HasPayload for TraceSpansRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for TraceSpansRequest<R>
+where
+    R: Request,
+    R::Err: ErrorLabel,
+{
+    type Err = R::Err;
+
+    type Send = TraceSpansSend<R::Send>;
+
+    type SendRef = TraceSpansSend<R::SendRef>;
+
+    fn send(self) -> Self::Send {
+        let span = self.span();
+        let inner = span.in_scope(|| self.inner.send());
+
+        TraceSpansSend { span, inner }
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let span = self.span();
+        let inner = span.in_scope(|| self.inner.send_ref());
+
+        TraceSpansSend { span, inner }
+    }
+}
+
+impl<R> IntoFuture for TraceSpansRequest<R>
+where
+    R: Request,
+    R::Err: ErrorLabel,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`TraceSpansRequest`]s.
+#[pin_project::pin_project]
+pub struct TraceSpansSend<F> {
+    span: Span,
+    #[pin]
+    inner: F,
+}
+
+impl<F, T, E> Future for TraceSpansSend<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: ErrorLabel,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _enter = this.span.enter();
+
+        let ret = ready!(this.inner.poll(cx));
+        match &ret {
+            Ok(_) => {
+                this.span.record("result", "ok");
+            }
+            Err(err) => {
+                this.span.record("result", "error");
+                this.span.record("error", err.label());
+            }
+        }
+
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{
+        collections::HashMap,
+        fmt,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Instrument, Metadata, Subscriber,
+    };
+
+    use crate::{
+        errors::{ApiError, RequestError},
+        payloads::SendMessage,
+        prelude::*,
+        testing::MockBot,
+        types::ChatId,
+    };
+
+    #[derive(Debug, Default, Clone)]
+    struct SpanData {
+        name: &'static str,
+        parent: Option<u64>,
+        fields: HashMap<&'static str, String>,
+    }
+
+    impl Visit for SpanData {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.fields.insert(field.name(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields.insert(field.name(), value.to_owned());
+        }
+    }
+
+    /// A subscriber which remembers all spans.
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        spans: Mutex<HashMap<u64, SpanData>>,
+        stack: Mutex<Vec<u64>>,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+            let parent = match attrs.parent() {
+                Some(parent) => Some(parent.into_u64()),
+                None if attrs.is_contextual() => self.stack.lock().unwrap().last().copied(),
+                None => None,
+            };
+
+            let mut data = SpanData { name: attrs.metadata().name(), parent, ..<_>::default() };
+            attrs.record(&mut data);
+            self.spans.lock().unwrap().insert(id, data);
+
+            span::Id::from_u64(id)
+        }
+
+        fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+            values.record(self.spans.lock().unwrap().get_mut(&span.into_u64()).unwrap());
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &span::Id) {
+            self.stack.lock().unwrap().push(span.into_u64());
+        }
+
+        fn exit(&self, _: &span::Id) {
+            self.stack.lock().unwrap().pop();
+        }
+    }
+
+    #[tokio::test]
+    async fn spans() {
+        let recorder = Arc::new(Recorder::default());
+        let _guard = tracing::subscriber::set_default(Arc::clone(&recorder));
+
+        let inner = MockBot::new();
+        inner.respond_error::<SendMessage>(RequestError::from(ApiError::BotBlocked));
+        let bot = inner.trace_spans();
+
+        let request = bot.send_message(ChatId(42), "Hi!");
+        let update = tracing::info_span!("update");
+        async { request.send_ref().await }.instrument(update.clone()).await.unwrap_err();
+        request.send_ref().await.unwrap();
+        bot.get_me().await.unwrap();
+
+        let spans = recorder.spans.lock().unwrap();
+        let update_id = update.id().unwrap().into_u64();
+        let mut requests = spans
+            .iter()
+            .filter(|(_, span)| span.name == "request")
+            .map(|(&id, span)| (id, span.clone()))
+            .collect::<Vec<_>>();
+        requests.sort_by_key(|&(id, _)| id);
+        let requests = requests.into_iter().map(|(_, span)| span).collect::<Vec<_>>();
+
+        assert_eq!(requests.len(), 3);
+
+        assert_eq!(requests[0].parent, Some(update_id));
+        assert_eq!(requests[0].fields["method"], "SendMessage");
+        assert_eq!(requests[0].fields["chat_id"], "42");
+        assert_eq!(requests[0].fields["attempt"], "1");
+        assert_eq!(requests[0].fields["result"], "error");
+        assert_eq!(requests[0].fields["error"], "BotBlocked");
+
+        assert_eq!(requests[1].parent, None);
+        assert_eq!(requests[1].fields["attempt"], "2");
+        assert_eq!(requests[1].fields["result"], "ok");
+        assert!(!requests[1].fields.contains_key("error"));
+
+        assert_eq!(requests[2].fields["method"], "GetMe");
+        assert!(!requests[2].fields.contains_key("chat_id"));
+    }
+}
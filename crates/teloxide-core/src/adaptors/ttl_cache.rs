@@ -0,0 +1,693 @@
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{self, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    requests::{HasPayload, Output, Payload, Request, Requester},
+    types::*,
+};
+
+/// Caches responses of read-only methods for a configurable time.
+///
+/// Requests are identified by their method and payload, so e.g.
+/// `get_chat_member(chat, user)` is only sent to Telegram if the same member
+/// of the same chat wasn't requested in the last [`Settings::ttl`] of
+/// `getChatMember`. Only successful responses are cached.
+///
+/// By default, responses of [`get_chat`], [`get_chat_member`],
+/// [`get_chat_administrators`] and [`get_chat_member_count`] are cached for a
+/// minute. Other `get_*` methods (e.g. [`get_my_commands`] or
+/// [`get_sticker_set`]) can be cached too, if they have a TTL set with
+/// [`Settings::ttl`]. All other methods are never cached.
+///
+/// ## Invalidation
+///
+/// Cached responses can be outdated, e.g. after a user was promoted to an
+/// administrator. Chat members updates (`chat_member` and `my_chat_member`)
+/// can be used to invalidate responses cached for their chat, see
+/// [`Invalidator::invalidate_update`]. When the `ttl-cache` feature of
+/// `teloxide` is enabled, the dispatcher can do this automatically, see
+/// `DispatcherBuilder::invalidate_ttl_cache`.
+///
+/// Requests sent with `@channelusername` are not invalidated by chat ID.
+/// Responses of methods which change the state (e.g. `set_my_commands`) don't
+/// invalidate the cache either.
+///
+/// ## Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use teloxide_core::{
+///     adaptors::{ttl_cache::Settings, TtlCache},
+///     prelude::*,
+///     types::{ChatId, UserId},
+/// };
+///
+/// # async {
+/// let settings = Settings::default().ttl("getMyCommands", Duration::from_secs(600));
+/// let bot = TtlCache::with_settings(Bot::from_env(), settings);
+///
+/// // The first request is sent to Telegram...
+/// bot.get_chat_member(ChatId(-1), UserId(1)).await?;
+/// // ...the second one is answered from the cache
+/// bot.get_chat_member(ChatId(-1), UserId(1)).await?;
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`get_chat`]: crate::requests::Requester::get_chat
+/// [`get_chat_member`]: crate::requests::Requester::get_chat_member
+/// [`get_chat_administrators`]: crate::requests::Requester::get_chat_administrators
+/// [`get_chat_member_count`]: crate::requests::Requester::get_chat_member_count
+/// [`get_my_commands`]: crate::requests::Requester::get_my_commands
+/// [`get_sticker_set`]: crate::requests::Requester::get_sticker_set
+#[derive(Clone)]
+pub struct TtlCache<B> {
+    inner: B,
+    cache: Arc<Cache>,
+}
+
+/// Settings of [`TtlCache`].
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct Settings {
+    ttls: HashMap<String, Duration>,
+    max_entries: usize,
+}
+
+/// Invalidates responses cached by a [`TtlCache`].
+///
+/// Returned by [`TtlCache::invalidator`], can be cloned and used after the
+/// bot was moved.
+#[derive(Clone)]
+pub struct Invalidator {
+    cache: Arc<Cache>,
+}
+
+struct Cache {
+    // Lowercase method names
+    ttls: HashMap<String, Duration>,
+    max_entries: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    // Keys in the order of insertion, alongside with the `seq` of the entry,
+    // used to evict the oldest entries. Keys of removed or replaced entries are
+    // skipped.
+    order: VecDeque<(String, u64)>,
+    seq: u64,
+}
+
+struct Entry {
+    value: Arc<dyn Any + Send + Sync>,
+    expires: Instant,
+    chat: Option<ChatId>,
+    seq: u64,
+}
+
+impl<B> TtlCache<B> {
+    /// Creates new adaptor with the default [`Settings`].
+    ///
+    /// Note: it's recommended to use [`RequesterExt::ttl_cache`] instead.
+    ///
+    /// [`RequesterExt::ttl_cache`]: crate::requests::RequesterExt::ttl_cache
+    pub fn new(inner: B) -> Self {
+        Self::with_settings(inner, Settings::default())
+    }
+
+    /// Creates new adaptor with the given [`Settings`].
+    pub fn with_settings(inner: B, Settings { ttls, max_entries }: Settings) -> Self {
+        // Method names are case-insensitive
+        let ttls =
+            ttls.into_iter().map(|(method, ttl)| (method.to_ascii_lowercase(), ttl)).collect();
+        let cache = Cache { ttls, max_entries, entries: <_>::default() };
+        Self { inner, cache: Arc::new(cache) }
+    }
+
+    /// Allows to access the inner bot.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwraps the inner bot.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns an [`Invalidator`] of this cache.
+    pub fn invalidator(&self) -> Invalidator {
+        Invalidator { cache: Arc::clone(&self.cache) }
+    }
+}
+
+impl<B: fmt::Debug> fmt::Debug for TtlCache<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TtlCache").field("inner", &self.inner).finish_non_exhaustive()
+    }
+}
+
+impl Settings {
+    /// Sets the time for which responses of `method` are cached, e.g.
+    /// `Duration::from_secs(60)` for `"getChatMember"`.
+    ///
+    /// Method names are case-insensitive. [`Duration::ZERO`] disables caching
+    /// of the method. Methods which don't only read data are never cached.
+    pub fn ttl(mut self, method: impl Into<String>, ttl: Duration) -> Self {
+        self.ttls.insert(method.into().to_ascii_lowercase(), ttl);
+        self
+    }
+
+    /// Sets the maximum number of cached responses.
+    ///
+    /// When the cache is full, the oldest responses are removed. Default is
+    /// `10_000`.
+    pub fn max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        const MINUTE: Duration = Duration::from_secs(60);
+
+        let ttls = ["getChat", "getChatMember", "getChatAdministrators", "getChatMemberCount"]
+            .into_iter()
+            .map(|method| (method.to_ascii_lowercase(), MINUTE))
+            .collect();
+
+        Self { ttls, max_entries: 10_000 }
+    }
+}
+
+impl Invalidator {
+    /// Removes all responses cached for requests to the chat `chat_id`.
+    pub fn invalidate_chat(&self, chat_id: ChatId) {
+        let mut entries = self.cache.entries.lock().unwrap();
+        entries.map.retain(|_, entry| entry.chat != Some(chat_id));
+    }
+
+    /// Removes responses which may be outdated because of `update`.
+    ///
+    /// For `chat_member` and `my_chat_member` updates, all responses cached
+    /// for the chat of the update are removed. Other updates are ignored.
+    pub fn invalidate_update(&self, update: &Update) {
+        match &update.kind {
+            UpdateKind::ChatMember(member) | UpdateKind::MyChatMember(member) => {
+                self.invalidate_chat(member.chat.id)
+            }
+            _ => {}
+        }
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        *self.cache.entries.lock().unwrap() = Entries::default();
+    }
+}
+
+impl fmt::Debug for Invalidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Invalidator").finish_non_exhaustive()
+    }
+}
+
+impl Cache {
+    fn ttl(&self, method: &str) -> Option<Duration> {
+        self.ttls.get(&method.to_ascii_lowercase()).copied().filter(|ttl| !ttl.is_zero())
+    }
+
+    fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.map.get(key)?;
+
+        if entry.expires <= Instant::now() {
+            entries.map.remove(key);
+            return None;
+        }
+
+        entry.value.downcast_ref::<T>().cloned()
+    }
+
+    fn insert<T>(&self, key: String, value: T, ttl: Duration, chat: Option<ChatId>)
+    where
+        T: Send + Sync + 'static,
+    {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        if !entries.map.contains_key(&key) && entries.map.len() >= self.max_entries {
+            entries.map.retain(|_, entry| entry.expires > now);
+        }
+        while !entries.map.contains_key(&key) && entries.map.len() >= self.max_entries {
+            let Some((oldest, seq)) = entries.order.pop_front() else { break };
+            if entries.map.get(&oldest).is_some_and(|entry| entry.seq == seq) {
+                entries.map.remove(&oldest);
+            }
+        }
+
+        entries.seq += 1;
+        let seq = entries.seq;
+        let entry = Entry { value: Arc::new(value), expires: now + ttl, chat, seq };
+        entries.map.insert(key.clone(), entry);
+        entries.order.push_back((key, seq));
+
+        // Don't let keys of removed entries accumulate
+        if entries.order.len() > self.max_entries.saturating_mul(2) {
+            let Entries { map, order, .. } = &mut *entries;
+            order.retain(|(key, seq)| map.get(key).is_some_and(|entry| entry.seq == *seq));
+        }
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        TtlCacheRequest {
+            inner: Arc::new($this.inner().$m($($arg),*)),
+            cache: Arc::clone(&$this.cache),
+        }
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        TtlCacheRequest<B::$T>
+    };
+}
+
+macro_rules! fid {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        $this.inner().$m($($arg),*)
+    };
+}
+
+macro_rules! ftyid {
+    ($T:ident) => {
+        B::$T
+    };
+}
+
+impl<B> Requester for TtlCache<B>
+where
+    B: Requester,
+    B::Err: Send,
+    B::GetMe: Clone + Send + Sync + 'static,
+    B::GetChat: Clone + Send + Sync + 'static,
+    B::GetChatAdministrators: Clone + Send + Sync + 'static,
+    B::GetChatMembersCount: Clone + Send + Sync + 'static,
+    B::GetChatMemberCount: Clone + Send + Sync + 'static,
+    B::GetChatMember: Clone + Send + Sync + 'static,
+    B::GetUserProfilePhotos: Clone + Send + Sync + 'static,
+    B::GetStickerSet: Clone + Send + Sync + 'static,
+    B::GetCustomEmojiStickers: Clone + Send + Sync + 'static,
+    B::GetForumTopicIconStickers: Clone + Send + Sync + 'static,
+    B::GetMyCommands: Clone + Send + Sync + 'static,
+    B::GetMyName: Clone + Send + Sync + 'static,
+    B::GetMyDescription: Clone + Send + Sync + 'static,
+    B::GetMyShortDescription: Clone + Send + Sync + 'static,
+    B::GetChatMenuButton: Clone + Send + Sync + 'static,
+    B::GetMyDefaultAdministratorRights: Clone + Send + Sync + 'static,
+    B::GetUserChatBoosts: Clone + Send + Sync + 'static,
+    B::GetBusinessConnection: Clone + Send + Sync + 'static,
+    B::GetAvailableGifts: Clone + Send + Sync + 'static,
+{
+    type Err = B::Err;
+
+    requester_forward! {
+        get_me,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        get_user_profile_photos,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        get_forum_topic_icon_stickers,
+        get_my_commands,
+        get_my_name,
+        get_my_description,
+        get_my_short_description,
+        get_chat_menu_button,
+        get_my_default_administrator_rights,
+        get_user_chat_boosts,
+        get_business_connection,
+        get_available_gifts
+        => f, fty
+    }
+
+    requester_forward! {
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_sticker,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        set_my_commands,
+        set_my_name,
+        set_my_description,
+        set_my_short_description,
+        set_chat_menu_button,
+        set_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => fid, ftyid
+    }
+}
+
+download_forward! {
+    B
+    TtlCache<B>
+    { this => this.inner() }
+}
+
+/// Request returned by [`TtlCache`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+pub struct TtlCacheRequest<R> {
+    inner: Arc<R>,
+    cache: Arc<Cache>,
+}
+
+impl<R> Clone for TtlCacheRequest<R> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner), cache: Arc::clone(&self.cache) }
+    }
+}
+
+impl<R> HasPayload for TtlCacheRequest<R>
+where
+    R: HasPayload + Clone,
+{
+    type Payload = R::Payload;
+
+    /// Note that if this request was already sent via `send_ref` and it
+    /// didn't yet complete, this method will clone the underlying request.
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        Arc::make_mut(&mut self.inner).payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for TtlCacheRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Payload: Serialize,
+    R::Err: Send,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    type Err = R::Err;
+
+    type Send = TtlCacheSend<R>;
+
+    type SendRef = TtlCacheSend<R>;
+
+    fn send(self) -> Self::Send {
+        TtlCacheSend(send(self).boxed())
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        TtlCacheSend(send(self.clone()).boxed())
+    }
+}
+
+impl<R> IntoFuture for TtlCacheRequest<R>
+where
+    R: Request + Clone + Send + Sync + 'static,
+    R::Payload: Serialize,
+    R::Err: Send,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    type Output = Result<Output<Self>, <Self as Request>::Err>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+/// Future returned by [`TtlCacheRequest`]s.
+#[pin_project::pin_project]
+pub struct TtlCacheSend<R: Request>(#[pin] BoxFuture<'static, Result<Output<R>, R::Err>>);
+
+impl<R: Request> Future for TtlCacheSend<R> {
+    type Output = Result<Output<R>, R::Err>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        self.project().0.poll(cx)
+    }
+}
+
+async fn send<R>(request: TtlCacheRequest<R>) -> Result<Output<R>, R::Err>
+where
+    R: Request,
+    R::Payload: Serialize,
+    Output<R>: Clone + Send + Sync + 'static,
+{
+    let TtlCacheRequest { inner, cache } = request;
+
+    let name = R::Payload::NAME;
+    let Some(ttl) = cache.ttl(name) else { return inner.send_ref().await };
+
+    let payload = match serde_json::to_value(inner.payload_ref()) {
+        Ok(payload) => payload,
+        Err(err) => {
+            log::warn!("Couldn't serialize a payload of {name} to look up the cache: {err}");
+            return inner.send_ref().await;
+        }
+    };
+    let key = format!("{name}:{payload}");
+
+    if let Some(output) = cache.get::<Output<R>>(&key) {
+        return Ok(output);
+    }
+
+    let output = inner.send_ref().await?;
+    let chat = payload.get("chat_id").and_then(serde_json::Value::as_i64).map(ChatId);
+    cache.insert(key, output.clone(), ttl, chat);
+
+    Ok(output)
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        adaptors::{ttl_cache::Settings, TtlCache},
+        errors::{ApiError, RequestError},
+        payloads::GetChatMemberCount,
+        prelude::*,
+        testing::{ChatBuilder, MockBot, UpdateBuilder, UserBuilder},
+        types::{
+            ChatId, ChatMember, ChatMemberKind, ChatMemberUpdated, Member, UpdateKind, UserId,
+        },
+    };
+
+    #[tokio::test]
+    async fn caches_and_invalidates() {
+        let bot = MockBot::new();
+        let settings = Settings::default().ttl("getChatMemberCount", Duration::from_millis(100));
+        let cache = TtlCache::with_settings(bot.clone(), settings);
+
+        for _ in 0..2 {
+            cache.get_chat_administrators(ChatId(-1)).await.unwrap();
+            cache.get_chat_administrators(ChatId(-2)).await.unwrap();
+        }
+        assert_eq!(bot.requests().len(), 2);
+
+        // Not cached by default
+        cache.get_my_commands().await.unwrap();
+        cache.get_my_commands().await.unwrap();
+        assert_eq!(bot.requests().len(), 4);
+
+        // Errors are not cached
+        bot.respond_error::<GetChatMemberCount>(RequestError::from(ApiError::ChatNotFound));
+        cache.get_chat_member_count(ChatId(-1)).await.unwrap_err();
+        bot.respond::<GetChatMemberCount>(3);
+        assert_eq!(cache.get_chat_member_count(ChatId(-1)).await.unwrap(), 3);
+        assert_eq!(cache.get_chat_member_count(ChatId(-1)).await.unwrap(), 3);
+        assert_eq!(bot.requests().len(), 6);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        bot.respond::<GetChatMemberCount>(4);
+        assert_eq!(cache.get_chat_member_count(ChatId(-1)).await.unwrap(), 4);
+        assert_eq!(bot.requests().len(), 7);
+
+        let user = UserBuilder::new(UserId(1)).build();
+        let update = UpdateBuilder::new(UpdateKind::ChatMember(ChatMemberUpdated {
+            chat: ChatBuilder::group(ChatId(-1), "Group").build(),
+            from: user.clone(),
+            date: chrono::DateTime::UNIX_EPOCH,
+            old_chat_member: ChatMember {
+                user: user.clone(),
+                kind: ChatMemberKind::Member(Member { until_date: None }),
+            },
+            new_chat_member: ChatMember { user, kind: ChatMemberKind::Left },
+            invite_link: None,
+            via_join_request: false,
+            via_chat_folder_invite_link: false,
+        }))
+        .build();
+        cache.invalidator().invalidate_update(&update);
+        cache.get_chat_administrators(ChatId(-1)).await.unwrap();
+        cache.get_chat_administrators(ChatId(-1)).await.unwrap();
+        cache.get_chat_administrators(ChatId(-2)).await.unwrap();
+        assert_eq!(bot.requests().len(), 8);
+    }
+}
@@ -1,10 +1,10 @@
-use std::{future::Future, sync::Arc};
+use std::{fmt, future::Future, sync::Arc};
 
 use reqwest::Client;
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
-    net,
+    net::{self, HttpTransport},
     requests::{MultipartPayload, Payload, ResponseResult},
     serde_multipart,
 };
@@ -45,6 +45,34 @@ const TELOXIDE_API_URL: &str = "TELOXIDE_API_URL";
 ///
 /// [`Download<'_>`]: crate::net::Download
 ///
+/// ## Local Bot API server
+///
+/// A [self-hosted Bot API server][tbas] started with `--local` lifts most of
+/// the file limits, but changes how files are exchanged: [`GetFile`] returns
+/// absolute paths on the server's filesystem and uploads may reference local
+/// files by `file://` URIs. Use [`Bot::set_local_mode`] to tell the bot about
+/// that:
+///
+/// - files are downloaded by reading the path returned by [`GetFile`] directly,
+/// - [`InputFile::file`]s are sent as `file://` references instead of being
+///   uploaded via `multipart/form-data`.
+///
+/// Note that this requires the bot and the server to share a filesystem.
+///
+/// | Limit         | Cloud Bot API | Local Bot API server |
+/// |---------------|---------------|----------------------|
+/// | Upload size   | 50 MB         | 2000 MB              |
+/// | Download size | 20 MB         | unlimited            |
+///
+/// [tbas]: https://github.com/tdlib/telegram-bot-api
+/// [`GetFile`]: crate::payloads::GetFile
+/// [`InputFile::file`]: crate::types::InputFile::file
+///
+/// ## HTTP transport
+///
+/// By default requests are sent with a [`reqwest::Client`]. Any other
+/// [`HttpTransport`] can be used instead with [`Bot::with_transport`].
+///
 /// ## Clone cost
 ///
 /// `Bot::clone` is relatively cheap, so if you need to share `Bot`, it's
@@ -53,11 +81,14 @@ const TELOXIDE_API_URL: &str = "TELOXIDE_API_URL";
 /// [`Arc`]: std::sync::Arc
 /// [Telegram Bot API]: https://core.telegram.org/bots/api
 #[must_use]
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Bot {
-    token: Arc<str>,
+    token: Token,
     api_url: Arc<reqwest::Url>,
-    client: Client,
+    // `None` if a custom transport is used
+    client: Option<Client>,
+    transport: Arc<dyn HttpTransport>,
+    local: bool,
 }
 
 /// Constructors
@@ -91,13 +122,32 @@ impl Bot {
     where
         S: Into<String>,
     {
-        let token = Into::<String>::into(token).into();
+        let mut bot = Self::with_transport(token, client.clone());
+        bot.client = Some(client);
+        bot
+    }
+
+    /// Creates a new `Bot` with the specified token and a custom
+    /// [`HttpTransport`].
+    ///
+    /// This allows to send requests with an HTTP client other than
+    /// [`reqwest::Client`] or to answer them in memory, see [`HttpTransport`]
+    /// for more.
+    ///
+    /// Note that timeouts are the responsibility of the transport, see
+    /// [`net::default_reqwest_settings`] for the ones used by default.
+    pub fn with_transport<S, T>(token: S, transport: T) -> Self
+    where
+        S: Into<String>,
+        T: HttpTransport,
+    {
+        let token = Token(Into::<String>::into(token).into());
         let api_url = Arc::new(
             reqwest::Url::parse(net::TELEGRAM_API_URL)
                 .expect("Failed to parse the default TBA URL"),
         );
 
-        Self { token, api_url, client }
+        Self { token, api_url, client: None, transport: Arc::new(transport), local: false }
     }
 
     /// Creates a new `Bot` with the `TELOXIDE_TOKEN` & `TELOXIDE_API_URL` &
@@ -199,6 +249,37 @@ impl Bot {
         self.api_url = Arc::new(url);
         self
     }
+
+    /// Sets whether the bot talks to a [local Bot API server][tbas] (one
+    /// started with the `--local` flag).
+    ///
+    /// In local mode [`Download`] reads the absolute paths returned by
+    /// [`GetFile`] directly from the filesystem and [`InputFile::file`]s are
+    /// sent as `file://` references, so the server reads them itself instead
+    /// of them being uploaded. See also the [limits of each mode].
+    ///
+    /// This does not change the API URL, use [`Bot::set_api_url`] for that.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use teloxide_core::Bot;
+    ///
+    /// let url = reqwest::Url::parse("http://localhost:8081").unwrap();
+    /// let bot = Bot::new("TOKEN").set_api_url(url).set_local_mode(true);
+    ///
+    /// assert!(bot.local_mode());
+    /// ```
+    ///
+    /// [tbas]: https://github.com/tdlib/telegram-bot-api
+    /// [`Download`]: crate::net::Download
+    /// [`GetFile`]: crate::payloads::GetFile
+    /// [`InputFile::file`]: crate::types::InputFile::file
+    /// [limits of each mode]: Bot#local-bot-api-server
+    pub fn set_local_mode(mut self, local: bool) -> Self {
+        self.local = local;
+        self
+    }
 }
 
 /// Getters
@@ -206,13 +287,26 @@ impl Bot {
     /// Returns currently used token.
     #[must_use]
     pub fn token(&self) -> &str {
-        &self.token
+        &self.token.0
     }
 
     /// Returns currently used http-client.
+    ///
+    /// # Panics
+    ///
+    /// If the bot was created with a custom [`HttpTransport`] by
+    /// [`Bot::with_transport`], use [`Bot::transport`] instead.
     #[must_use]
     pub fn client(&self) -> &Client {
-        &self.client
+        self.client.as_ref().expect("the bot was created with a custom transport")
+    }
+
+    /// Returns the [`HttpTransport`] used to send requests.
+    ///
+    /// For bots created with a [`reqwest::Client`] this is the client.
+    #[must_use]
+    pub fn transport(&self) -> &dyn HttpTransport {
+        &*self.transport
     }
 
     /// Returns currently used token API URL.
@@ -220,6 +314,14 @@ impl Bot {
     pub fn api_url(&self) -> reqwest::Url {
         reqwest::Url::clone(&*self.api_url)
     }
+
+    /// Returns `true` if the bot is set up to work with a local Bot API server.
+    ///
+    /// See [`Bot::set_local_mode`].
+    #[must_use]
+    pub fn local_mode(&self) -> bool {
+        self.local
+    }
 }
 
 impl Bot {
@@ -231,8 +333,8 @@ impl Bot {
         P: Payload + Serialize,
         P::Output: DeserializeOwned + 'static,
     {
-        let client = self.client.clone();
-        let token = Arc::clone(&self.token);
+        let transport = Arc::clone(&self.transport);
+        let token = Arc::clone(&self.token.0);
         let api_url = Arc::clone(&self.api_url);
 
         let timeout_hint = payload.timeout_hint();
@@ -240,10 +342,10 @@ impl Bot {
             // this `expect` should be ok since we don't write request those may trigger error here
             .expect("serialization of request to be infallible");
 
-        // async move to capture client&token&api_url&params
+        // async move to capture transport&token&api_url&params
         async move {
             net::request_json(
-                &client,
+                &*transport,
                 token.as_ref(),
                 reqwest::Url::clone(&*api_url),
                 P::NAME,
@@ -262,18 +364,18 @@ impl Bot {
         P: MultipartPayload + Serialize,
         P::Output: DeserializeOwned + 'static,
     {
-        let client = self.client.clone();
-        let token = Arc::clone(&self.token);
+        let transport = Arc::clone(&self.transport);
+        let token = Arc::clone(&self.token.0);
         let api_url = Arc::clone(&self.api_url);
 
         let timeout_hint = payload.timeout_hint();
-        let params = serde_multipart::to_form(payload);
+        let params = serde_multipart::to_form(payload, self.local);
 
-        // async move to capture client&token&api_url&params
+        // async move to capture transport&token&api_url&params
         async move {
             let params = params?.await;
             net::request_multipart(
-                &client,
+                &*transport,
                 token.as_ref(),
                 reqwest::Url::clone(&*api_url),
                 P::NAME,
@@ -292,18 +394,18 @@ impl Bot {
         P: MultipartPayload + Serialize,
         P::Output: DeserializeOwned + 'static,
     {
-        let client = self.client.clone();
-        let token = Arc::clone(&self.token);
+        let transport = Arc::clone(&self.transport);
+        let token = Arc::clone(&self.token.0);
         let api_url = self.api_url.clone();
 
         let timeout_hint = payload.timeout_hint();
-        let params = serde_multipart::to_form_ref(payload);
+        let params = serde_multipart::to_form_ref(payload, self.local);
 
-        // async move to capture client&token&api_url&params
+        // async move to capture transport&token&api_url&params
         async move {
             let params = params?.await;
             net::request_multipart(
-                &client,
+                &*transport,
                 token.as_ref(),
                 reqwest::Url::clone(&*api_url),
                 P::NAME,
@@ -315,6 +417,157 @@ impl Bot {
     }
 }
 
+/// Bot token, which is redacted in `Debug` output.
+#[derive(Clone)]
+struct Token(Arc<str>);
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The part before `:` is the id of the bot, which is not a secret
+        match self.0.split_once(':') {
+            Some((id, _)) => write!(f, "\"{id}:redacted\""),
+            None => f.write_str("\"redacted\""),
+        }
+    }
+}
+
+impl fmt::Debug for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Bot")
+            .field("token", &self.token)
+            .field("api_url", &self.api_url)
+            .field("client", &self.client)
+            .field("local", &self.local)
+            .finish_non_exhaustive()
+    }
+}
+
 fn get_env(env: &'static str) -> String {
     std::env::var(env).unwrap_or_else(|_| panic!("Cannot get the {env} env variable"))
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures::{
+        future::{self, BoxFuture},
+        FutureExt,
+    };
+
+    use crate::{
+        net::{Download, HttpTransport, TransportError},
+        payloads::{GetMe, SendDocument},
+        prelude::*,
+        testing::{FakeServer, MessageBuilder},
+        types::{ChatId, InputFile},
+    };
+
+    #[tokio::test]
+    async fn local_mode_sends_file_uris() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot().set_local_mode(true);
+        server.respond::<SendDocument>(MessageBuilder::text("document").build());
+
+        let path = std::path::absolute("../../media/teloxide-core-logo.png").unwrap();
+        bot.send_document(ChatId(1), InputFile::file(&path))
+            .thumbnail(InputFile::memory("thumbnail"))
+            .await
+            .unwrap();
+
+        let payload = &server.requests()[0].payload;
+        let uri = reqwest::Url::from_file_path(&path).unwrap();
+        assert_eq!(payload["document"], uri.as_str());
+        assert!(payload["thumbnail"].as_str().unwrap().starts_with("attach://"));
+    }
+
+    #[tokio::test]
+    async fn local_mode_reads_files() {
+        let path = std::env::temp_dir().join("teloxide-local-mode-download");
+        tokio::fs::write(&path, "local").await.unwrap();
+
+        let bot = Bot::new("0:local").set_local_mode(true);
+        let mut data = Vec::new();
+        bot.download_file(path.to_str().unwrap(), &mut data).await.unwrap();
+        assert_eq!(data, b"local");
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        // Answers every request with `True`, remembering the requested paths
+        #[derive(Default)]
+        struct InMemory(Mutex<Vec<String>>);
+
+        impl HttpTransport for Arc<InMemory> {
+            fn execute(
+                &self,
+                request: reqwest::Request,
+            ) -> BoxFuture<'static, Result<reqwest::Response, TransportError>> {
+                self.0.lock().unwrap().push(request.url().path().to_owned());
+                let response = hyper::Response::new(r#"{"ok":true,"result":true}"#);
+                future::ready(Ok(response.into())).boxed()
+            }
+        }
+
+        let transport = Arc::new(InMemory::default());
+        let bot = Bot::with_transport("0:token", Arc::clone(&transport));
+
+        bot.delete_webhook().await.unwrap();
+        bot.download_file("file_0.jpg", &mut Vec::new()).await.unwrap();
+
+        assert_eq!(
+            *transport.0.lock().unwrap(),
+            ["/bot0:token/DeleteWebhook", "/file/bot0:token/file_0.jpg"]
+        );
+    }
+
+    #[tokio::test]
+    async fn token_is_redacted() {
+        const SECRET: &str = "AAHdqTcvCH1vGWJxfSeofSAs0K5PALDsaw1";
+        let token = format!("123456:{SECRET}");
+        let assert_redacted = |error: &dyn std::error::Error| {
+            let formatted = format!("{error} {error:?}");
+            assert!(!formatted.contains(SECRET), "{formatted}");
+        };
+
+        assert!(!format!("{:?}", Bot::new(&token)).contains(SECRET));
+
+        // Nothing listens on the port, so requests fail with network errors
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        for api_url in [format!("http://{address}"), format!("http://{address}/prefix/")] {
+            let bot = Bot::new(&token).set_api_url(api_url.parse().unwrap());
+            assert_redacted(&bot.get_me().await.unwrap_err());
+            assert_redacted(&bot.download_file("file_0.jpg", &mut Vec::new()).await.unwrap_err());
+        }
+
+        // Errors of other transports may include the URL
+        struct Failing;
+
+        impl HttpTransport for Failing {
+            fn execute(
+                &self,
+                request: reqwest::Request,
+            ) -> BoxFuture<'static, Result<reqwest::Response, TransportError>> {
+                let error = TransportError::other(format!("can't connect to {}", request.url()));
+                future::ready(Err(error)).boxed()
+            }
+        }
+
+        let bot = Bot::with_transport(&token, Failing);
+        assert_redacted(&bot.get_me().await.unwrap_err());
+        assert_redacted(&bot.download_file("file_0.jpg", &mut Vec::new()).await.unwrap_err());
+
+        // Error pages of proxies may include the URL too
+        let server = FakeServer::start().await.unwrap();
+        let page = format!("<h1>Not Found</h1> /bot{}/GetMe", server.token());
+        server.respond_raw::<GetMe>(404, page.into());
+        let error = server.bot().get_me().await.unwrap_err();
+        let formatted = format!("{error} {error:?}");
+        assert!(formatted.contains("Not Found"), "{formatted}");
+        assert!(!formatted.contains(server.token()), "{formatted}");
+    }
+}
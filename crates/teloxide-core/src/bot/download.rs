@@ -1,16 +1,16 @@
-use std::path::Path;
+use std::{io::SeekFrom, path::Path};
 
 use bytes::Bytes;
 use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 
 use tokio::{
     fs::File,
-    io::{AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt},
 };
 
 use crate::{
     bot::Bot,
-    net::{self, Download},
+    net::{self, Download, DownloadOptions},
     DownloadError,
 };
 
@@ -26,48 +26,79 @@ impl Download for Bot {
         path: &'dst str,
         destination: &'dst mut (dyn AsyncWrite + Unpin + Send),
     ) -> Self::Fut<'dst> {
-        let is_localhost = match &self.api_url.host_str() {
-            Some(host) => ["localhost", "127.0.0.1", "::1"].contains(host),
-            None => false,
-        };
-        // If path is absolute and api_url contains localhost, it is pretty clear there
-        // is a local TBA server with --local option, we can just copy the file
-        if is_localhost && Path::new(&path).is_absolute() {
-            return copy_file(path, destination).boxed();
-        }
+        self.download(path, destination, DownloadOptions::new())
+    }
 
-        net::download_file(
-            &self.client,
-            reqwest::Url::clone(&*self.api_url),
-            &self.token,
-            path,
-            destination,
-        )
-        .boxed()
+    fn download_file_with<'a>(
+        &'a self,
+        path: &'a str,
+        destination: &'a mut (dyn AsyncWrite + Unpin + Send),
+        options: DownloadOptions,
+    ) -> BoxFuture<'a, Result<(), DownloadError>> {
+        self.download(path, destination, options)
     }
 
-    type StreamErr = reqwest::Error;
+    type StreamErr = DownloadError;
 
     type Stream = BoxStream<'static, Result<Bytes, Self::StreamErr>>;
 
     fn download_file_stream(&self, path: &str) -> Self::Stream {
         net::download_file_stream(
-            &self.client,
+            &*self.transport,
+            reqwest::Url::clone(&*self.api_url),
+            &self.token.0,
+            path,
+        )
+        .boxed()
+    }
+}
+
+impl Bot {
+    fn download<'dst>(
+        &self,
+        path: &'dst str,
+        destination: &'dst mut (dyn AsyncWrite + Unpin + Send),
+        options: DownloadOptions,
+    ) -> BoxFuture<'dst, Result<(), DownloadError>> {
+        let is_localhost = match &self.api_url.host_str() {
+            Some(host) => ["localhost", "127.0.0.1", "::1"].contains(host),
+            None => false,
+        };
+        // A local TBA server (with --local option) returns absolute paths, we can just
+        // copy the file. If local mode wasn't set explicitly, but the path is absolute
+        // and api_url contains localhost, it is pretty clear there is such a server too
+        if (self.local_mode() || is_localhost) && Path::new(&path).is_absolute() {
+            return copy_file(path, destination, options).boxed();
+        }
+
+        net::download_file_with(
+            &*self.transport,
             reqwest::Url::clone(&*self.api_url),
-            &self.token,
+            &self.token.0,
             path,
+            destination,
+            options,
         )
-        .map(|res| res.map_err(crate::errors::hide_token))
         .boxed()
     }
 }
 
-async fn copy_file<'o, D>(path: &'o str, dst: &'o mut D) -> Result<(), DownloadError>
+async fn copy_file<'o, D>(
+    path: &'o str,
+    dst: &'o mut D,
+    options: DownloadOptions,
+) -> Result<(), DownloadError>
 where
     D: ?Sized + AsyncWrite + Unpin,
 {
     let mut src_file = File::open(path).await?;
 
+    let size = src_file.metadata().await?.len();
+    options.check_downloaded(size)?;
+
+    let offset = options.offset().min(size);
+    src_file.seek(SeekFrom::Start(offset)).await?;
+
     let mut buffer = [0; 128 * 1024];
     loop {
         let n = src_file.read(&mut buffer).await?;
@@ -1,12 +1,17 @@
 //! Possible error types.
 
-use std::{io, sync::Arc};
+use std::{fmt, io, sync::Arc};
 
 use thiserror::Error;
 
-use crate::types::{ChatId, ResponseParameters, Seconds};
+use crate::{
+    net::TransportError,
+    types::{ChatId, ResponseParameters, Seconds},
+};
 
 /// An error caused by sending a request to Telegram.
+///
+/// The bot token is redacted from errors, so they can be logged safely.
 #[derive(Debug, Error, Clone)]
 pub enum RequestError {
     /// A Telegram API error.
@@ -45,9 +50,37 @@ pub enum RequestError {
     /// Occurs when trying to send a file to Telegram.
     #[error("An I/O error: {0}")]
     Io(#[from] Arc<io::Error>),
+
+    /// The request was cancelled before it was sent to Telegram.
+    ///
+    /// For example, `Throttle::cancel_chat` cancels requests waiting in the
+    /// queue of the [`Throttle`] adaptor.
+    ///
+    /// [`Throttle`]: crate::adaptors::Throttle
+    #[error("The request was cancelled")]
+    Cancelled,
+
+    /// The request wasn't sent, because the [`CircuitBreaker`] adaptor is
+    /// open after too many failures.
+    ///
+    /// [`CircuitBreaker`]: crate::adaptors::CircuitBreaker
+    #[error("The request wasn't sent, because the circuit breaker is open")]
+    CircuitOpen,
+
+    /// The request didn't complete within the timeout set by the [`Timeout`]
+    /// adaptor.
+    ///
+    /// Note that timeouts of the HTTP client are reported as
+    /// [`RequestError::Network`] errors.
+    ///
+    /// [`Timeout`]: crate::adaptors::Timeout
+    #[error("The request timed out")]
+    TimedOut,
 }
 
 /// An error caused by downloading a file.
+///
+/// The bot token is redacted from errors, so they can be logged safely.
 #[derive(Debug, Error, Clone)]
 pub enum DownloadError {
     /// A network error while downloading a file from Telegram.
@@ -58,6 +91,60 @@ pub enum DownloadError {
     /// An I/O error while writing a file to destination.
     #[error("An I/O error: {0}")]
     Io(#[from] Arc<std::io::Error>),
+
+    /// The file is larger than the limit set by
+    /// [`DownloadOptions::max_size`].
+    ///
+    /// [`DownloadOptions::max_size`]: crate::net::DownloadOptions::max_size
+    #[error("The file is larger than {limit} bytes")]
+    TooLarge {
+        /// The size limit, in bytes.
+        limit: u64,
+    },
+
+    /// The size of the downloaded file differs from the one set by
+    /// [`DownloadOptions::expected_size`].
+    ///
+    /// [`DownloadOptions::expected_size`]: crate::net::DownloadOptions::expected_size
+    #[error("The file has {actual} bytes, but {expected} bytes were expected")]
+    SizeMismatch {
+        /// The expected size, in bytes.
+        expected: u64,
+        /// The size of the downloaded file, in bytes.
+        actual: u64,
+    },
+}
+
+/// An error response of Telegram which is not known to `teloxide`, see
+/// [`ApiError::Unknown`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorResponse {
+    /// The error code, e.g. `400` or `403`.
+    ///
+    /// Telegram uses HTTP status codes as error codes. This is `None` if the
+    /// error wasn't received from Telegram (e.g. it was created by
+    /// [`ErrorResponse::new`]) or the response didn't have a code.
+    pub error_code: Option<u16>,
+
+    /// The description of the error, e.g. `Bad Request: chat not found`.
+    pub description: String,
+
+    /// Parameters of the response.
+    pub parameters: Option<ResponseParameters>,
+}
+
+impl ErrorResponse {
+    /// Creates an error response with `description`, without an error code and
+    /// parameters.
+    pub fn new(description: impl Into<String>) -> Self {
+        Self { error_code: None, description: description.into(), parameters: None }
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.description)
+    }
 }
 
 pub trait AsResponseParameters {
@@ -83,11 +170,56 @@ impl AsResponseParameters for crate::RequestError {
         match *self {
             Self::RetryAfter(n) => Some(ResponseParameters::RetryAfter(n)),
             Self::MigrateToChatId(id) => Some(ResponseParameters::MigrateToChatId(id)),
+            Self::Api(ApiError::Unknown(ref response)) => response.parameters,
             _ => None,
         }
     }
 }
 
+/// Errors which can be classified by a short label.
+///
+/// This is used, for example, by the [`Metrics`] adaptor to count errors.
+///
+/// [`Metrics`]: crate::adaptors::Metrics
+pub trait ErrorLabel {
+    /// Returns a short label of this error, e.g. `BotBlocked`.
+    ///
+    /// The label doesn't include any details of the error, so the number of
+    /// distinct labels is small.
+    fn label(&self) -> &'static str;
+}
+
+impl ErrorLabel for RequestError {
+    /// Returns the name of the [`ApiError`] variant (e.g. `BotBlocked`) for
+    /// API errors and the name of the [`RequestError`] variant (e.g.
+    /// `Network`) for other errors.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Api(err) => err.label(),
+            Self::MigrateToChatId(_) => "MigrateToChatId",
+            Self::RetryAfter(_) => "RetryAfter",
+            Self::Network(_) => "Network",
+            Self::InvalidJson { .. } => "InvalidJson",
+            Self::Io(_) => "Io",
+            Self::Cancelled => "Cancelled",
+            Self::CircuitOpen => "CircuitOpen",
+            Self::TimedOut => "TimedOut",
+        }
+    }
+}
+
+impl ErrorLabel for DownloadError {
+    /// Returns the name of the variant, e.g. `Network`.
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Network(_) => "Network",
+            Self::Io(_) => "Io",
+            Self::TooLarge { .. } => "TooLarge",
+            Self::SizeMismatch { .. } => "SizeMismatch",
+        }
+    }
+}
+
 macro_rules! impl_api_error {
     (
         $( #[$meta:meta] )*
@@ -109,6 +241,15 @@ macro_rules! impl_api_error {
             )*
         }
 
+        impl ErrorLabel for $ident {
+            /// Returns the name of the variant, e.g. `BotBlocked`.
+            fn label(&self) -> &'static str {
+                match self {
+                    $(Self::$var_name { .. } => stringify!($var_name),)*
+                }
+            }
+        }
+
         const _: () = {
             struct Visitor;
 
@@ -750,11 +891,117 @@ impl_api_error! {
 
         /// Error which is not known to `teloxide`.
         ///
+        /// [`ErrorResponse`] contains the description of the error, as well as
+        /// the error code and the parameters of the response, so such errors
+        /// can still be told apart.
+        ///
         /// If you've received this error, please [open an issue] with the
         /// description of the error.
         ///
         /// [open an issue]: https://github.com/teloxide/teloxide/issues/new
-        Unknown(String) = "Unknown error: {0:?}" with |text: &str| Some(text.to_owned())
+        Unknown(ErrorResponse) = "Unknown error: \"{0}\"" with |text: &str| Some(ErrorResponse::new(text))
+    }
+}
+
+impl ApiError {
+    /// Returns `true` if the bot isn't allowed to perform the request, for
+    /// example because it was blocked by the user or lacks administrator
+    /// rights in the chat.
+    ///
+    /// Unknown errors with the `403 Forbidden` code are permission errors too.
+    #[must_use]
+    pub fn is_permission_error(&self) -> bool {
+        if let Self::Unknown(response) = self {
+            return response.error_code == Some(403);
+        }
+
+        matches!(
+            self,
+            Self::BotBlocked
+                | Self::BotKicked
+                | Self::BotKickedFromSupergroup
+                | Self::BotKickedFromChannel
+                | Self::UserDeactivated
+                | Self::CantInitiateConversation
+                | Self::CantTalkWithBots
+                | Self::NotEnoughRightsToPinMessage
+                | Self::NotEnoughRightsToManagePins
+                | Self::NotEnoughRightsToChangeChatPermissions
+                | Self::NotEnoughRightsToRestrict
+                | Self::NotEnoughRightsToPostMessages
+                | Self::ChatAdminRequired
+        )
+    }
+
+    /// Returns `true` if the bot can't send anything to the chat anymore, for
+    /// example because the chat was deleted, the bot was kicked from it or
+    /// blocked by the user.
+    #[must_use]
+    pub fn is_chat_gone(&self) -> bool {
+        matches!(
+            self,
+            Self::ChatNotFound
+                | Self::GroupDeactivated
+                | Self::BotBlocked
+                | Self::BotKicked
+                | Self::BotKickedFromSupergroup
+                | Self::BotKickedFromChannel
+                | Self::UserDeactivated
+        )
+    }
+}
+
+impl RequestError {
+    /// Returns the error code sent by Telegram for an error which is not known
+    /// to `teloxide`, see [`ErrorResponse::error_code`].
+    ///
+    /// Known errors are identified by their [`ApiError`] variant instead.
+    #[must_use]
+    pub fn error_code(&self) -> Option<u16> {
+        match self {
+            Self::Api(ApiError::Unknown(response)) => response.error_code,
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the request may succeed when it's sent again.
+    ///
+    /// These are network errors, timeouts, flood control and unknown errors
+    /// with `429` or `5xx` error codes.
+    ///
+    /// Note that [`RequestError::InvalidJson`] isn't retryable, since it may
+    /// be caused by a successful response which `teloxide` failed to parse.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::RetryAfter(_) | Self::TimedOut => true,
+            Self::Api(_) => self.error_code().is_some_and(|code| code == 429 || code >= 500),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the bot isn't allowed to perform the request, see
+    /// [`ApiError::is_permission_error`].
+    #[must_use]
+    pub fn is_permission_error(&self) -> bool {
+        match self {
+            Self::Api(error) => error.is_permission_error(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the bot can't send anything to the chat anymore, see
+    /// [`ApiError::is_chat_gone`].
+    ///
+    /// Groups migrated to supergroups are gone too, requests should be sent
+    /// to the supergroup instead.
+    #[must_use]
+    pub fn is_chat_gone(&self) -> bool {
+        match self {
+            Self::Api(error) => error.is_chat_gone(),
+            Self::MigrateToChatId(_) => true,
+            _ => false,
+        }
     }
 }
 
@@ -780,6 +1027,12 @@ impl From<DownloadError> for RequestError {
         match download_err {
             DownloadError::Network(err) => RequestError::Network(err),
             DownloadError::Io(err) => RequestError::Io(err),
+            err @ (DownloadError::TooLarge { .. } | DownloadError::SizeMismatch { .. }) => {
+                RequestError::Io(Arc::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    err,
+                )))
+            }
         }
     }
 }
@@ -802,6 +1055,33 @@ impl From<reqwest::Error> for RequestError {
     }
 }
 
+impl From<TransportError> for RequestError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Reqwest(error) => error.into(),
+            TransportError::Other(error) => RequestError::Io(Arc::new(io::Error::other(error))),
+        }
+    }
+}
+
+impl From<TransportError> for DownloadError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::Reqwest(error) => error.into(),
+            TransportError::Other(error) => DownloadError::Io(Arc::new(io::Error::other(error))),
+        }
+    }
+}
+
+/// Replaces `token` in `text` with `token:redacted` string.
+pub(crate) fn redact_token(text: &str, token: &str) -> String {
+    if token.is_empty() {
+        return text.to_owned();
+    }
+
+    text.replace(token, "token:redacted")
+}
+
 /// Replaces token in the url in the error with `token:redacted` string.
 pub(crate) fn hide_token(mut error: reqwest::Error) -> reqwest::Error {
     let url = match error.url_mut() {
@@ -856,7 +1136,7 @@ pub(crate) fn hide_token(mut error: reqwest::Error) -> reqwest::Error {
 mod tests {
     #[test]
     fn custom_result() {
-        use super::ApiError;
+        use super::{ApiError, ErrorResponse};
         use serde::Deserialize;
 
         let cases = &[
@@ -1066,7 +1346,10 @@ mod tests {
             ),
             ("{\"data\": \"Bad Request: invalid file id\"}", ApiError::FileIdInvalid),
             ("{\"data\": \"Request Entity Too Large\"}", ApiError::RequestEntityTooLarge),
-            ("{\"data\": \"RandomError\"}", ApiError::Unknown("RandomError".to_string())),
+            (
+                "{\"data\": \"RandomError\"}",
+                ApiError::Unknown(ErrorResponse::new("RandomError".to_string())),
+            ),
         ];
 
         #[derive(Deserialize, Debug)]
@@ -1089,4 +1372,69 @@ mod tests {
             assert_eq!(parsed.to_string(), expected_error_message);
         }
     }
+
+    #[test]
+    fn labels() {
+        use super::{ApiError, ErrorLabel, ErrorResponse, RequestError};
+        use crate::types::Seconds;
+
+        assert_eq!(ApiError::BotBlocked.label(), "BotBlocked");
+        assert_eq!(
+            ApiError::CantParseEntities("Bad Request".to_owned()).label(),
+            "CantParseEntities"
+        );
+        assert_eq!(ApiError::Unknown(ErrorResponse::new("Some error")).label(), "Unknown");
+        assert_eq!(RequestError::from(ApiError::ChatNotFound).label(), "ChatNotFound");
+        assert_eq!(RequestError::RetryAfter(Seconds::from_seconds(1)).label(), "RetryAfter");
+    }
+
+    #[test]
+    fn classification() {
+        use std::sync::Arc;
+
+        use super::{ApiError, ErrorResponse, RequestError};
+        use crate::types::{ChatId, Seconds};
+
+        let unknown = |error_code, description| {
+            let response = ErrorResponse {
+                error_code,
+                description: String::from(description),
+                parameters: None,
+            };
+            RequestError::from(ApiError::Unknown(response))
+        };
+
+        let blocked = RequestError::from(ApiError::BotBlocked);
+        assert_eq!(blocked.error_code(), None);
+        assert!(blocked.is_permission_error());
+        assert!(blocked.is_chat_gone());
+        assert!(!blocked.is_retryable());
+
+        let not_found = RequestError::from(ApiError::ChatNotFound);
+        assert!(!not_found.is_permission_error());
+        assert!(not_found.is_chat_gone());
+
+        let forbidden = unknown(Some(403), "Forbidden: something new");
+        assert_eq!(forbidden.error_code(), Some(403));
+        assert!(forbidden.is_permission_error());
+        assert!(!forbidden.is_chat_gone());
+
+        let server = unknown(Some(502), "Bad Gateway");
+        assert_eq!(server.error_code(), Some(502));
+        assert!(server.is_retryable());
+
+        // Codes aren't inferred from descriptions
+        let created = unknown(None, "Bad Gateway");
+        assert_eq!(created.error_code(), None);
+        assert!(!created.is_retryable());
+
+        let raw = "{\"ok\":true,\"result\":{\"new_field\":1}}";
+        let source = serde_json::from_str::<()>(raw).unwrap_err();
+        let invalid = RequestError::InvalidJson { source: Arc::new(source), raw: raw.into() };
+        assert!(!invalid.is_retryable());
+
+        assert!(RequestError::RetryAfter(Seconds::from_seconds(1)).is_retryable());
+        assert!(RequestError::MigrateToChatId(ChatId(-1)).is_chat_gone());
+        assert!(!RequestError::Cancelled.is_retryable());
+    }
 }
@@ -42,7 +42,21 @@
 //! - `trace_adaptor` — enables [`Trace`] bot adaptor
 //! - `erased` — enables [`ErasedRequester`] bot adaptor
 //! - `throttle` — enables [`Throttle`] bot adaptor
+//! - `throttle_redis` — enables [`RedisBackend`] for the [`Throttle`] bot
+//!   adaptor
 //! - `cache_me` — enables [`CacheMe`] bot adaptor
+//! - `retry` — enables [`Retry`] bot adaptor
+//! - `follow_migrations` — enables [`FollowMigrations`] bot adaptor
+//! - `metrics` — enables [`Metrics`] bot adaptor
+//! - `trace_spans` — enables [`TraceSpans`] bot adaptor
+//! - `record_replay` — enables [`Record`] bot adaptor and [`Replay`] requester
+//! - `file_cache` — enables [`FileCache`] bot adaptor
+//! - `ttl_cache` — enables [`TtlCache`] bot adaptor
+//! - `coalesce` — enables [`Coalesce`] bot adaptor
+//! - `circuit_breaker` — enables [`CircuitBreaker`] bot adaptor
+//! - `timeout` — enables [`Timeout`] bot adaptor
+//! - `testing` — enables [`testing`] utilities, such as [`MockBot`] and
+//!   [`FakeServer`]
 //! - `full` — enables all features except `nightly` and tls-related
 //! - `nightly` — enables nightly-only features, currently:
 //!   - Removes some future boxing using `#![feature(type_alias_impl_trait)]`
@@ -51,7 +65,21 @@
 //! [`Trace`]: adaptors::Trace
 //! [`ErasedRequester`]: adaptors::ErasedRequester
 //! [`Throttle`]: adaptors::Throttle
+//! [`RedisBackend`]: adaptors::throttle::RedisBackend
 //! [`CacheMe`]: adaptors::CacheMe
+//! [`Retry`]: adaptors::Retry
+//! [`FollowMigrations`]: adaptors::FollowMigrations
+//! [`Metrics`]: adaptors::Metrics
+//! [`TraceSpans`]: adaptors::TraceSpans
+//! [`Record`]: adaptors::Record
+//! [`Replay`]: adaptors::Replay
+//! [`FileCache`]: adaptors::FileCache
+//! [`TtlCache`]: adaptors::TtlCache
+//! [`Coalesce`]: adaptors::Coalesce
+//! [`CircuitBreaker`]: adaptors::CircuitBreaker
+//! [`Timeout`]: adaptors::Timeout
+//! [`MockBot`]: testing::MockBot
+//! [`FakeServer`]: testing::FakeServer
 //! [`native-tls`]: https://docs.rs/native-tls
 //! [`rustls`]: https://docs.rs/rustls
 
@@ -118,6 +146,8 @@ pub mod net;
 pub mod payloads;
 pub mod prelude;
 pub mod requests;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod types;
 
 // reexported
@@ -374,7 +374,7 @@ macro_rules! impl_payload {
 
 macro_rules! download_forward {
     ($T:ident $S:ty {$this:ident => $inner:expr}) => {
-        impl<$T: $crate::net::Download> $crate::net::Download for $S {
+        impl<$T: $crate::net::Download + Sync> $crate::net::Download for $S {
             type Err<'dst> = <$T as $crate::net::Download>::Err<'dst>;
 
             type Fut<'dst> = <$T as $crate::net::Download>::Fut<'dst>;
@@ -390,6 +390,22 @@ macro_rules! download_forward {
                 ($inner).download_file(path, destination)
             }
 
+            fn download_file_with<'a>(
+                &'a self,
+                path: &'a str,
+                destination: &'a mut (dyn tokio::io::AsyncWrite
+                             + core::marker::Unpin
+                             + core::marker::Send),
+                options: $crate::net::DownloadOptions,
+            ) -> futures::future::BoxFuture<'a, Result<(), $crate::errors::DownloadError>>
+            where
+                Self: Sync,
+                for<'dst> Self::Err<'dst>: Into<$crate::errors::DownloadError>,
+            {
+                let $this = self;
+                ($inner).download_file_with(path, destination, options)
+            }
+
             type StreamErr = <$T as $crate::net::Download>::StreamErr;
 
             type Stream = <$T as $crate::net::Download>::Stream;
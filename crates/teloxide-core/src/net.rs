@@ -2,16 +2,25 @@
 
 use std::time::Duration;
 
-pub use self::download::{download_file, download_file_stream, Download};
+pub use self::{
+    download::{
+        download_file, download_file_stream, download_file_with, Download, DownloadOptions,
+    },
+    transport::{HttpTransport, TransportError},
+};
 
 pub(crate) use self::{
     request::{request_json, request_multipart},
     telegram_response::TelegramResponse,
 };
 
+#[cfg(any(feature = "record_replay", feature = "testing"))]
+pub(crate) use self::transport::NoTransport;
+
 mod download;
 mod request;
 mod telegram_response;
+mod transport;
 
 /// The default Telegram API URL.
 pub const TELEGRAM_API_URL: &str = "https://api.telegram.org";
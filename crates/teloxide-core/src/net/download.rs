@@ -1,15 +1,29 @@
-use std::{future::Future, sync::Arc};
+use std::{
+    ffi::OsString,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
 
 use bytes::Bytes;
 use futures::{
-    future::{ready, Either},
+    future::{ready, BoxFuture, Either},
     stream::{once, unfold},
-    FutureExt, Stream, StreamExt,
+    FutureExt, Stream, StreamExt, TryFutureExt,
+};
+use reqwest::{
+    header::{self, HeaderValue},
+    Method, Request, Response, StatusCode, Url,
 };
-use reqwest::{Client, Response, Url};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-use crate::{errors::DownloadError, net::file_url};
+use crate::{
+    errors::DownloadError,
+    net::{file_url, HttpTransport},
+};
 
 /// A trait for downloading files from Telegram.
 pub trait Download {
@@ -29,7 +43,7 @@ pub trait Download {
     /// `path` can be obtained from [`GetFile`].
     ///
     /// If the bot uses a [local bot api](https://github.com/tdlib/telegram-bot-api), this function
-    /// just copies the file into `destination`.
+    /// just copies the file into `destination`. See [`Bot::set_local_mode`].
     ///
     /// To download as a stream of chunks, see [`download_file_stream`].
     ///
@@ -55,12 +69,128 @@ pub trait Download {
     ///
     /// [`GetFile`]: crate::payloads::GetFile
     /// [`download_file_stream`]: Self::download_file_stream
+    /// [`Bot::set_local_mode`]: crate::Bot::set_local_mode
     fn download_file<'dst>(
         &self,
         path: &'dst str,
         destination: &'dst mut (dyn AsyncWrite + Unpin + Send),
     ) -> Self::Fut<'dst>;
 
+    /// Download a file from Telegram into `destination`, applying `options`.
+    ///
+    /// This is the same as [`download_file`], but allows to limit the size of
+    /// the file, check it against the expected one and to resume a partial
+    /// download. See [`DownloadOptions`] for more.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use teloxide_core::{
+    ///     net::{Download, DownloadOptions},
+    ///     requests::{Request, Requester},
+    ///     types::FileId,
+    ///     Bot,
+    /// };
+    /// use tokio::fs;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bot = Bot::new("TOKEN");
+    ///
+    /// let file = bot.get_file(FileId("*file_id*".to_string())).await?;
+    /// let mut dst = fs::File::create("/tmp/test.png").await?;
+    /// let options = DownloadOptions::new().max_size(10 * 1024 * 1024).expected_size(file.size);
+    /// bot.download_file_with(&file.path, &mut dst, options).await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// The default implementation checks the size of the file written by
+    /// [`download_file`], so the download fails only after `max_size` bytes
+    /// were written. Resuming a download ([`DownloadOptions::resume_from`])
+    /// isn't supported by it and fails with [`DownloadError::Io`].
+    ///
+    /// [`download_file`]: Self::download_file
+    fn download_file_with<'a>(
+        &'a self,
+        path: &'a str,
+        destination: &'a mut (dyn AsyncWrite + Unpin + Send),
+        options: DownloadOptions,
+    ) -> BoxFuture<'a, Result<(), DownloadError>>
+    where
+        Self: Sync,
+        for<'dst> Self::Err<'dst>: Into<DownloadError>,
+    {
+        Box::pin(async move {
+            if options.offset > 0 {
+                let err = io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "resuming downloads is not supported by this `Download` implementation",
+                );
+                return Err(DownloadError::Io(Arc::new(err)));
+            }
+
+            let mut destination = SizeChecked::new(destination, options.max_size);
+            let res = self.download_file(path, &mut destination).await.map_err(Into::into);
+            match (res, options.max_size) {
+                (Err(_), Some(limit)) if destination.exceeded => {
+                    Err(DownloadError::TooLarge { limit })
+                }
+                (res, _) => res.and_then(|()| options.check_downloaded(destination.size)),
+            }
+        })
+    }
+
+    /// Download a file from Telegram to the file at `target`.
+    ///
+    /// The file is first downloaded to a temporary file next to `target` (with
+    /// a `.part` extension added), which is renamed to `target` only if the
+    /// download succeeds, so `target` never contains a partial file.
+    ///
+    /// If [`DownloadOptions::resume`] is set and the temporary file is left
+    /// from a previous failed download, the download continues from where it
+    /// stopped, otherwise the temporary file is overwritten.
+    fn download_file_to<'a>(
+        &'a self,
+        path: &'a str,
+        target: &'a Path,
+        options: DownloadOptions,
+    ) -> BoxFuture<'a, Result<(), DownloadError>>
+    where
+        Self: Sync,
+        for<'dst> Self::Err<'dst>: Into<DownloadError>,
+    {
+        Box::pin(async move {
+            let part = part_path(target);
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(options.resume)
+                .truncate(!options.resume)
+                .open(&part)
+                .await?;
+
+            let mut options = options;
+            if options.resume {
+                options = options.resume_from(file.metadata().await?.len());
+            }
+
+            let res = async {
+                self.download_file_with(path, &mut file, options).await?;
+                file.sync_all().await?;
+                drop(file);
+                tokio::fs::rename(&part, target).await?;
+                Ok(())
+            }
+            .await;
+
+            // Partial files are kept only if they can be resumed
+            if res.is_err() && !options.resume {
+                let _ = tokio::fs::remove_file(&part).await;
+            }
+
+            res
+        })
+    }
+
     /// An error returned from
     /// [`download_file_stream`](Self::download_file_stream).
     type StreamErr;
@@ -77,6 +207,10 @@ pub trait Download {
     /// To download into an [`AsyncWrite`] (e.g. [`tokio::fs::File`]), see
     /// [`download_file`].
     ///
+    /// Note: files of a [local bot api](https://github.com/tdlib/telegram-bot-api)
+    /// are not served over HTTP, use [`download_file`] or read the returned
+    /// path directly instead.
+    ///
     /// [`GetFile`]: crate::payloads::GetFile
     /// [`AsyncWrite`]: tokio::io::AsyncWrite
     /// [`tokio::fs::File`]: tokio::fs::File
@@ -84,22 +218,169 @@ pub trait Download {
     fn download_file_stream(&self, path: &str) -> Self::Stream;
 }
 
+/// Options of [`Download::download_file_with`] and
+/// [`Download::download_file_to`].
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::net::DownloadOptions;
+///
+/// let options = DownloadOptions::new().max_size(20 * 1024 * 1024).resume_from(1024);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[must_use]
+pub struct DownloadOptions {
+    max_size: Option<u64>,
+    expected_size: Option<u64>,
+    offset: u64,
+    resume: bool,
+}
+
+impl DownloadOptions {
+    /// Creates options which download the whole file without any checks.
+    pub const fn new() -> Self {
+        Self { max_size: None, expected_size: None, offset: 0, resume: false }
+    }
+
+    /// Fails the download with [`DownloadError::TooLarge`] if the file is
+    /// larger than `max_size` bytes.
+    ///
+    /// If the server reports the size of the file, the download fails before
+    /// anything is written to the destination.
+    pub const fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Fails the download with [`DownloadError::SizeMismatch`] if the size of
+    /// the downloaded file is not `size` bytes.
+    ///
+    /// The size of a file is returned by [`GetFile`] as [`File::size`].
+    ///
+    /// [`GetFile`]: crate::payloads::GetFile
+    /// [`File::size`]: crate::types::FileMeta::size
+    pub const fn expected_size(mut self, size: u32) -> Self {
+        self.expected_size = Some(size as u64);
+        self
+    }
+
+    /// Downloads only the part of the file after the first `offset` bytes,
+    /// which the destination is expected to already contain.
+    ///
+    /// The rest of the file is requested with an HTTP `Range` header. If the
+    /// server doesn't support it, the first `offset` bytes of the response are
+    /// skipped. If the file is smaller than `offset` bytes, the download fails
+    /// with [`DownloadError::SizeMismatch`].
+    pub const fn resume_from(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Makes [`Download::download_file_to`] keep partial files of failed
+    /// downloads and resume them.
+    pub const fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Returns the number of bytes which the destination already contains.
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Checks the total size of the file, if it's known.
+    pub(crate) fn check_total(&self, total: u64) -> Result<(), DownloadError> {
+        match self.max_size {
+            Some(limit) if total > limit => Err(DownloadError::TooLarge { limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the size of the file after it was downloaded.
+    pub(crate) fn check_downloaded(&self, size: u64) -> Result<(), DownloadError> {
+        self.check_total(size)?;
+
+        match self.expected_size {
+            Some(expected) if size != expected => {
+                Err(DownloadError::SizeMismatch { expected, actual: size })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A writer used by the default implementation of
+/// [`Download::download_file_with`], which counts written bytes and fails once
+/// there are more than `limit` of them.
+struct SizeChecked<'a> {
+    inner: &'a mut (dyn AsyncWrite + Unpin + Send),
+    limit: Option<u64>,
+    size: u64,
+    exceeded: bool,
+}
+
+impl<'a> SizeChecked<'a> {
+    fn new(inner: &'a mut (dyn AsyncWrite + Unpin + Send), limit: Option<u64>) -> Self {
+        Self { inner, limit, size: 0, exceeded: false }
+    }
+}
+
+impl AsyncWrite for SizeChecked<'_> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        if matches!(this.limit, Some(limit) if this.size + buf.len() as u64 > limit) {
+            this.exceeded = true;
+            return Poll::Ready(Err(io::Error::other("the file is too large")));
+        }
+
+        let n = ready!(Pin::new(&mut *this.inner).poll_write(cx, buf))?;
+        this.size += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Returns the path of the temporary file used by `download_file_to`.
+fn part_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".part");
+    target.with_file_name(name)
+}
+
 /// Download a file from Telegram into `dst`.
 ///
+/// `transport` is usually a [`reqwest::Client`], see [`HttpTransport`].
+///
 /// Note: if you don't need to use a different (from you're bot) client and
 /// don't need to get *all* performance (and you don't, c'mon it's very io-bound
 /// job), then it's recommended to use [`Download::download_file`].
-pub fn download_file<'o, D>(
-    client: &Client,
+pub fn download_file<'o, T, D>(
+    transport: &T,
     api_url: Url,
     token: &str,
     path: &str,
     dst: &'o mut D,
 ) -> impl Future<Output = Result<(), DownloadError>> + 'o
 where
+    T: ?Sized + HttpTransport,
     D: ?Sized + AsyncWrite + Unpin,
 {
-    client.get(file_url(api_url, token, path)).send().then(move |r| async move {
+    let request = Request::new(Method::GET, file_url(api_url, token, path));
+    let token = token.to_owned();
+    let response = transport.execute(request).map_err(move |err| err.hide_token(&token));
+    response.then(move |r| async move {
         let mut res = r?.error_for_status()?;
 
         while let Some(chunk) = res.chunk().await? {
@@ -110,22 +391,110 @@ where
     })
 }
 
+/// Download a file from Telegram into `dst`, applying `options`.
+///
+/// `transport` is usually a [`reqwest::Client`], see [`HttpTransport`].
+///
+/// Note: if you don't need to use a different (from you're bot) client, it's
+/// recommended to use [`Download::download_file_with`].
+pub fn download_file_with<'o, T, D>(
+    transport: &T,
+    api_url: Url,
+    token: &str,
+    path: &str,
+    dst: &'o mut D,
+    options: DownloadOptions,
+) -> impl Future<Output = Result<(), DownloadError>> + 'o
+where
+    T: ?Sized + HttpTransport,
+    D: ?Sized + AsyncWrite + Unpin,
+{
+    let mut request = Request::new(Method::GET, file_url(api_url, token, path));
+    if options.offset > 0 {
+        let range = HeaderValue::from_str(&format!("bytes={}-", options.offset))
+            .expect("range is a valid header value");
+        request.headers_mut().insert(header::RANGE, range);
+    }
+
+    let token = token.to_owned();
+    let response = transport.execute(request).map_err(move |err| err.hide_token(&token));
+    response.then(move |r| async move {
+        let res = r?;
+
+        if options.offset > 0 && res.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+            if let Some(size) = unsatisfied_range_size(&res) {
+                // The whole file is already downloaded
+                if size == options.offset {
+                    return options.check_downloaded(size);
+                }
+
+                // The destination contains more than the whole file
+                return Err(DownloadError::SizeMismatch { expected: size, actual: options.offset });
+            }
+        }
+
+        let mut res = res.error_for_status()?;
+
+        // If the server ignored the `Range` header, the whole file is sent
+        let mut skip = match res.status() {
+            StatusCode::PARTIAL_CONTENT => 0,
+            _ => options.offset,
+        };
+
+        if let Some(len) = res.content_length() {
+            options.check_total(options.offset - skip + len)?;
+        }
+
+        let mut size = options.offset;
+        while let Some(mut chunk) = res.chunk().await? {
+            if skip > 0 {
+                let n = skip.min(chunk.len() as u64);
+                skip -= n;
+                let _ = chunk.split_to(n as usize);
+            }
+
+            size += chunk.len() as u64;
+            options.check_total(size)?;
+
+            dst.write_all(&chunk).await.map_err(Arc::new)?;
+        }
+
+        options.check_downloaded(size)
+    })
+}
+
+/// Returns the size of the file from the `Content-Range: bytes */<size>`
+/// header of a `416 Range Not Satisfiable` response.
+fn unsatisfied_range_size(res: &Response) -> Option<u64> {
+    let range = res.headers().get(header::CONTENT_RANGE)?.to_str().ok()?;
+    range.strip_prefix("bytes */")?.parse().ok()
+}
+
 /// Download a file from Telegram as [`Stream`].
 ///
+/// `transport` is usually a [`reqwest::Client`], see [`HttpTransport`].
+///
 /// Note: if you don't need to use a different (from you're bot) client and
 /// don't need to get *all* performance (and you don't, c'mon it's very io-bound
 /// job), then it's recommended to use [`Download::download_file_stream`].
-pub fn download_file_stream(
-    client: &Client,
+pub fn download_file_stream<T>(
+    transport: &T,
     api_url: Url,
     token: &str,
     path: &str,
-) -> impl Stream<Item = reqwest::Result<Bytes>> + 'static {
-    client.get(file_url(api_url, token, path)).send().into_stream().flat_map(|res| {
-        match res.and_then(Response::error_for_status) {
+) -> impl Stream<Item = Result<Bytes, DownloadError>> + 'static
+where
+    T: ?Sized + HttpTransport,
+{
+    let request = Request::new(Method::GET, file_url(api_url, token, path));
+    let token = token.to_owned();
+    let response = transport.execute(request).map_err(move |err| err.hide_token(&token));
+    response.into_stream().flat_map(|res| {
+        let res = res.map_err(DownloadError::from);
+        match res.and_then(|res| res.error_for_status().map_err(DownloadError::from)) {
             Ok(res) => Either::Left(unfold(res, |mut res| async {
                 match res.chunk().await {
-                    Err(err) => Some((Err(err), res)),
+                    Err(err) => Some((Err(err.into()), res)),
                     Ok(Some(c)) => Some((Ok(c), res)),
                     Ok(None) => None,
                 }
@@ -134,3 +503,155 @@ pub fn download_file_stream(
         }
     })
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use cool_asserts::assert_matches;
+    use futures::{
+        future::{ready, BoxFuture},
+        stream::{once, BoxStream},
+        StreamExt,
+    };
+    use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+    use crate::{
+        errors::DownloadError,
+        net::{Download, DownloadOptions},
+        testing::FakeServer,
+    };
+
+    #[tokio::test]
+    async fn options() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        let file = server.add_file("0123456789");
+
+        let mut data = Vec::new();
+        let options = DownloadOptions::new().max_size(5);
+        assert_matches!(
+            bot.download_file_with(&file.path, &mut data, options).await,
+            Err(DownloadError::TooLarge { limit: 5 })
+        );
+        assert_eq!(data, b"");
+
+        let options = DownloadOptions::new().expected_size(3);
+        assert_matches!(
+            bot.download_file_with(&file.path, &mut data, options).await,
+            Err(DownloadError::SizeMismatch { expected: 3, actual: 10 })
+        );
+
+        let mut data = b"0123".to_vec();
+        let options = DownloadOptions::new().resume_from(4).expected_size(file.size);
+        bot.download_file_with(&file.path, &mut data, options).await.unwrap();
+        assert_eq!(data, b"0123456789");
+
+        // Nothing is left to download
+        let options = DownloadOptions::new().resume_from(10).expected_size(file.size);
+        bot.download_file_with(&file.path, &mut data, options).await.unwrap();
+        assert_eq!(data, b"0123456789");
+
+        // The destination can't be a part of the file
+        let options = DownloadOptions::new().resume_from(12);
+        assert_matches!(
+            bot.download_file_with(&file.path, &mut data, options).await,
+            Err(DownloadError::SizeMismatch { expected: 10, actual: 12 })
+        );
+    }
+
+    /// A `Download` implementation which only provides `download_file`.
+    struct Static(&'static [u8]);
+
+    impl Download for Static {
+        type Err<'dst> = DownloadError;
+
+        type Fut<'dst> = BoxFuture<'dst, Result<(), DownloadError>>;
+
+        fn download_file<'dst>(
+            &self,
+            _path: &'dst str,
+            destination: &'dst mut (dyn AsyncWrite + Unpin + Send),
+        ) -> Self::Fut<'dst> {
+            let data = self.0;
+            Box::pin(async move {
+                for chunk in data.chunks(4) {
+                    destination.write_all(chunk).await.map_err(Arc::new)?;
+                }
+                Ok(())
+            })
+        }
+
+        type StreamErr = DownloadError;
+
+        type Stream = BoxStream<'static, Result<Bytes, DownloadError>>;
+
+        fn download_file_stream(&self, _path: &str) -> Self::Stream {
+            once(ready(Ok(Bytes::from_static(self.0)))).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn default_options() {
+        let downloader = Static(b"0123456789");
+
+        let mut data = Vec::new();
+        let options = DownloadOptions::new().max_size(5);
+        assert_matches!(
+            downloader.download_file_with("", &mut data, options).await,
+            Err(DownloadError::TooLarge { limit: 5 })
+        );
+        assert_eq!(data, b"0123");
+
+        let mut data = Vec::new();
+        let options = DownloadOptions::new().expected_size(3);
+        assert_matches!(
+            downloader.download_file_with("", &mut data, options).await,
+            Err(DownloadError::SizeMismatch { expected: 3, actual: 10 })
+        );
+
+        let mut data = Vec::new();
+        let options = DownloadOptions::new().max_size(10).expected_size(10);
+        downloader.download_file_with("", &mut data, options).await.unwrap();
+        assert_eq!(data, b"0123456789");
+
+        let mut data = b"0123".to_vec();
+        let options = DownloadOptions::new().resume_from(4);
+        assert_matches!(
+            downloader.download_file_with("", &mut data, options).await,
+            Err(DownloadError::Io(err)) if err.kind() == std::io::ErrorKind::Unsupported
+        );
+        assert_eq!(data, b"0123");
+    }
+
+    #[tokio::test]
+    async fn download_to() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        let file = server.add_file("0123456789");
+
+        let dir = std::env::temp_dir().join(format!("teloxide-download-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir(&dir).await.unwrap();
+        let target = dir.join("file.txt");
+        let part = dir.join("file.txt.part");
+
+        // A failed download doesn't create the target
+        let options = DownloadOptions::new().max_size(5);
+        assert_matches!(
+            bot.download_file_to(&file.path, &target, options).await,
+            Err(DownloadError::TooLarge { .. })
+        );
+        assert!(!target.exists());
+        assert!(!part.exists());
+
+        // A partial file is resumed
+        tokio::fs::write(&part, "01234").await.unwrap();
+        let options = DownloadOptions::new().resume(true).expected_size(file.size);
+        bot.download_file_to(&file.path, &target, options).await.unwrap();
+        assert_eq!(tokio::fs::read(&target).await.unwrap(), b"0123456789");
+        assert!(!part.exists());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
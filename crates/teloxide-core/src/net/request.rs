@@ -1,21 +1,27 @@
 use std::{any::TypeId, sync::Arc, time::Duration};
 
 use reqwest::{
-    header::{HeaderValue, CONTENT_TYPE},
-    Client, Response,
+    header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    Body, Method, Request, Response,
 };
 use serde::de::DeserializeOwned;
 
-use crate::{net::TelegramResponse, requests::ResponseResult, RequestError};
+use crate::{
+    errors::redact_token,
+    net::{HttpTransport, TelegramResponse},
+    requests::ResponseResult,
+    serde_multipart::MultipartForm,
+    RequestError,
+};
 
 const DELAY_ON_SERVER_ERROR: Duration = Duration::from_secs(10);
 
 pub async fn request_multipart<T>(
-    client: &Client,
+    transport: &dyn HttpTransport,
     token: &str,
     api_url: reqwest::Url,
     method_name: &str,
-    params: reqwest::multipart::Form,
+    params: MultipartForm,
     _timeout_hint: Option<Duration>,
 ) -> ResponseResult<T>
 where
@@ -34,23 +40,28 @@ where
     // [#460]: https://github.com/teloxide/teloxide/issues/460
     let method_name = method_name.trim_end_matches("Inline");
 
-    let request = client
-        .post(crate::net::method_url(api_url, token, method_name))
-        .multipart(params)
-        .build()?;
+    let mut request =
+        Request::new(Method::POST, crate::net::method_url(api_url, token, method_name));
+    let content_type = format!("multipart/form-data; boundary={}", params.boundary());
+    let headers = request.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::try_from(content_type).expect("boundary is ASCII"));
+    if let Some(length) = params.content_length() {
+        headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+    }
+    *request.body_mut() = Some(Body::wrap_stream(params.into_stream()));
 
     // FIXME: uncomment this, when reqwest starts setting default timeout early
     // if let Some(timeout) = timeout_hint {
     //     *request.timeout_mut().get_or_insert(Duration::ZERO) += timeout;
     // }
 
-    let response = client.execute(request).await?;
+    let response = transport.execute(request).await.map_err(|err| err.hide_token(token))?;
 
-    process_response(response).await
+    process_response(response).await.map_err(|err| hide_token_in_raw(err, token))
 }
 
 pub async fn request_json<T>(
-    client: &Client,
+    transport: &dyn HttpTransport,
     token: &str,
     api_url: reqwest::Url,
     method_name: &str,
@@ -73,20 +84,19 @@ where
     // [#460]: https://github.com/teloxide/teloxide/issues/460
     let method_name = method_name.trim_end_matches("Inline");
 
-    let request = client
-        .post(crate::net::method_url(api_url, token, method_name))
-        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
-        .body(params)
-        .build()?;
+    let mut request =
+        Request::new(Method::POST, crate::net::method_url(api_url, token, method_name));
+    request.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    *request.body_mut() = Some(params.into());
 
     // FIXME: uncomment this, when reqwest starts setting default timeout early
     // if let Some(timeout) = timeout_hint {
     //     *request.timeout_mut().get_or_insert(Duration::ZERO) += timeout;
     // }
 
-    let response = client.execute(request).await?;
+    let response = transport.execute(request).await.map_err(|err| err.hide_token(token))?;
 
-    process_response(response).await
+    process_response(response).await.map_err(|err| hide_token_in_raw(err, token))
 }
 
 async fn process_response<T>(response: Response) -> ResponseResult<T>
@@ -102,6 +112,16 @@ where
     deserialize_response(text)
 }
 
+// Error pages of proxies may include the URL of the request, with the token
+fn hide_token_in_raw(error: RequestError, token: &str) -> RequestError {
+    match error {
+        RequestError::InvalidJson { source, raw } => {
+            RequestError::InvalidJson { source, raw: redact_token(&raw, token).into() }
+        }
+        error => error,
+    }
+}
+
 fn deserialize_response<T>(text: String) -> Result<T, RequestError>
 where
     T: DeserializeOwned + 'static,
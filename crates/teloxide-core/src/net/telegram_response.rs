@@ -25,9 +25,8 @@ pub(crate) enum TelegramResponse<R> {
         #[serde(rename = "description")]
         error: ApiError,
 
-        // // This field is present in the json sent by telegram, but isn't currently used anywhere
-        // // and as such - ignored
-        // error_code: u16,
+        error_code: Option<u16>,
+
         #[serde(rename = "parameters")]
         response_parameters: Option<ResponseParameters>,
     },
@@ -41,7 +40,14 @@ impl<R> From<TelegramResponse<R>> for ResponseResult<R> {
                 ResponseParameters::RetryAfter(i) => RequestError::RetryAfter(i),
                 ResponseParameters::MigrateToChatId(to) => RequestError::MigrateToChatId(to),
             }),
-            TelegramResponse::Err { error, .. } => Err(RequestError::Api(error)),
+            TelegramResponse::Err { mut error, error_code, response_parameters, .. } => {
+                if let ApiError::Unknown(response) = &mut error {
+                    response.error_code = error_code;
+                    response.parameters = response_parameters;
+                }
+
+                Err(RequestError::Api(error))
+            }
         }
     }
 }
@@ -68,7 +74,19 @@ mod tests {
         let val = serde_json::from_str::<TelegramResponse<Update>>(s).unwrap();
 
         assert!(
-            matches!(val, TelegramResponse::Err { error: ApiError::Unknown(s), .. } if s == "Unknown description that won't match anything")
+            matches!(val.into(), ResponseResult::<Update>::Err(RequestError::Api(ApiError::Unknown(response))) if response.description == "Unknown description that won't match anything" && response.error_code == Some(111))
         );
     }
+
+    #[test]
+    fn parse_without_error_code() {
+        let s = r#"{"ok":false,"description":"Unknown description that won't match anything"}"#;
+        let val = serde_json::from_str::<TelegramResponse<Update>>(s).unwrap();
+
+        assert!(matches!(
+            val.into(),
+            ResponseResult::<Update>::Err(RequestError::Api(ApiError::Unknown(response)))
+                if response.error_code.is_none()
+        ));
+    }
 }
@@ -0,0 +1,132 @@
+use std::error::Error as StdError;
+
+use futures::{future::BoxFuture, FutureExt};
+use reqwest::{Client, Request, Response};
+use thiserror::Error;
+
+use crate::errors::redact_token;
+
+/// HTTP client used by [`Bot`] to send requests to Telegram.
+///
+/// [`reqwest::Client`] implements this trait and is used by default. Other
+/// implementations can be passed to [`Bot::with_transport`] to send requests
+/// with a different HTTP client, through a custom connection pool, DNS
+/// resolver or proxy, or to answer them in memory in tests.
+///
+/// Requests and responses are [`reqwest`] types. Transports which don't use
+/// `reqwest` can convert them to and from [`http`] types with
+/// `http::Request::try_from(request)` and `Response::from(http_response)`.
+///
+/// ## Examples
+///
+/// ```
+/// use futures::{future::BoxFuture, FutureExt};
+/// use teloxide_core::{
+///     net::{HttpTransport, TransportError},
+///     Bot,
+/// };
+///
+/// /// Logs requests before sending them with `reqwest`.
+/// struct Logging(reqwest::Client);
+///
+/// impl HttpTransport for Logging {
+///     fn execute(
+///         &self,
+///         request: reqwest::Request,
+///     ) -> BoxFuture<'static, Result<reqwest::Response, TransportError>> {
+///         log::debug!("{} {}", request.method(), request.url());
+///         HttpTransport::execute(&self.0, request)
+///     }
+/// }
+///
+/// let bot = Bot::with_transport("TOKEN", Logging(reqwest::Client::new()));
+/// # let _ = bot;
+/// ```
+///
+/// [`Bot`]: crate::Bot
+/// [`Bot::with_transport`]: crate::Bot::with_transport
+/// [`http`]: https://docs.rs/http
+pub trait HttpTransport: Send + Sync + 'static {
+    /// Sends a request and returns the response.
+    ///
+    /// Responses with non-success status codes should be returned as `Ok`,
+    /// since Telegram describes errors in the response body.
+    fn execute(&self, request: Request) -> BoxFuture<'static, Result<Response, TransportError>>;
+}
+
+/// An error of [`HttpTransport`].
+///
+/// [`Reqwest`] errors are converted to [`RequestError::Network`] and
+/// [`DownloadError::Network`], [`Other`] errors are converted to
+/// [`RequestError::Io`] and [`DownloadError::Io`].
+///
+/// [`Reqwest`]: TransportError::Reqwest
+/// [`Other`]: TransportError::Other
+/// [`RequestError::Network`]: crate::RequestError::Network
+/// [`RequestError::Io`]: crate::RequestError::Io
+/// [`DownloadError::Network`]: crate::DownloadError::Network
+/// [`DownloadError::Io`]: crate::DownloadError::Io
+#[derive(Debug, Error)]
+pub enum TransportError {
+    /// An error of [`reqwest::Client`].
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    /// An error of another transport.
+    #[error(transparent)]
+    Other(Box<dyn StdError + Send + Sync>),
+}
+
+impl TransportError {
+    /// Creates an error of a transport other than [`reqwest::Client`].
+    pub fn other<E>(error: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Self::Other(error.into())
+    }
+
+    /// Replaces `token` in errors of other transports, since they may include
+    /// the URL of the request.
+    ///
+    /// URLs of [`reqwest`] errors are redacted when they are converted to
+    /// [`RequestError`] or [`DownloadError`].
+    ///
+    /// [`RequestError`]: crate::RequestError
+    /// [`DownloadError`]: crate::DownloadError
+    pub(crate) fn hide_token(self, token: &str) -> Self {
+        match self {
+            Self::Other(error) => {
+                let display = error.to_string();
+                let leaks = !token.is_empty()
+                    && (display.contains(token) || format!("{error:?}").contains(token));
+
+                if leaks {
+                    Self::Other(redact_token(&display, token).into())
+                } else {
+                    Self::Other(error)
+                }
+            }
+            error @ Self::Reqwest(_) => error,
+        }
+    }
+}
+
+impl HttpTransport for Client {
+    fn execute(&self, request: Request) -> BoxFuture<'static, Result<Response, TransportError>> {
+        Client::execute(self, request).map(|res| res.map_err(TransportError::from)).boxed()
+    }
+}
+
+/// Transport of bots which are only used to build requests, e.g. by `Replay`
+/// and `MockBot`, it fails all requests without sending them.
+#[cfg(any(feature = "record_replay", feature = "testing"))]
+pub(crate) struct NoTransport;
+
+#[cfg(any(feature = "record_replay", feature = "testing"))]
+impl HttpTransport for NoTransport {
+    fn execute(&self, _: Request) -> BoxFuture<'static, Result<Response, TransportError>> {
+        let error = TransportError::other("this bot doesn't send requests");
+        futures::future::ready(Err(error)).boxed()
+    }
+}
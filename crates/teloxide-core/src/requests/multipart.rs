@@ -11,7 +11,10 @@ use crate::{
 /// A ready-to-send Telegram request whose payload is sent using
 /// [multipart/form-data].
 ///
+/// Progress of the file uploads can be observed with [`InputFile::progress`].
+///
 /// [multipart/form-data]: https://core.telegram.org/bots/api#making-requests
+/// [`InputFile::progress`]: crate::types::InputFile::progress
 #[must_use = "Requests are lazy and do nothing unless sent"]
 #[derive(Clone)]
 pub struct MultipartRequest<P> {
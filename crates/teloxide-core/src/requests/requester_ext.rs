@@ -12,6 +12,36 @@ use crate::adaptors::trace::{Settings, Trace};
 #[cfg(feature = "throttle")]
 use crate::adaptors::throttle::{Limits, Throttle};
 
+#[cfg(feature = "retry")]
+use crate::adaptors::retry::{Retry, RetryPolicy};
+
+#[cfg(feature = "follow_migrations")]
+use crate::adaptors::FollowMigrations;
+
+#[cfg(feature = "metrics")]
+use crate::adaptors::Metrics;
+
+#[cfg(feature = "trace_spans")]
+use crate::adaptors::TraceSpans;
+
+#[cfg(feature = "record_replay")]
+use crate::adaptors::Record;
+
+#[cfg(feature = "file_cache")]
+use crate::adaptors::FileCache;
+
+#[cfg(feature = "ttl_cache")]
+use crate::adaptors::TtlCache;
+
+#[cfg(feature = "coalesce")]
+use crate::adaptors::Coalesce;
+
+#[cfg(feature = "circuit_breaker")]
+use crate::adaptors::{circuit_breaker::Settings as CircuitBreakerSettings, CircuitBreaker};
+
+#[cfg(feature = "timeout")]
+use crate::adaptors::{timeout::Settings as TimeoutSettings, Timeout};
+
 /// Extensions methods for [`Requester`].
 pub trait RequesterExt: Requester {
     /// Add `get_me` caching ability, see [`CacheMe`] for more.
@@ -59,6 +89,115 @@ pub trait RequesterExt: Requester {
         Throttle::new_spawn(self, limits)
     }
 
+    /// Retry failed requests, see [`Retry`] for more.
+    #[cfg(feature = "retry")]
+    #[must_use]
+    fn retry(self, policy: RetryPolicy) -> Retry<Self>
+    where
+        Self: Sized + Requester<Err = crate::RequestError>,
+    {
+        Retry::new(self, policy)
+    }
+
+    /// Follow group to supergroup migrations, see [`FollowMigrations`] for
+    /// more.
+    #[cfg(feature = "follow_migrations")]
+    #[must_use]
+    fn follow_migrations(self) -> FollowMigrations<Self>
+    where
+        Self: Sized,
+    {
+        FollowMigrations::new(self)
+    }
+
+    /// Collect statistics of requests, see [`Metrics`] for more.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    fn metrics(self) -> Metrics<Self>
+    where
+        Self: Sized,
+    {
+        Metrics::new(self)
+    }
+
+    /// Open a [`tracing`] span for every request, see [`TraceSpans`] for
+    /// more.
+    ///
+    /// [`tracing`]: ::tracing
+    #[cfg(feature = "trace_spans")]
+    #[must_use]
+    fn trace_spans(self) -> TraceSpans<Self>
+    where
+        Self: Sized,
+    {
+        TraceSpans::new(self)
+    }
+
+    /// Record requests and responses to `writer`, see [`Record`] for more.
+    #[cfg(feature = "record_replay")]
+    #[must_use]
+    fn record<W>(self, writer: W) -> Record<Self>
+    where
+        Self: Sized + Requester<Err = crate::RequestError>,
+        W: std::io::Write + Send + 'static,
+    {
+        Record::new(self, writer)
+    }
+
+    /// Reuse `file_id`s of already uploaded files, see [`FileCache`] for
+    /// more.
+    #[cfg(feature = "file_cache")]
+    #[must_use]
+    fn file_cache(self) -> FileCache<Self>
+    where
+        Self: Sized + Requester<Err = crate::RequestError>,
+    {
+        FileCache::new(self)
+    }
+
+    /// Cache responses of read-only methods, see [`TtlCache`] for more.
+    #[cfg(feature = "ttl_cache")]
+    #[must_use]
+    fn ttl_cache(self) -> TtlCache<Self>
+    where
+        Self: Sized,
+    {
+        TtlCache::new(self)
+    }
+
+    /// Send identical concurrent read-only requests only once, see
+    /// [`Coalesce`] for more.
+    #[cfg(feature = "coalesce")]
+    #[must_use]
+    fn coalesce(self) -> Coalesce<Self>
+    where
+        Self: Sized,
+    {
+        Coalesce::new(self)
+    }
+
+    /// Fail requests fast while Telegram is unavailable, see
+    /// [`CircuitBreaker`] for more.
+    #[cfg(feature = "circuit_breaker")]
+    #[must_use]
+    fn circuit_breaker(self, settings: CircuitBreakerSettings) -> CircuitBreaker<Self>
+    where
+        Self: Sized,
+    {
+        CircuitBreaker::new(self, settings)
+    }
+
+    /// Limit the time requests can take and allow to cancel them, see
+    /// [`Timeout`] for more.
+    #[cfg(feature = "timeout")]
+    #[must_use]
+    fn timeout(self, settings: TimeoutSettings) -> Timeout<Self>
+    where
+        Self: Sized,
+    {
+        Timeout::new(self, settings)
+    }
+
     /// Specifies default [`ParseMode`], which will be used during all calls to:
     ///
     ///  - [`send_message`]
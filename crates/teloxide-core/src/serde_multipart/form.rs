@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use bytes::Bytes;
+use futures::Stream;
+use reqwest::multipart::{Form, Part};
+
+/// [`Form`] which keeps track of its encoded length.
+///
+/// `reqwest` only computes the length of a form when it's sent by a
+/// `reqwest::Client`, but requests are built without one, so the length needed
+/// for `Content-Length` has to be computed here.
+pub(crate) struct MultipartForm {
+    form: Form,
+    /// Length of all parts so far, `None` if some part has an unknown length.
+    length: Option<u64>,
+    empty: bool,
+}
+
+impl MultipartForm {
+    pub(crate) fn new() -> Self {
+        Self { form: Form::new(), length: Some(0), empty: true }
+    }
+
+    /// Adds a part with the value `value`.
+    pub(crate) fn bytes<T>(self, name: T, value: Vec<u8>) -> Self
+    where
+        T: Into<Cow<'static, str>>,
+    {
+        let len = value.len() as u64;
+        self.part(name.into(), Part::bytes(value), None, Some(len))
+    }
+
+    /// Adds a part, `file_name` and `len` must be the file name and the length
+    /// of the value of `part`, if it has any.
+    pub(crate) fn part(
+        mut self,
+        name: Cow<'static, str>,
+        part: Part,
+        file_name: Option<&str>,
+        len: Option<u64>,
+    ) -> Self {
+        let header = header_len(&name, file_name);
+        // Mirrors `--{boundary}\r\n{header}\r\n\r\n{value}\r\n`, the format of a part
+        // in `reqwest`
+        self.length = self.length.zip(header).zip(len).map(|((length, header), len)| {
+            length + 2 + self.form.boundary().len() as u64 + 2 + header + 4 + len + 2
+        });
+        self.empty = false;
+        self.form = self.form.part(name, part);
+        self
+    }
+
+    pub(crate) fn boundary(&self) -> &str {
+        self.form.boundary()
+    }
+
+    /// Returns the length of the encoded form, if all of the parts have a known
+    /// length.
+    pub(crate) fn content_length(&self) -> Option<u64> {
+        // The form ends with `--{boundary}--\r\n`, unless it's empty
+        match self.empty {
+            true => self.length,
+            false => self.length.map(|length| length + 2 + self.boundary().len() as u64 + 4),
+        }
+    }
+
+    pub(crate) fn into_stream(
+        self,
+    ) -> impl Stream<Item = Result<Bytes, reqwest::Error>> + Send + Sync + 'static {
+        self.form.into_stream()
+    }
+}
+
+/// Returns the length of the `Content-Disposition` header of a part, or `None`
+/// if `reqwest` would percent-encode the name.
+fn header_len(name: &str, file_name: Option<&str>) -> Option<u64> {
+    // Names of fields and ids of files never need to be encoded, so other names
+    // are just not supported
+    let plain = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c == b'-';
+    if !name.bytes().all(plain) {
+        return None;
+    }
+
+    let mut len = "Content-Disposition: form-data; name=\"\"".len() + name.len();
+    if let Some(file_name) = file_name {
+        // `\`, `"`, `\r` and `\n` are escaped with a `\`
+        let escaped = file_name.bytes().filter(|c| matches!(c, b'\\' | b'"' | b'\r' | b'\n'));
+        len += "; filename=\"\"".len() + file_name.len() + escaped.count();
+    }
+
+    Some(len as u64)
+}
@@ -11,25 +11,33 @@
 //! versions (in favor of something less automatic, but more simple).
 
 mod error;
+mod form;
 mod serializers;
 
 use std::future::Future;
 
-use reqwest::multipart::Form;
 use serde::Serialize;
 
-use crate::requests::MultipartPayload;
+use crate::{requests::MultipartPayload, types::InputFile};
 use error::Error;
 use serializers::MultipartSerializer;
 
-/// Serializes given value into [`Form`] **taking all input files out**.
+pub(crate) use form::MultipartForm;
+pub(crate) use serializers::local_mode;
+
+/// Serializes given value into [`MultipartForm`] **taking all input files
+/// out**.
 ///
-/// [`Form`]:  reqwest::multipart::Form
-pub(crate) fn to_form<T>(val: &mut T) -> Result<impl Future<Output = Form>, Error>
+/// If `local` is `true`, files from the filesystem are referenced by `file://`
+/// URIs instead of being uploaded, see [`local_mode`].
+pub(crate) fn to_form<T>(
+    val: &mut T,
+    local: bool,
+) -> Result<impl Future<Output = MultipartForm>, Error>
 where
     T: Serialize + MultipartPayload,
 {
-    let mut form = val.serialize(MultipartSerializer::new())?;
+    let mut form = val.serialize(MultipartSerializer::new(local))?;
 
     let mut vec = Vec::with_capacity(1);
     val.move_files(&mut |f| vec.push(f));
@@ -37,10 +45,11 @@ where
 
     let fut = async move {
         for file in iter {
-            if file.needs_attach() {
+            if needs_part(&file, local) {
                 let id = file.id().to_owned();
                 if let Some(part) = file.into_part() {
-                    form = form.part(id, part.await);
+                    let (part, file_name, len) = part.await;
+                    form = form.part(id.into(), part, Some(&file_name), len);
                 }
             }
         }
@@ -51,14 +60,18 @@ where
     Ok(fut)
 }
 
-/// Serializes given value into [`Form`].
+/// Serializes given value into [`MultipartForm`].
 ///
-/// [`Form`]:  reqwest::multipart::Form
-pub(crate) fn to_form_ref<T: ?Sized>(val: &T) -> Result<impl Future<Output = Form>, Error>
+/// If `local` is `true`, files from the filesystem are referenced by `file://`
+/// URIs instead of being uploaded, see [`local_mode`].
+pub(crate) fn to_form_ref<T: ?Sized>(
+    val: &T,
+    local: bool,
+) -> Result<impl Future<Output = MultipartForm>, Error>
 where
     T: Serialize + MultipartPayload,
 {
-    let mut form = val.serialize(MultipartSerializer::new())?;
+    let mut form = val.serialize(MultipartSerializer::new(local))?;
     let mut vec = Vec::with_capacity(1);
     val.copy_files(&mut |f| vec.push(f));
 
@@ -66,10 +79,11 @@ where
 
     let fut = async move {
         for file in iter {
-            if file.needs_attach() {
+            if needs_part(&file, local) {
                 let id = file.id().to_owned();
                 if let Some(part) = file.into_part() {
-                    form = form.part(id, part.await);
+                    let (part, file_name, len) = part.await;
+                    form = form.part(id.into(), part, Some(&file_name), len);
                 }
             }
         }
@@ -80,6 +94,11 @@ where
     Ok(fut)
 }
 
+/// Returns `true` if `file` has to be uploaded as a separate part.
+fn needs_part(file: &InputFile, local: bool) -> bool {
+    file.needs_attach() && !(local && file.local_url().is_some())
+}
+
 #[cfg(test)]
 mod tests {
     use tokio::fs::File;
@@ -101,6 +120,7 @@ mod tests {
             &payloads::SendPhoto::new(ChatId(0), InputFile::file_id("0".into())).caption_entities(
                 [MessageEntity { kind: MessageEntityKind::Url, offset: 0, length: 0 }],
             ),
+            false,
         )
         .unwrap()
         .await;
@@ -110,56 +130,65 @@ mod tests {
     async fn test_send_media_group() {
         const CAPTION: &str = "caption";
 
-        to_form_ref(&payloads::SendMediaGroup::new(
-            ChatId(0),
-            [
-                InputMedia::Photo(
-                    InputMediaPhoto::new(InputFile::file("../../media/teloxide-core-logo.png"))
-                        .caption(CAPTION)
-                        .parse_mode(ParseMode::MarkdownV2)
-                        .caption_entities(entities()),
-                ),
-                InputMedia::Video(
-                    InputMediaVideo::new(InputFile::file_id("17".into())).supports_streaming(true),
-                ),
-                InputMedia::Animation(
-                    InputMediaAnimation::new(InputFile::read(
-                        File::open("../../media/example.gif").await.unwrap(),
-                    ))
-                    .thumbnail(InputFile::read(
-                        File::open("../../media/teloxide-core-logo.png").await.unwrap(),
-                    ))
-                    .duration(17),
-                ),
-                InputMedia::Audio(
-                    InputMediaAudio::new(InputFile::url("https://example.com".parse().unwrap()))
+        to_form_ref(
+            &payloads::SendMediaGroup::new(
+                ChatId(0),
+                [
+                    InputMedia::Photo(
+                        InputMediaPhoto::new(InputFile::file("../../media/teloxide-core-logo.png"))
+                            .caption(CAPTION)
+                            .parse_mode(ParseMode::MarkdownV2)
+                            .caption_entities(entities()),
+                    ),
+                    InputMedia::Video(
+                        InputMediaVideo::new(InputFile::file_id("17".into()))
+                            .supports_streaming(true),
+                    ),
+                    InputMedia::Animation(
+                        InputMediaAnimation::new(InputFile::read(
+                            File::open("../../media/example.gif").await.unwrap(),
+                        ))
+                        .thumbnail(InputFile::read(
+                            File::open("../../media/teloxide-core-logo.png").await.unwrap(),
+                        ))
+                        .duration(17),
+                    ),
+                    InputMedia::Audio(
+                        InputMediaAudio::new(InputFile::url(
+                            "https://example.com".parse().unwrap(),
+                        ))
                         .performer("a"),
-                ),
-                InputMedia::Document(InputMediaDocument::new(InputFile::memory(
-                    &b"Hello world!"[..],
-                ))),
-            ],
-        ))
+                    ),
+                    InputMedia::Document(InputMediaDocument::new(InputFile::memory(
+                        &b"Hello world!"[..],
+                    ))),
+                ],
+            ),
+            false,
+        )
         .unwrap()
         .await;
     }
 
     #[tokio::test]
     async fn test_add_sticker_to_set() {
-        to_form_ref(&payloads::AddStickerToSet::new(
-            UserId(0),
-            "name",
-            InputSticker {
-                sticker: InputFile::file(
-                    "../../media/
+        to_form_ref(
+            &payloads::AddStickerToSet::new(
+                UserId(0),
+                "name",
+                InputSticker {
+                    sticker: InputFile::file(
+                        "../../media/
                 teloxide-core-logo.png",
-                ),
-                emoji_list: vec!["✈️⚙️".to_owned()],
-                keywords: vec![],
-                mask_position: None,
-                format: StickerFormat::Static,
-            },
-        ))
+                    ),
+                    emoji_list: vec!["✈️⚙️".to_owned()],
+                    keywords: vec![],
+                    mask_position: None,
+                    format: StickerFormat::Static,
+                },
+            ),
+            false,
+        )
         .unwrap()
         .await;
     }
@@ -175,9 +204,43 @@ mod tests {
             .thumbnail(InputFile::read(
                 File::open("../../media/teloxide-core-logo.png").await.unwrap(),
             )),
+            false,
+        )
+        .unwrap()
+        .await;
+    }
+
+    #[tokio::test]
+    async fn content_length() {
+        use futures::TryStreamExt;
+
+        let form = to_form_ref(
+            &payloads::SendDocument::new(
+                ChatId(0),
+                InputFile::memory(&b"Hello world!"[..]).file_name("a \"quoted\"\\name"),
+            )
+            .caption("Привет")
+            .caption_entities(entities())
+            .thumbnail(InputFile::file("../../media/teloxide-core-logo.png")),
+            false,
+        )
+        .unwrap()
+        .await;
+
+        let length = form.content_length().expect("all parts have a known length");
+        let body: Vec<_> = form.into_stream().try_collect().await.unwrap();
+        assert_eq!(length, body.iter().map(|chunk| chunk.len() as u64).sum::<u64>());
+
+        let form = to_form_ref(
+            &payloads::SendDocument::new(
+                ChatId(0),
+                InputFile::read(File::open("../../media/teloxide-core-logo.png").await.unwrap()),
+            ),
+            false,
         )
         .unwrap()
         .await;
+        assert_eq!(form.content_length(), None);
     }
 
     fn entities() -> impl Iterator<Item = MessageEntity> {
@@ -1,18 +1,23 @@
-use crate::serde_multipart::error::Error;
+use std::cell::Cell;
+
+use crate::serde_multipart::{error::Error, form::MultipartForm};
 
-use reqwest::multipart::{Form, Part};
 use serde::{
     ser::{Impossible, SerializeMap, SerializeSeq, SerializeStruct},
     Serialize, Serializer,
 };
 
 /// The main serializer that serializes top-level and structures
-pub(super) struct MultipartSerializer(Form);
+pub(super) struct MultipartSerializer {
+    form: MultipartForm,
+    local: bool,
+}
 
 /// Serializer for maps (support for `#[serde(flatten)]`)
 pub(super) struct MultipartMapSerializer {
-    form: Form,
+    form: MultipartForm,
     key: Option<String>,
+    local: bool,
 }
 
 /// Serializer for single "fields" that are serialized as multipart "part"s.
@@ -38,14 +43,40 @@ enum PartSerializerStructState {
     Rest,
 }
 
+thread_local! {
+    static LOCAL_MODE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns `true` if a field is being serialized for a local Bot API server.
+///
+/// In this case `InputFile`s from the filesystem are serialized as `file://`
+/// URIs, which the server reads directly, instead of being attached.
+pub(crate) fn local_mode() -> bool {
+    LOCAL_MODE.with(Cell::get)
+}
+
 impl MultipartSerializer {
-    pub(super) fn new() -> Self {
-        Self(Form::new())
+    /// Creates a serializer, `local` enables [`local_mode`] while the fields
+    /// are serialized.
+    pub(super) fn new(local: bool) -> Self {
+        Self { form: MultipartForm::new(), local }
     }
 }
 
+/// Serializes a field into a part, with [`local_mode`] set to `local`.
+fn serialize_part<T: ?Sized>(value: &T, local: bool) -> Result<Vec<u8>, Error>
+where
+    T: Serialize,
+{
+    let prev = LOCAL_MODE.replace(local);
+    let part = value.serialize(PartSerializer {});
+    LOCAL_MODE.set(prev);
+
+    part
+}
+
 impl Serializer for MultipartSerializer {
-    type Ok = Form;
+    type Ok = MultipartForm;
     type Error = Error;
 
     // for `serde(flatten)` (e.g.: in CreateNewStickerSet)
@@ -62,7 +93,7 @@ impl Serializer for MultipartSerializer {
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(MultipartMapSerializer { form: Form::new(), key: None })
+        Ok(MultipartMapSerializer { form: MultipartForm::new(), key: None, local: self.local })
     }
 
     fn serialize_struct(
@@ -222,7 +253,7 @@ impl Serializer for MultipartSerializer {
 }
 
 impl SerializeStruct for MultipartSerializer {
-    type Ok = Form;
+    type Ok = MultipartForm;
     type Error = Error;
 
     fn serialize_field<T: ?Sized>(
@@ -233,19 +264,19 @@ impl SerializeStruct for MultipartSerializer {
     where
         T: Serialize,
     {
-        let part = value.serialize(PartSerializer {})?;
-        take_mut::take(&mut self.0, |f| f.part(key, part));
+        let value = serialize_part(value, self.local)?;
+        take_mut::take(&mut self.form, |f| f.bytes(key, value));
 
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.0)
+        Ok(self.form)
     }
 }
 
 impl SerializeMap for MultipartMapSerializer {
-    type Ok = Form;
+    type Ok = MultipartForm;
     type Error = Error;
 
     fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
@@ -265,9 +296,9 @@ impl SerializeMap for MultipartMapSerializer {
     {
         let key = self.key.take().expect("Value serialized before key or key is not string");
 
-        let part = value.serialize(PartSerializer {})?;
+        let value = serialize_part(value, self.local)?;
 
-        take_mut::take(&mut self.form, |f| f.part(key, part));
+        take_mut::take(&mut self.form, |f| f.bytes(key, value));
         Ok(())
     }
 
@@ -277,7 +308,7 @@ impl SerializeMap for MultipartMapSerializer {
 }
 
 impl Serializer for PartSerializer {
-    type Ok = Part;
+    type Ok = Vec<u8>;
     type Error = Error;
 
     type SerializeStruct = JsonPartSerializer;
@@ -291,59 +322,59 @@ impl Serializer for PartSerializer {
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_string()))
+        Ok(v.to_string().into())
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(v.to_owned()))
+        Ok(v.to_owned().into())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::bytes(v.to_owned()))
+        Ok(v.to_owned())
     }
 
     fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
@@ -359,7 +390,7 @@ impl Serializer for PartSerializer {
         _: u32,
         variant_name: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Ok(Part::text(variant_name))
+        Ok(variant_name.into())
     }
 
     fn serialize_struct(
@@ -453,7 +484,7 @@ impl Serializer for PartSerializer {
 }
 
 impl SerializeStruct for JsonPartSerializer {
-    type Ok = Part;
+    type Ok = Vec<u8>;
     type Error = Error;
 
     fn serialize_field<T: ?Sized>(
@@ -484,18 +515,18 @@ impl SerializeStruct for JsonPartSerializer {
         use PartSerializerStructState::*;
 
         match self.state {
-            Empty => Ok(Part::text("{{}}")),
+            Empty => Ok("{{}}".into()),
             Rest => {
                 self.buf += "}";
 
-                Ok(Part::text(self.buf))
+                Ok(self.buf.into())
             }
         }
     }
 }
 
 impl SerializeSeq for JsonPartSerializer {
-    type Ok = Part;
+    type Ok = Vec<u8>;
 
     type Error = Error;
 
@@ -523,11 +554,11 @@ impl SerializeSeq for JsonPartSerializer {
         use PartSerializerStructState::*;
 
         match self.state {
-            Empty => Ok(Part::text("[]")),
+            Empty => Ok("[]".into()),
             Rest => {
                 self.buf += "]";
 
-                Ok(Part::text(self.buf))
+                Ok(self.buf.into())
             }
         }
     }
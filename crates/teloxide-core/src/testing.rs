@@ -0,0 +1,9 @@
+//! Utilities for testing bots without access to Telegram.
+
+mod builders;
+mod fake_server;
+mod mock_bot;
+
+pub use builders::{CallbackQueryBuilder, ChatBuilder, MessageBuilder, UpdateBuilder, UserBuilder};
+pub use fake_server::FakeServer;
+pub use mock_bot::{MockBot, MockRequest, RecordedRequest};
@@ -0,0 +1,550 @@
+use std::{
+    sync::atomic::{AtomicI32, AtomicU32, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::types::{
+    CallbackQuery, CallbackQueryId, Chat, ChatId, ChatKind, ChatPrivate, ChatPublic,
+    InlineKeyboardMarkup, MaybeInaccessibleMessage, MediaKind, MediaText, Message, MessageCommon,
+    MessageEntity, MessageId, MessageKind, PublicChatChannel, PublicChatKind, PublicChatSupergroup,
+    ThreadId, Update, UpdateId, UpdateKind, User, UserId,
+};
+
+// Ids are unique across the process, so that fixtures created in different
+// places don't accidentally compare equal.
+static LAST_MESSAGE_ID: AtomicI32 = AtomicI32::new(0);
+static LAST_UPDATE_ID: AtomicU32 = AtomicU32::new(0);
+static LAST_CALLBACK_QUERY_ID: AtomicU32 = AtomicU32::new(0);
+
+/// The id of the user used by default as a sender.
+const DEFAULT_USER_ID: UserId = UserId(1);
+
+/// A builder of [`User`]s for tests.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::{testing::UserBuilder, types::UserId};
+///
+/// let user = UserBuilder::new(UserId(42)).first_name("Ferris").username("ferris").build();
+/// assert_eq!(user.username.as_deref(), Some("ferris"));
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct UserBuilder {
+    user: User,
+}
+
+impl UserBuilder {
+    /// Creates a builder of a regular (non-bot) user named "User".
+    pub fn new(id: UserId) -> Self {
+        Self {
+            user: User {
+                id,
+                is_bot: false,
+                first_name: "User".to_owned(),
+                last_name: None,
+                username: None,
+                language_code: None,
+                is_premium: false,
+                added_to_attachment_menu: false,
+            },
+        }
+    }
+
+    pub fn first_name(mut self, val: impl Into<String>) -> Self {
+        self.user.first_name = val.into();
+        self
+    }
+
+    pub fn last_name(mut self, val: impl Into<String>) -> Self {
+        self.user.last_name = Some(val.into());
+        self
+    }
+
+    pub fn username(mut self, val: impl Into<String>) -> Self {
+        self.user.username = Some(val.into());
+        self
+    }
+
+    pub fn language_code(mut self, val: impl Into<String>) -> Self {
+        self.user.language_code = Some(val.into());
+        self
+    }
+
+    pub fn is_bot(mut self, val: bool) -> Self {
+        self.user.is_bot = val;
+        self
+    }
+
+    pub fn is_premium(mut self, val: bool) -> Self {
+        self.user.is_premium = val;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> User {
+        self.user
+    }
+}
+
+impl Default for UserBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_USER_ID)
+    }
+}
+
+/// A builder of [`Chat`]s for tests.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::{testing::ChatBuilder, types::ChatId};
+///
+/// let chat = ChatBuilder::supergroup(ChatId(-1001234567890), "Rust").username("rust").build();
+/// assert_eq!(chat.title(), Some("Rust"));
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct ChatBuilder {
+    chat: Chat,
+}
+
+impl ChatBuilder {
+    /// Creates a builder of a private chat.
+    pub fn private(id: ChatId) -> Self {
+        let kind = ChatPrivate { username: None, first_name: None, last_name: None };
+        Self { chat: Chat { id, kind: ChatKind::Private(kind) } }
+    }
+
+    /// Creates a builder of a private chat with `user`.
+    pub fn private_with(user: &User) -> Self {
+        let kind = ChatPrivate {
+            username: user.username.clone(),
+            first_name: Some(user.first_name.clone()),
+            last_name: user.last_name.clone(),
+        };
+        Self { chat: Chat { id: user.id.into(), kind: ChatKind::Private(kind) } }
+    }
+
+    /// Creates a builder of a group chat.
+    pub fn group(id: ChatId, title: impl Into<String>) -> Self {
+        Self::public(id, title, PublicChatKind::Group)
+    }
+
+    /// Creates a builder of a supergroup chat.
+    pub fn supergroup(id: ChatId, title: impl Into<String>) -> Self {
+        let kind =
+            PublicChatSupergroup { username: None, is_forum: false, is_direct_messages: false };
+        Self::public(id, title, PublicChatKind::Supergroup(kind))
+    }
+
+    /// Creates a builder of a channel.
+    pub fn channel(id: ChatId, title: impl Into<String>) -> Self {
+        Self::public(id, title, PublicChatKind::Channel(PublicChatChannel { username: None }))
+    }
+
+    /// Sets a username of a private chat, supergroup or channel.
+    ///
+    /// Groups don't have usernames, so this does nothing for them.
+    pub fn username(mut self, val: impl Into<String>) -> Self {
+        let username = match &mut self.chat.kind {
+            ChatKind::Private(ChatPrivate { username, .. })
+            | ChatKind::Public(ChatPublic {
+                kind:
+                    PublicChatKind::Channel(PublicChatChannel { username })
+                    | PublicChatKind::Supergroup(PublicChatSupergroup { username, .. }),
+                ..
+            }) => username,
+            ChatKind::Public(ChatPublic { kind: PublicChatKind::Group, .. }) => return self,
+        };
+
+        *username = Some(val.into());
+        self
+    }
+
+    /// Makes a supergroup a forum.
+    ///
+    /// This does nothing for other kinds of chats.
+    pub fn is_forum(mut self, val: bool) -> Self {
+        if let ChatKind::Public(ChatPublic {
+            kind: PublicChatKind::Supergroup(PublicChatSupergroup { is_forum, .. }),
+            ..
+        }) = &mut self.chat.kind
+        {
+            *is_forum = val;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Chat {
+        self.chat
+    }
+
+    fn public(id: ChatId, title: impl Into<String>, kind: PublicChatKind) -> Self {
+        Self {
+            chat: Chat {
+                id,
+                kind: ChatKind::Public(ChatPublic { title: Some(title.into()), kind }),
+            },
+        }
+    }
+}
+
+/// A builder of [`Message`]s for tests.
+///
+/// By default a message gets a unique id, the current date and is sent by a
+/// default user into a private chat with the sender.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::{
+///     testing::{ChatBuilder, MessageBuilder, UserBuilder},
+///     types::{ChatId, UserId},
+/// };
+///
+/// let user = UserBuilder::new(UserId(42)).username("ferris").build();
+/// let chat = ChatBuilder::group(ChatId(-42), "Crabs").build();
+/// let message = MessageBuilder::text("/start").from(user).chat(chat).build();
+///
+/// assert_eq!(message.text(), Some("/start"));
+/// assert_eq!(message.chat.id, ChatId(-42));
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct MessageBuilder {
+    id: Option<MessageId>,
+    thread_id: Option<ThreadId>,
+    from: Option<User>,
+    sender_chat: Option<Chat>,
+    chat: Option<Chat>,
+    date: Option<DateTime<Utc>>,
+    common: MessageCommon,
+}
+
+impl MessageBuilder {
+    /// Creates a builder of a message with the specified media.
+    pub fn new(media_kind: MediaKind) -> Self {
+        Self {
+            id: None,
+            thread_id: None,
+            from: Some(UserBuilder::default().build()),
+            sender_chat: None,
+            chat: None,
+            date: None,
+            common: MessageCommon {
+                author_signature: None,
+                paid_star_count: None,
+                effect_id: None,
+                forward_origin: None,
+                reply_to_message: None,
+                external_reply: None,
+                quote: None,
+                reply_to_story: None,
+                reply_to_checklist_task_id: None,
+                sender_boost_count: None,
+                edit_date: None,
+                media_kind,
+                reply_markup: None,
+                is_automatic_forward: false,
+                has_protected_content: false,
+                is_from_offline: false,
+                business_connection_id: None,
+            },
+        }
+    }
+
+    /// Creates a builder of a text message.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new(MediaKind::Text(MediaText {
+            text: text.into(),
+            entities: Vec::new(),
+            link_preview_options: None,
+        }))
+    }
+
+    pub fn id(mut self, val: MessageId) -> Self {
+        self.id = Some(val);
+        self
+    }
+
+    pub fn thread_id(mut self, val: ThreadId) -> Self {
+        self.thread_id = Some(val);
+        self
+    }
+
+    /// Sets the sender of the message.
+    ///
+    /// If [`chat`] is not set, the message is sent into a private chat with
+    /// this user.
+    ///
+    /// [`chat`]: MessageBuilder::chat
+    pub fn from(mut self, val: User) -> Self {
+        self.from = Some(val);
+        self
+    }
+
+    /// Sets the chat on behalf of which the message was sent, removing the
+    /// user sender.
+    pub fn sender_chat(mut self, val: Chat) -> Self {
+        self.from = None;
+        self.sender_chat = Some(val);
+        self
+    }
+
+    pub fn chat(mut self, val: Chat) -> Self {
+        self.chat = Some(val);
+        self
+    }
+
+    pub fn date(mut self, val: DateTime<Utc>) -> Self {
+        self.date = Some(val);
+        self
+    }
+
+    pub fn edit_date(mut self, val: DateTime<Utc>) -> Self {
+        self.common.edit_date = Some(val);
+        self
+    }
+
+    pub fn reply_to(mut self, val: Message) -> Self {
+        self.common.reply_to_message = Some(Box::new(val));
+        self
+    }
+
+    pub fn reply_markup(mut self, val: InlineKeyboardMarkup) -> Self {
+        self.common.reply_markup = Some(val);
+        self
+    }
+
+    /// Sets entities of a text message.
+    ///
+    /// This does nothing for other kinds of messages.
+    pub fn entities(mut self, val: Vec<MessageEntity>) -> Self {
+        if let MediaKind::Text(MediaText { entities, .. }) = &mut self.common.media_kind {
+            *entities = val;
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Message {
+        let chat = match (self.chat, &self.from, &self.sender_chat) {
+            (Some(chat), _, _) => chat,
+            (None, Some(user), _) => ChatBuilder::private_with(user).build(),
+            (None, None, Some(sender_chat)) => sender_chat.clone(),
+            (None, None, None) => {
+                ChatBuilder::private_with(&UserBuilder::default().build()).build()
+            }
+        };
+
+        Message {
+            id: self.id.unwrap_or_else(|| MessageId(next_i32(&LAST_MESSAGE_ID))),
+            thread_id: self.thread_id,
+            direct_messages_topic: None,
+            from: self.from,
+            sender_chat: self.sender_chat,
+            date: self.date.unwrap_or_else(now),
+            chat,
+            is_topic_message: self.thread_id.is_some(),
+            suggested_post_info: None,
+            is_paid_post: false,
+            via_bot: None,
+            sender_business_bot: None,
+            kind: MessageKind::Common(self.common),
+        }
+    }
+}
+
+/// A builder of [`CallbackQuery`]s for tests.
+///
+/// By default a callback query gets a unique id and is sent by a default user.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::testing::{CallbackQueryBuilder, MessageBuilder};
+///
+/// let message = MessageBuilder::text("Choose:").build();
+/// let query = CallbackQueryBuilder::new("yes").message(message).build();
+/// assert_eq!(query.data.as_deref(), Some("yes"));
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct CallbackQueryBuilder {
+    query: CallbackQuery,
+}
+
+impl CallbackQueryBuilder {
+    /// Creates a builder of a callback query with the specified data.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            query: CallbackQuery {
+                id: CallbackQueryId(next_u32(&LAST_CALLBACK_QUERY_ID).to_string()),
+                from: UserBuilder::default().build(),
+                message: None,
+                inline_message_id: None,
+                chat_instance: "0".to_owned(),
+                data: Some(data.into()),
+                game_short_name: None,
+            },
+        }
+    }
+
+    pub fn id(mut self, val: CallbackQueryId) -> Self {
+        self.query.id = val;
+        self
+    }
+
+    pub fn from(mut self, val: User) -> Self {
+        self.query.from = val;
+        self
+    }
+
+    /// Sets the message with the callback button that originated the query.
+    pub fn message(mut self, val: Message) -> Self {
+        self.query.message = Some(MaybeInaccessibleMessage::Regular(Box::new(val)));
+        self
+    }
+
+    pub fn inline_message_id(mut self, val: impl Into<String>) -> Self {
+        self.query.inline_message_id = Some(val.into());
+        self
+    }
+
+    pub fn chat_instance(mut self, val: impl Into<String>) -> Self {
+        self.query.chat_instance = val.into();
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> CallbackQuery {
+        self.query
+    }
+}
+
+/// A builder of [`Update`]s for tests.
+///
+/// By default an update gets a unique id.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::{
+///     testing::{CallbackQueryBuilder, MessageBuilder, UpdateBuilder},
+///     types::UpdateKind,
+/// };
+///
+/// let update = UpdateBuilder::message(MessageBuilder::text("Hi!").build()).build();
+/// assert!(matches!(update.kind, UpdateKind::Message(_)));
+///
+/// let update = UpdateBuilder::callback_query(CallbackQueryBuilder::new("data").build()).build();
+/// assert!(matches!(update.kind, UpdateKind::CallbackQuery(_)));
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct UpdateBuilder {
+    id: Option<UpdateId>,
+    kind: UpdateKind,
+}
+
+impl UpdateBuilder {
+    /// Creates a builder of an update of the specified kind.
+    pub fn new(kind: UpdateKind) -> Self {
+        Self { id: None, kind }
+    }
+
+    pub fn message(message: Message) -> Self {
+        Self::new(UpdateKind::Message(message))
+    }
+
+    pub fn edited_message(message: Message) -> Self {
+        Self::new(UpdateKind::EditedMessage(message))
+    }
+
+    pub fn channel_post(message: Message) -> Self {
+        Self::new(UpdateKind::ChannelPost(message))
+    }
+
+    pub fn edited_channel_post(message: Message) -> Self {
+        Self::new(UpdateKind::EditedChannelPost(message))
+    }
+
+    pub fn callback_query(query: CallbackQuery) -> Self {
+        Self::new(UpdateKind::CallbackQuery(query))
+    }
+
+    pub fn id(mut self, val: UpdateId) -> Self {
+        self.id = Some(val);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Update {
+        Update {
+            id: self.id.unwrap_or_else(|| UpdateId(next_u32(&LAST_UPDATE_ID))),
+            kind: self.kind,
+        }
+    }
+}
+
+fn next_i32(counter: &AtomicI32) -> i32 {
+    counter.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+fn next_u32(counter: &AtomicU32) -> u32 {
+    counter.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+pub(super) fn now() -> DateTime<Utc> {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    DateTime::from_timestamp(secs as i64, 0).expect("current time to be representable")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testing::{CallbackQueryBuilder, ChatBuilder, MessageBuilder, UpdateBuilder, UserBuilder},
+        types::{ChatId, Update, UpdateKind, UserId},
+    };
+
+    #[test]
+    fn defaults() {
+        let first = MessageBuilder::text("first").build();
+        let second = MessageBuilder::text("second").build();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.from.as_ref().unwrap().id, UserId(1));
+        assert_eq!(first.chat.id, ChatId(1));
+        assert!(first.chat.is_private());
+
+        let user = UserBuilder::new(UserId(42)).build();
+        let message = MessageBuilder::text("").from(user).build();
+        assert_eq!(message.chat.id, ChatId(42));
+
+        let chat = ChatBuilder::channel(ChatId(-1001), "News").build();
+        let post = MessageBuilder::text("").sender_chat(chat.clone()).build();
+        assert_eq!(post.from, None);
+        assert_eq!(post.chat, chat);
+    }
+
+    #[test]
+    fn round_trip() {
+        let message = MessageBuilder::text("/start").build();
+        let query = CallbackQueryBuilder::new("data").message(message.clone()).build();
+
+        for update in
+            [UpdateBuilder::message(message).build(), UpdateBuilder::callback_query(query).build()]
+        {
+            let json = serde_json::to_string(&update).unwrap();
+            let parsed = serde_json::from_str::<Update>(&json).unwrap();
+
+            assert!(!matches!(parsed.kind, UpdateKind::Error(_)));
+            assert_eq!(parsed, update);
+        }
+    }
+}
@@ -0,0 +1,1147 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Incoming, header, server::conn::http1, service::service_fn, Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio::{net::TcpListener, sync::Notify, task::JoinHandle, time::Instant};
+use url::Url;
+
+use crate::{
+    requests::Payload,
+    testing::{builders::now, mock_bot::default_me, MessageBuilder, RecordedRequest},
+    types::{
+        Chat, ChatId, Document, File, FileId, FileMeta, FileUniqueId, InlineKeyboardMarkup, Me,
+        MediaDocument, MediaKind, Message, MessageCommon, MessageId, MessageKind, Update, UpdateId,
+        UpdateKind, UserId,
+    },
+    Bot,
+};
+
+mod schema;
+
+/// The only token accepted by [`FakeServer`].
+const TOKEN: &str = "1234567890:FAKE-bot-token";
+
+/// Id of the bot, matches the [`TOKEN`].
+const BOT_ID: UserId = UserId(1234567890);
+
+/// Header which is used to send the secret token to webhooks.
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// A fake Telegram Bot API server, listening on localhost.
+///
+/// The server keeps chats, messages and files in memory and implements the
+/// most commonly used methods (`getMe`, `getUpdates`, `setWebhook`,
+/// `deleteWebhook`, `getWebhookInfo`, `sendMessage`, `editMessageText`,
+/// `deleteMessage`, `sendDocument`, `getFile` and all methods that return
+/// `True`) as well as file downloads, including `Range` requests. This allows
+/// testing [`Bot`] and everything built on top of it — update listeners,
+/// adaptors, [`Download`] — end to end, without accessing Telegram.
+///
+/// Parameters of all requests are checked against the Bot API schema: calling
+/// an unknown method, omitting a required parameter or passing an unknown one
+/// results in an error. Responses for methods that the server doesn't
+/// implement can be scripted with [`FakeServer::respond`].
+///
+/// Updates added by [`FakeServer::push_update`] are returned by `getUpdates`,
+/// or sent to the webhook, if it's set.
+///
+/// The server is stopped when dropped.
+///
+/// ## Examples
+///
+/// ```
+/// use teloxide_core::{
+///     prelude::*,
+///     testing::{FakeServer, MessageBuilder},
+///     types::UpdateKind,
+/// };
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let server = FakeServer::start().await?;
+/// let bot = server.bot();
+///
+/// let message = MessageBuilder::text("Hi!").build();
+/// let chat_id = message.chat.id;
+/// server.push_update(UpdateKind::Message(message));
+///
+/// let updates = bot.get_updates().await?;
+/// assert_eq!(updates.len(), 1);
+///
+/// bot.send_message(chat_id, "Hello!").await?;
+///
+/// let messages = server.messages(chat_id);
+/// assert_eq!(messages[1].text(), Some("Hello!"));
+/// # Ok(()) }
+/// ```
+///
+/// [`Download`]: crate::net::Download
+pub struct FakeServer {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+struct Shared {
+    state: Mutex<State>,
+
+    /// Notified whenever updates are pushed or the webhook changes.
+    updates: Notify,
+}
+
+struct State {
+    me: Me,
+    last_update_id: u32,
+    last_message_id: i32,
+    last_file_id: u32,
+    /// Updates which were not yet confirmed by `getUpdates` or delivered to
+    /// the webhook.
+    pending_updates: VecDeque<Update>,
+    webhook: Option<Webhook>,
+    chats: HashMap<ChatId, Chat>,
+    messages: Vec<Message>,
+    files: HashMap<FileId, StoredFile>,
+    requests: Vec<RecordedRequest>,
+    responses: HashMap<&'static str, VecDeque<(StatusCode, Value)>>,
+}
+
+#[derive(Clone)]
+struct Webhook {
+    url: Url,
+    secret_token: Option<String>,
+}
+
+struct StoredFile {
+    file: File,
+    data: Bytes,
+}
+
+/// A file uploaded with `multipart/form-data`.
+struct Upload {
+    file_name: Option<String>,
+    data: Bytes,
+}
+
+/// A description of a Bot API method, see `schema.rs`.
+struct MethodSchema {
+    /// Name of the method, e.g. `sendMessage`.
+    name: &'static str,
+    /// Name of the payload type, e.g. `SendMessage`.
+    payload: &'static str,
+    returns_true: bool,
+    required: &'static [&'static str],
+    optional: &'static [&'static str],
+}
+
+/// An error returned from a Bot API method.
+#[derive(Debug)]
+struct MethodError {
+    status: StatusCode,
+    description: String,
+}
+
+type Params = Map<String, Value>;
+
+impl FakeServer {
+    /// Starts the server on a random port of `127.0.0.1`.
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let address = listener.local_addr()?;
+
+        let shared = Arc::new(Shared { state: Mutex::new(State::new()), updates: Notify::new() });
+        let tasks = [
+            tokio::spawn(serve(listener, Arc::clone(&shared))),
+            tokio::spawn(deliver_to_webhook(Arc::clone(&shared))),
+        ];
+
+        Ok(Self { address, shared, tasks })
+    }
+
+    /// Returns the API url of the server, see [`Bot::set_api_url`].
+    #[must_use]
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.address)).expect("socket address to be a valid host")
+    }
+
+    /// Returns the only bot token accepted by the server.
+    #[must_use]
+    pub fn token(&self) -> &'static str {
+        TOKEN
+    }
+
+    /// Returns a [`Bot`] which sends requests to this server.
+    pub fn bot(&self) -> Bot {
+        Bot::new(TOKEN).set_api_url(self.url())
+    }
+
+    /// Returns information about the bot, which is returned from `getMe`.
+    #[must_use]
+    pub fn me(&self) -> Me {
+        self.shared.state().me.clone()
+    }
+
+    /// Adds a chat, so that the bot can send messages into it.
+    ///
+    /// Chats of updates added with [`FakeServer::push_update`] are added
+    /// automatically.
+    pub fn add_chat(&self, chat: Chat) {
+        self.shared.state().chats.insert(chat.id, chat);
+    }
+
+    /// Adds a file, so that it can be received with `getFile` and downloaded.
+    pub fn add_file(&self, data: impl Into<Bytes>) -> File {
+        self.shared.state().store_file(data.into())
+    }
+
+    /// Adds an update which will be received by the bot.
+    ///
+    /// The update gets the next id, its chat and message are remembered by the
+    /// server.
+    pub fn push_update(&self, kind: UpdateKind) -> UpdateId {
+        let id = {
+            let mut state = self.shared.state();
+
+            state.last_update_id += 1;
+            let update = Update { id: UpdateId(state.last_update_id), kind };
+
+            if let Some(chat) = update.chat() {
+                state.chats.entry(chat.id).or_insert_with(|| chat.clone());
+            }
+            if let UpdateKind::Message(message) | UpdateKind::ChannelPost(message) = &update.kind {
+                state.last_message_id = state.last_message_id.max(message.id.0);
+                state.messages.push(message.clone());
+            }
+
+            let id = update.id;
+            state.pending_updates.push_back(update);
+            id
+        };
+
+        self.shared.updates.notify_waiters();
+        id
+    }
+
+    /// Returns updates which were not yet received by the bot.
+    #[must_use]
+    pub fn pending_updates(&self) -> Vec<Update> {
+        self.shared.state().pending_updates.iter().cloned().collect()
+    }
+
+    /// Returns all messages in the chat, both received and sent by the bot,
+    /// in the order they were sent.
+    #[must_use]
+    pub fn messages(&self, chat_id: ChatId) -> Vec<Message> {
+        let state = self.shared.state();
+        state.messages.iter().filter(|m| m.chat.id == chat_id).cloned().collect()
+    }
+
+    /// Returns the url of the webhook, if it's set.
+    #[must_use]
+    pub fn webhook(&self) -> Option<Url> {
+        self.shared.state().webhook.as_ref().map(|w| w.url.clone())
+    }
+
+    /// Returns all requests received by the server, in order.
+    ///
+    /// Note that all parameters of `multipart/form-data` requests, except for
+    /// JSON objects and arrays, are recorded as strings.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.shared.state().requests.clone()
+    }
+
+    /// Forgets all recorded requests.
+    pub fn clear_requests(&self) {
+        self.shared.state().requests.clear();
+    }
+
+    /// Scripts a successful response for the next request with payload `P`.
+    ///
+    /// Scripted responses are returned in the order they were added, after all
+    /// of them are used, the server handles requests as usual again.
+    pub fn respond<P>(&self, output: P::Output)
+    where
+        P: Payload,
+        P::Output: Serialize,
+    {
+        let body = json!({ "ok": true, "result": output });
+        self.respond_raw::<P>(StatusCode::OK.as_u16(), body)
+    }
+
+    /// Scripts a raw response for the next request with payload `P`.
+    ///
+    /// This can be used to emulate errors, for example:
+    ///
+    /// ```
+    /// # use teloxide_core::{payloads::SendMessage, testing::FakeServer};
+    /// # async fn f(server: FakeServer) {
+    /// server.respond_raw::<SendMessage>(
+    ///     429,
+    ///     serde_json::json!({
+    ///         "ok": false,
+    ///         "error_code": 429,
+    ///         "description": "Too Many Requests: retry after 1",
+    ///         "parameters": { "retry_after": 1 },
+    ///     }),
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// If `status` is not a valid HTTP status code.
+    pub fn respond_raw<P>(&self, status: u16, body: Value)
+    where
+        P: Payload,
+    {
+        let status = StatusCode::from_u16(status).expect("a valid HTTP status code");
+        self.shared.state().responses.entry(P::NAME).or_default().push_back((status, body));
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.tasks.iter().for_each(JoinHandle::abort);
+    }
+}
+
+impl std::fmt::Debug for FakeServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FakeServer").field("address", &self.address).finish_non_exhaustive()
+    }
+}
+
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                log::error!("FakeServer failed to accept a connection: {err}");
+                continue;
+            }
+        };
+
+        let shared = Arc::clone(&shared);
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle(Arc::clone(&shared), req));
+            let connection = http1::Builder::new().serve_connection(TokioIo::new(stream), service);
+
+            if let Err(err) = connection.await {
+                log::debug!("FakeServer connection failed: {err}");
+            }
+        });
+    }
+}
+
+async fn deliver_to_webhook(shared: Arc<Shared>) {
+    let client = reqwest::Client::new();
+
+    loop {
+        let notified = shared.updates.notified();
+        let next = {
+            let state = shared.state();
+            state.webhook.clone().zip(state.pending_updates.front().cloned())
+        };
+
+        let Some((webhook, update)) = next else {
+            notified.await;
+            continue;
+        };
+
+        let mut request = client.post(webhook.url).json(&update);
+        if let Some(secret_token) = webhook.secret_token {
+            request = request.header(SECRET_TOKEN_HEADER, secret_token);
+        }
+
+        match request.send().await.and_then(|r| r.error_for_status()) {
+            Ok(_) => shared.state().pending_updates.retain(|u| u.id != update.id),
+            Err(err) => {
+                log::debug!("FakeServer failed to send an update to the webhook: {err}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+async fn handle(
+    shared: Arc<Shared>,
+    req: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let path = req.uri().path().to_owned();
+    let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let bot = format!("bot{TOKEN}");
+
+    let response = match segments[..] {
+        ["file", token, file_path] if token == bot => match shared.state().download(file_path) {
+            Some(data) => file_response(data, req.headers().get(header::RANGE)),
+            None => MethodError::not_found("Not Found").into_response(),
+        },
+        [token, method] if token == bot => match shared.call(method, req).await {
+            Ok((status, body)) => json_response(status, &body),
+            Err(err) => err.into_response(),
+        },
+        ["file", _, _] | [_, _] => MethodError::unauthorized().into_response(),
+        _ => MethodError::not_found("Not Found").into_response(),
+    };
+
+    Ok(response)
+}
+
+impl Shared {
+    fn state(&self) -> MutexGuard<'_, State> {
+        // Panics in tests shouldn't make the server unusable
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn call(
+        &self,
+        method: &str,
+        req: Request<Incoming>,
+    ) -> Result<(StatusCode, Value), MethodError> {
+        let schema = schema::METHODS
+            .iter()
+            .find(|m| m.name.eq_ignore_ascii_case(method))
+            .ok_or_else(|| MethodError::not_found(format!("Not Found: method `{method}`")))?;
+
+        let (params, uploads) = read_params(schema, req).await?;
+        schema.validate(&params)?;
+
+        {
+            let mut state = self.state();
+            let payload = Value::Object(params.clone());
+            state.requests.push(RecordedRequest { method: schema.payload, payload });
+
+            if let Some(response) =
+                state.responses.get_mut(schema.payload).and_then(VecDeque::pop_front)
+            {
+                return Ok(response);
+            }
+        }
+
+        let result = match schema.name {
+            "getUpdates" => self.get_updates(&params).await,
+            _ => self.state().call(schema, &params, uploads),
+        };
+
+        // The webhook might have changed
+        self.updates.notify_waiters();
+
+        Ok((StatusCode::OK, json!({ "ok": true, "result": result? })))
+    }
+
+    async fn get_updates(&self, params: &Params) -> Result<Value, MethodError> {
+        let offset = int_param(params, "offset")?.unwrap_or(0);
+        let limit = int_param(params, "limit")?.unwrap_or(100).clamp(1, 100) as usize;
+        let timeout = int_param(params, "timeout")?.unwrap_or(0).max(0) as u64;
+        let deadline = Instant::now() + Duration::from_secs(timeout);
+
+        loop {
+            let notified = self.updates.notified();
+            {
+                let mut state = self.state();
+                if state.webhook.is_some() {
+                    return Err(MethodError::conflict(
+                        "can't use getUpdates method while webhook is active",
+                    ));
+                }
+
+                // Calling `getUpdates` with an `offset` confirms all previous updates
+                if offset > 0 {
+                    state.pending_updates.retain(|u| i64::from(u.id.0) >= offset);
+                }
+
+                if !state.pending_updates.is_empty() || Instant::now() >= deadline {
+                    let updates = state.pending_updates.iter().take(limit).collect::<Vec<_>>();
+                    return Ok(to_json(updates));
+                }
+            }
+
+            // If the timeout elapses, the next iteration returns an empty list
+            let _ = tokio::time::timeout_at(deadline, notified).await;
+        }
+    }
+}
+
+impl State {
+    fn new() -> Self {
+        let mut me = default_me();
+        me.user.id = BOT_ID;
+        me.user.first_name = "Fake".to_owned();
+        me.user.username = Some("fake_bot".to_owned());
+
+        Self {
+            me,
+            last_update_id: 0,
+            last_message_id: 0,
+            last_file_id: 0,
+            pending_updates: VecDeque::new(),
+            webhook: None,
+            chats: HashMap::new(),
+            messages: Vec::new(),
+            files: HashMap::new(),
+            requests: Vec::new(),
+            responses: HashMap::new(),
+        }
+    }
+
+    fn call(
+        &mut self,
+        schema: &MethodSchema,
+        params: &Params,
+        mut uploads: HashMap<String, Upload>,
+    ) -> Result<Value, MethodError> {
+        match schema.name {
+            "getMe" => Ok(to_json(&self.me)),
+            "setWebhook" => self.set_webhook(params),
+            "deleteWebhook" => {
+                self.webhook = None;
+                if bool_param(params, "drop_pending_updates") {
+                    self.pending_updates.clear();
+                }
+                Ok(Value::Bool(true))
+            }
+            "getWebhookInfo" => Ok(json!({
+                "url": self.webhook.as_ref().map_or("", |w| w.url.as_str()),
+                "has_custom_certificate": false,
+                "pending_update_count": self.pending_updates.len(),
+            })),
+            "sendMessage" => self.send_message(params),
+            "editMessageText" => self.edit_message_text(params),
+            "deleteMessage" => {
+                let chat_id = self.chat(params)?.id;
+                let message_id = MessageId(required_int_param(params, "message_id")? as i32);
+
+                match self.messages.iter().position(|m| m.chat.id == chat_id && m.id == message_id)
+                {
+                    Some(idx) => {
+                        self.messages.remove(idx);
+                        Ok(Value::Bool(true))
+                    }
+                    None => {
+                        Err(MethodError::bad_request("Bad Request: message to delete not found"))
+                    }
+                }
+            }
+            "sendDocument" => self.send_document(params, &mut uploads),
+            "getFile" => {
+                let file_id = FileId(required_str_param(params, "file_id")?);
+                match self.files.get(&file_id) {
+                    Some(stored) => Ok(to_json(&stored.file)),
+                    None => Err(MethodError::bad_request("Bad Request: invalid file id")),
+                }
+            }
+            _ if schema.returns_true => Ok(Value::Bool(true)),
+            name => Err(MethodError::bad_request(format!(
+                "Bad Request: method `{name}` is not supported by the fake server, use \
+                 `FakeServer::respond` to script a response"
+            ))),
+        }
+    }
+
+    fn set_webhook(&mut self, params: &Params) -> Result<Value, MethodError> {
+        let url = required_str_param(params, "url")?;
+
+        self.webhook = if url.is_empty() {
+            None
+        } else {
+            let url = Url::parse(&url).map_err(|_| {
+                MethodError::bad_request("Bad Request: invalid webhook URL specified")
+            })?;
+            let secret_token = str_param(params, "secret_token")?;
+            Some(Webhook { url, secret_token })
+        };
+
+        if bool_param(params, "drop_pending_updates") {
+            self.pending_updates.clear();
+        }
+
+        Ok(Value::Bool(true))
+    }
+
+    fn send_message(&mut self, params: &Params) -> Result<Value, MethodError> {
+        let chat = self.chat(params)?;
+        let text = required_str_param(params, "text")?;
+        if text.is_empty() {
+            return Err(MethodError::bad_request("Bad Request: message text is empty"));
+        }
+
+        let mut message = MessageBuilder::text(text);
+        if let Some(reply_markup) = inline_keyboard_param(params) {
+            message = message.reply_markup(reply_markup);
+        }
+
+        Ok(self.send(message, chat))
+    }
+
+    fn edit_message_text(&mut self, params: &Params) -> Result<Value, MethodError> {
+        let text = required_str_param(params, "text")?;
+
+        // Inline messages are not stored by the server
+        if params.contains_key("inline_message_id") {
+            return Ok(Value::Bool(true));
+        }
+
+        let chat_id = self.chat(params)?.id;
+        let message_id = MessageId(required_int_param(params, "message_id")? as i32);
+        let me = self.me.user.id;
+
+        let message = self
+            .messages
+            .iter_mut()
+            .find(|m| m.chat.id == chat_id && m.id == message_id)
+            .ok_or_else(|| MethodError::bad_request("Bad Request: message to edit not found"))?;
+
+        if message.from.as_ref().map(|u| u.id) != Some(me) {
+            return Err(MethodError::bad_request("Bad Request: message can't be edited"));
+        }
+
+        match &mut message.kind {
+            MessageKind::Common(MessageCommon {
+                media_kind: MediaKind::Text(media),
+                edit_date,
+                reply_markup,
+                ..
+            }) => {
+                media.text = text;
+                *edit_date = Some(now());
+                *reply_markup = inline_keyboard_param(params);
+            }
+            _ => {
+                return Err(MethodError::bad_request(
+                    "Bad Request: there is no text in the message to edit",
+                ))
+            }
+        }
+
+        Ok(to_json(&*message))
+    }
+
+    fn send_document(
+        &mut self,
+        params: &Params,
+        uploads: &mut HashMap<String, Upload>,
+    ) -> Result<Value, MethodError> {
+        let chat = self.chat(params)?;
+        let document = required_str_param(params, "document")?;
+
+        let (file, file_name) = match document.strip_prefix("attach://") {
+            Some(name) => {
+                let upload = uploads.remove(name).ok_or_else(|| {
+                    MethodError::bad_request(
+                        "Bad Request: wrong file identifier/HTTP URL specified",
+                    )
+                })?;
+                (self.store_file(upload.data).meta, upload.file_name)
+            }
+            None => match self.files.get(&FileId(document)) {
+                Some(stored) => (stored.file.meta.clone(), None),
+                None => {
+                    return Err(MethodError::bad_request(
+                        "Bad Request: wrong file identifier/HTTP URL specified",
+                    ))
+                }
+            },
+        };
+
+        let message = MessageBuilder::new(MediaKind::Document(MediaDocument {
+            document: Document { file, thumbnail: None, file_name, mime_type: None },
+            caption: str_param(params, "caption")?,
+            caption_entities: Vec::new(),
+            media_group_id: None,
+        }));
+
+        Ok(self.send(message, chat))
+    }
+
+    fn send(&mut self, message: MessageBuilder, chat: Chat) -> Value {
+        self.last_message_id += 1;
+
+        let message = message
+            .id(MessageId(self.last_message_id))
+            .from(self.me.user.clone())
+            .chat(chat)
+            .build();
+
+        let json = to_json(&message);
+        self.messages.push(message);
+        json
+    }
+
+    /// Finds the chat specified by the `chat_id` parameter.
+    fn chat(&self, params: &Params) -> Result<Chat, MethodError> {
+        let chat = match params.get("chat_id") {
+            Some(Value::String(s)) if s.starts_with('@') => {
+                self.chats.values().find(|c| c.username() == Some(&s[1..]))
+            }
+            Some(value) => as_int(value).and_then(|id| self.chats.get(&ChatId(id))),
+            None => None,
+        };
+
+        chat.cloned().ok_or_else(|| MethodError::bad_request("Bad Request: chat not found"))
+    }
+
+    fn store_file(&mut self, data: Bytes) -> File {
+        self.last_file_id += 1;
+        let id = self.last_file_id;
+
+        let file = File {
+            meta: FileMeta {
+                id: FileId(format!("fake-file-{id}")),
+                unique_id: FileUniqueId(format!("fake-unique-{id}")),
+                size: data.len().try_into().unwrap_or(u32::MAX),
+            },
+            path: format!("file_{id}"),
+        };
+
+        self.files.insert(file.meta.id.clone(), StoredFile { file: file.clone(), data });
+        file
+    }
+
+    fn download(&self, path: &str) -> Option<Bytes> {
+        self.files.values().find(|f| f.file.path == path).map(|f| f.data.clone())
+    }
+}
+
+impl MethodSchema {
+    fn has_param(&self, name: &str) -> bool {
+        self.required.contains(&name) || self.optional.contains(&name)
+    }
+
+    fn validate(&self, params: &Params) -> Result<(), MethodError> {
+        if let Some(missing) = self.required.iter().find(|p| !params.contains_key(**p)) {
+            return Err(MethodError::bad_request(format!(
+                "Bad Request: parameter `{missing}` is required"
+            )));
+        }
+
+        if let Some(unknown) = params.keys().find(|p| !self.has_param(p)) {
+            return Err(MethodError::bad_request(format!(
+                "Bad Request: unknown parameter `{unknown}`"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl MethodError {
+    fn bad_request(description: impl Into<String>) -> Self {
+        Self { status: StatusCode::BAD_REQUEST, description: description.into() }
+    }
+
+    fn unauthorized() -> Self {
+        Self { status: StatusCode::UNAUTHORIZED, description: "Unauthorized".to_owned() }
+    }
+
+    fn not_found(description: impl Into<String>) -> Self {
+        Self { status: StatusCode::NOT_FOUND, description: description.into() }
+    }
+
+    fn conflict(description: impl Into<String>) -> Self {
+        Self { status: StatusCode::CONFLICT, description: description.into() }
+    }
+
+    fn into_response(self) -> Response<Full<Bytes>> {
+        let body = json!({
+            "ok": false,
+            "error_code": self.status.as_u16(),
+            "description": self.description,
+        });
+
+        json_response(self.status, &body)
+    }
+}
+
+/// Reads parameters and uploaded files of a request.
+async fn read_params(
+    schema: &MethodSchema,
+    req: Request<Incoming>,
+) -> Result<(Params, HashMap<String, Upload>), MethodError> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+
+    let body = req
+        .into_body()
+        .collect()
+        .await
+        .map_err(|err| MethodError::bad_request(format!("Bad Request: can't read body: {err}")))?
+        .to_bytes();
+
+    let mut params = Params::new();
+    let mut uploads = HashMap::new();
+
+    if let Some(boundary) = content_type
+        .strip_prefix("multipart/form-data")
+        .and_then(|rest| rest.split("boundary=").nth(1))
+    {
+        let parts = parse_multipart(&body, boundary.trim_matches('"')).ok_or_else(|| {
+            MethodError::bad_request("Bad Request: invalid multipart/form-data body")
+        })?;
+
+        for part in parts {
+            // Files are attached by arbitrary names, which are not parameters
+            if part.file_name.is_some() || !schema.has_param(&part.name) {
+                uploads.insert(part.name, Upload { file_name: part.file_name, data: part.data });
+                continue;
+            }
+
+            let text = String::from_utf8_lossy(&part.data).into_owned();
+            let value = match text.as_bytes().first() {
+                Some(b'{' | b'[') => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+                _ => Value::String(text),
+            };
+            params.insert(part.name, value);
+        }
+    } else if !body.is_empty() {
+        match serde_json::from_slice(&body) {
+            Ok(Value::Object(map)) => params = map,
+            _ => return Err(MethodError::bad_request("Bad Request: body must be a JSON object")),
+        }
+    }
+
+    Ok((params, uploads))
+}
+
+struct Part {
+    name: String,
+    file_name: Option<String>,
+    data: Bytes,
+}
+
+/// Parses a `multipart/form-data` body, returns `None` if it's malformed.
+fn parse_multipart(body: &Bytes, boundary: &str) -> Option<Vec<Part>> {
+    let delimiter = format!("--{boundary}");
+    let separator = format!("\r\n{delimiter}");
+
+    let mut parts = Vec::new();
+    let mut rest = body.slice(find(body, delimiter.as_bytes())? + delimiter.len()..);
+
+    // The last delimiter is followed by `--`
+    while !rest.starts_with(b"--") {
+        if !rest.starts_with(b"\r\n") {
+            return None;
+        }
+        rest = rest.slice(2..);
+
+        let end = find(&rest, separator.as_bytes())?;
+        let part = rest.slice(..end);
+        rest = rest.slice(end + separator.len()..);
+
+        let headers_end = find(&part, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&part[..headers_end]).ok()?;
+        let data = part.slice(headers_end + 4..);
+
+        let disposition = headers.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("content-disposition").then_some(value)
+        })?;
+
+        let mut name = None;
+        let mut file_name = None;
+        for param in disposition.split(';').map(str::trim) {
+            if let Some(value) = param.strip_prefix("name=") {
+                name = Some(value.trim_matches('"').to_owned());
+            } else if let Some(value) = param.strip_prefix("filename=") {
+                file_name = Some(value.trim_matches('"').to_owned());
+            }
+        }
+
+        parts.push(Part { name: name?, file_name, data });
+    }
+
+    Some(parts)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn as_int(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn int_param(params: &Params, name: &str) -> Result<Option<i64>, MethodError> {
+    params
+        .get(name)
+        .map(|value| {
+            as_int(value).ok_or_else(|| {
+                MethodError::bad_request(format!(
+                    "Bad Request: parameter `{name}` must be an integer"
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn required_int_param(params: &Params, name: &str) -> Result<i64, MethodError> {
+    int_param(params, name)?.ok_or_else(|| {
+        MethodError::bad_request(format!("Bad Request: parameter `{name}` is required"))
+    })
+}
+
+fn str_param(params: &Params, name: &str) -> Result<Option<String>, MethodError> {
+    match params.get(name) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(Value::Number(n)) => Ok(Some(n.to_string())),
+        Some(_) => Err(MethodError::bad_request(format!(
+            "Bad Request: parameter `{name}` must be a string"
+        ))),
+    }
+}
+
+fn required_str_param(params: &Params, name: &str) -> Result<String, MethodError> {
+    str_param(params, name)?.ok_or_else(|| {
+        MethodError::bad_request(format!("Bad Request: parameter `{name}` is required"))
+    })
+}
+
+fn bool_param(params: &Params, name: &str) -> bool {
+    match params.get(name) {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::String(s)) => s == "true",
+        _ => false,
+    }
+}
+
+/// Returns the `reply_markup` parameter, if it's an inline keyboard.
+fn inline_keyboard_param(params: &Params) -> Option<InlineKeyboardMarkup> {
+    params.get("reply_markup").and_then(|v| serde_json::from_value(v.clone()).ok())
+}
+
+fn to_json(value: impl Serialize) -> Value {
+    serde_json::to_value(value).expect("types are always serializable to JSON")
+}
+
+/// Returns the file, or its part if a `bytes={start}-` range is requested.
+fn file_response(data: Bytes, range: Option<&header::HeaderValue>) -> Response<Full<Bytes>> {
+    let start: Option<usize> = range.and_then(|range| {
+        range.to_str().ok()?.strip_prefix("bytes=")?.strip_suffix('-')?.parse().ok()
+    });
+    let len = data.len();
+
+    let (status, body, content_range) = match start {
+        None => (StatusCode::OK, data, None),
+        Some(start) if start >= len => {
+            (StatusCode::RANGE_NOT_SATISFIABLE, Bytes::new(), Some(format!("bytes */{len}")))
+        }
+        Some(start) => (
+            StatusCode::PARTIAL_CONTENT,
+            data.slice(start..),
+            Some(format!("bytes {start}-{}/{len}", len - 1)),
+        ),
+    };
+
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    if let Some(content_range) = content_range.and_then(|r| header::HeaderValue::try_from(r).ok()) {
+        response.headers_mut().insert(header::CONTENT_RANGE, content_range);
+    }
+    response
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use cool_asserts::assert_matches;
+
+    use crate::{
+        errors::{ApiError, RequestError},
+        net::Download,
+        payloads::SendPhoto,
+        prelude::*,
+        testing::{ChatBuilder, FakeServer, MessageBuilder, UserBuilder},
+        types::{ChatId, InputFile, MediaKind, MessageKind, UpdateKind, UserId},
+    };
+
+    #[tokio::test]
+    async fn updates_and_messages() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+
+        assert_eq!(bot.get_me().await.unwrap().id, server.me().id);
+
+        let user = UserBuilder::new(UserId(42)).build();
+        let id =
+            server.push_update(UpdateKind::Message(MessageBuilder::text("hi").from(user).build()));
+
+        let updates = bot.get_updates().await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].id, id);
+
+        let sent = bot.send_message(ChatId(42), "hello").await.unwrap();
+        let edited = bot.edit_message_text(ChatId(42), sent.id, "hello!").await.unwrap();
+        assert_eq!(edited.text(), Some("hello!"));
+        assert!(edited.edit_date().is_some());
+
+        let texts = server
+            .messages(ChatId(42))
+            .iter()
+            .map(|m| m.text().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["hi", "hello!"]);
+
+        // Confirm the update
+        assert_eq!(bot.get_updates().offset(id.0 as i32 + 1).await.unwrap().len(), 0);
+        assert_eq!(server.pending_updates().len(), 0);
+
+        let methods = server.requests().iter().map(|r| r.method).collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            ["GetMe", "GetUpdates", "SendMessage", "EditMessageText", "GetUpdates"]
+        );
+    }
+
+    #[tokio::test]
+    async fn long_polling() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+
+        let updates = tokio::spawn(async move { bot.get_updates().timeout(10).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        server.push_update(UpdateKind::Message(MessageBuilder::text("hi").build()));
+
+        let updates = tokio::time::timeout(Duration::from_secs(5), updates).await.unwrap();
+        assert_eq!(updates.unwrap().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn files() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        server.add_chat(ChatBuilder::group(ChatId(-1), "Files").build());
+
+        let document = InputFile::memory("hello").file_name("hello.txt");
+        let message = bot.send_document(ChatId(-1), document).caption("greeting").await.unwrap();
+        let document = match message.kind {
+            MessageKind::Common(common) => match common.media_kind {
+                MediaKind::Document(media) => media.document,
+                _ => panic!("expected a document"),
+            },
+            _ => panic!("expected a common message"),
+        };
+        assert_eq!(document.file_name.as_deref(), Some("hello.txt"));
+
+        let file = bot.get_file(document.file.id).await.unwrap();
+        assert_eq!(file.size, 5);
+
+        let mut data = Vec::new();
+        bot.download_file(&file.path, &mut data).await.unwrap();
+        assert_eq!(data, b"hello");
+
+        let file = server.add_file("preexisting");
+        let mut data = Vec::new();
+        bot.download_file(&file.path, &mut data).await.unwrap();
+        assert_eq!(data, b"preexisting");
+    }
+
+    #[tokio::test]
+    async fn errors() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+
+        assert_matches!(
+            bot.send_message(ChatId(1), "hi").await,
+            Err(RequestError::Api(ApiError::ChatNotFound))
+        );
+
+        assert_matches!(
+            Bot::new("1:wrong").set_api_url(server.url()).get_me().await,
+            Err(RequestError::Api(ApiError::InvalidToken))
+        );
+
+        assert_matches!(
+            bot.send_photo(ChatId(1), InputFile::file_id("photo".into())).await,
+            Err(RequestError::Api(ApiError::Unknown(_)))
+        );
+
+        let response = reqwest::Client::new()
+            .post(format!("{}bot{}/sendMessage", server.url(), server.token()))
+            .json(&serde_json::json!({ "chat_id": 1, "txt": "hi" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+        let body = response.json::<serde_json::Value>().await.unwrap();
+        assert_eq!(body["description"], "Bad Request: parameter `text` is required");
+
+        server.respond_raw::<SendPhoto>(
+            400,
+            serde_json::json!({ "ok": false, "error_code": 400, "description": "Bad Request: chat not found" }),
+        );
+        assert_matches!(
+            bot.send_photo(ChatId(1), InputFile::file_id("photo".into())).await,
+            Err(RequestError::Api(ApiError::ChatNotFound))
+        );
+    }
+}
+
+#[test]
+fn codegen_fake_server_schema() {
+    use crate::codegen::{
+        add_preamble, ensure_file_contents, project_root, reformat,
+        schema::{self, Type},
+    };
+
+    let path = project_root().join("src/testing/fake_server/schema.rs");
+    let schema = schema::get();
+
+    let methods = schema
+        .methods
+        .iter()
+        .map(|m| {
+            let (optional, required): (Vec<_>, Vec<_>) =
+                m.params.iter().partition(|p| matches!(p.ty, Type::Option(_)));
+            let names = |params: Vec<&schema::Param>| {
+                params.iter().map(|p| format!("{:?}, ", p.name)).collect::<String>()
+            };
+
+            format!(
+                "    MethodSchema {{ name: {:?}, payload: {:?}, returns_true: {}, required: \
+                 &[{}], optional: &[{}] }},\n",
+                m.names.0,
+                m.names.1,
+                matches!(m.return_ty, Type::True),
+                names(required),
+                names(optional),
+            )
+        })
+        .collect::<String>();
+
+    let contents = format!(
+        "use super::MethodSchema;\n\npub(super) const METHODS: &[MethodSchema] = &[\n{methods}];\n"
+    );
+
+    ensure_file_contents(&path, &reformat(add_preamble("codegen_fake_server_schema", contents)));
+}
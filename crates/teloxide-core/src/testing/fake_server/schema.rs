@@ -0,0 +1,1604 @@
+//! Generated by `codegen_fake_server_schema`, do not edit by hand.
+
+use super::MethodSchema;
+
+pub(super) const METHODS: &[MethodSchema] = &[
+    MethodSchema {
+        name: "getUpdates",
+        payload: "GetUpdates",
+        returns_true: false,
+        required: &[],
+        optional: &["offset", "limit", "timeout", "allowed_updates"],
+    },
+    MethodSchema {
+        name: "setWebhook",
+        payload: "SetWebhook",
+        returns_true: true,
+        required: &["url"],
+        optional: &[
+            "certificate",
+            "ip_address",
+            "max_connections",
+            "allowed_updates",
+            "drop_pending_updates",
+            "secret_token",
+        ],
+    },
+    MethodSchema {
+        name: "deleteWebhook",
+        payload: "DeleteWebhook",
+        returns_true: true,
+        required: &[],
+        optional: &["drop_pending_updates"],
+    },
+    MethodSchema {
+        name: "getWebhookInfo",
+        payload: "GetWebhookInfo",
+        returns_true: false,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getMe",
+        payload: "GetMe",
+        returns_true: false,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "logOut",
+        payload: "LogOut",
+        returns_true: true,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "close",
+        payload: "Close",
+        returns_true: true,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "sendMessage",
+        payload: "SendMessage",
+        returns_true: false,
+        required: &["chat_id", "text"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "parse_mode",
+            "entities",
+            "link_preview_options",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "forwardMessage",
+        payload: "ForwardMessage",
+        returns_true: false,
+        required: &["chat_id", "from_chat_id", "message_id"],
+        optional: &[
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "video_start_timestamp",
+            "disable_notification",
+            "protect_content",
+            "suggested_post_parameters",
+        ],
+    },
+    MethodSchema {
+        name: "forwardMessages",
+        payload: "ForwardMessages",
+        returns_true: false,
+        required: &["chat_id", "from_chat_id", "message_ids"],
+        optional: &[
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "disable_notification",
+            "protect_content",
+        ],
+    },
+    MethodSchema {
+        name: "copyMessage",
+        payload: "CopyMessage",
+        returns_true: false,
+        required: &["chat_id", "from_chat_id", "message_id"],
+        optional: &[
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "video_start_timestamp",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "copyMessages",
+        payload: "CopyMessages",
+        returns_true: false,
+        required: &["chat_id", "from_chat_id", "message_ids"],
+        optional: &[
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "disable_notification",
+            "protect_content",
+            "remove_caption",
+        ],
+    },
+    MethodSchema {
+        name: "sendPhoto",
+        payload: "SendPhoto",
+        returns_true: false,
+        required: &["chat_id", "photo"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "has_spoiler",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendAudio",
+        payload: "SendAudio",
+        returns_true: false,
+        required: &["chat_id", "audio"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "duration",
+            "performer",
+            "title",
+            "thumbnail",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendDocument",
+        payload: "SendDocument",
+        returns_true: false,
+        required: &["chat_id", "document"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "thumbnail",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "disable_content_type_detection",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendVideo",
+        payload: "SendVideo",
+        returns_true: false,
+        required: &["chat_id", "video"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "duration",
+            "width",
+            "height",
+            "thumbnail",
+            "cover",
+            "start_timestamp",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "has_spoiler",
+            "supports_streaming",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendAnimation",
+        payload: "SendAnimation",
+        returns_true: false,
+        required: &["chat_id", "animation"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "duration",
+            "width",
+            "height",
+            "thumbnail",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "has_spoiler",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendVoice",
+        payload: "SendVoice",
+        returns_true: false,
+        required: &["chat_id", "voice"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "duration",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendVideoNote",
+        payload: "SendVideoNote",
+        returns_true: false,
+        required: &["chat_id", "video_note"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "duration",
+            "length",
+            "thumbnail",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendPaidMedia",
+        payload: "SendPaidMedia",
+        returns_true: false,
+        required: &["chat_id", "star_count", "media"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "payload",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendMediaGroup",
+        payload: "SendMediaGroup",
+        returns_true: false,
+        required: &["chat_id", "media"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "reply_parameters",
+        ],
+    },
+    MethodSchema {
+        name: "sendLocation",
+        payload: "SendLocation",
+        returns_true: false,
+        required: &["chat_id", "latitude", "longitude"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "horizontal_accuracy",
+            "live_period",
+            "heading",
+            "proximity_alert_radius",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageLiveLocation",
+        payload: "EditMessageLiveLocation",
+        returns_true: false,
+        required: &["chat_id", "message_id", "latitude", "longitude"],
+        optional: &[
+            "business_connection_id",
+            "live_period",
+            "horizontal_accuracy",
+            "heading",
+            "proximity_alert_radius",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageLiveLocationInline",
+        payload: "EditMessageLiveLocationInline",
+        returns_true: true,
+        required: &["inline_message_id", "latitude", "longitude"],
+        optional: &[
+            "business_connection_id",
+            "horizontal_accuracy",
+            "heading",
+            "proximity_alert_radius",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "stopMessageLiveLocation",
+        payload: "StopMessageLiveLocation",
+        returns_true: false,
+        required: &["chat_id", "message_id"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "stopMessageLiveLocationInline",
+        payload: "StopMessageLiveLocationInline",
+        returns_true: true,
+        required: &["inline_message_id"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "editMessageChecklist",
+        payload: "EditMessageChecklist",
+        returns_true: false,
+        required: &["business_connection_id", "chat_id", "message_id", "checklist"],
+        optional: &["reply_markup"],
+    },
+    MethodSchema {
+        name: "sendVenue",
+        payload: "SendVenue",
+        returns_true: false,
+        required: &["chat_id", "latitude", "longitude", "title", "address"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "foursquare_id",
+            "foursquare_type",
+            "google_place_id",
+            "google_place_type",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendContact",
+        payload: "SendContact",
+        returns_true: false,
+        required: &["chat_id", "phone_number", "first_name"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "last_name",
+            "vcard",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendPoll",
+        payload: "SendPoll",
+        returns_true: false,
+        required: &["chat_id", "question", "options"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "question_parse_mode",
+            "question_entities",
+            "is_anonymous",
+            "type_",
+            "allows_multiple_answers",
+            "correct_option_id",
+            "explanation",
+            "explanation_parse_mode",
+            "explanation_entities",
+            "open_period",
+            "close_date",
+            "is_closed",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendChecklist",
+        payload: "SendChecklist",
+        returns_true: false,
+        required: &["business_connection_id", "chat_id", "checklist"],
+        optional: &[
+            "disable_notification",
+            "protect_content",
+            "message_effect_id",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendDice",
+        payload: "SendDice",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "emoji",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "sendChatAction",
+        payload: "SendChatAction",
+        returns_true: true,
+        required: &["chat_id", "action"],
+        optional: &["business_connection_id", "message_thread_id"],
+    },
+    MethodSchema {
+        name: "setMessageReaction",
+        payload: "SetMessageReaction",
+        returns_true: true,
+        required: &["chat_id", "message_id"],
+        optional: &["reaction", "is_big"],
+    },
+    MethodSchema {
+        name: "getUserProfilePhotos",
+        payload: "GetUserProfilePhotos",
+        returns_true: false,
+        required: &["user_id"],
+        optional: &["offset", "limit"],
+    },
+    MethodSchema {
+        name: "setUserEmojiStatus",
+        payload: "SetUserEmojiStatus",
+        returns_true: true,
+        required: &["user_id"],
+        optional: &["emoji_status_custom_emoji_id", "emoji_status_expiration_date"],
+    },
+    MethodSchema {
+        name: "getFile",
+        payload: "GetFile",
+        returns_true: false,
+        required: &["file_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "banChatMember",
+        payload: "BanChatMember",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &["until_date", "revoke_messages"],
+    },
+    MethodSchema {
+        name: "kickChatMember",
+        payload: "KickChatMember",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &["until_date", "revoke_messages"],
+    },
+    MethodSchema {
+        name: "unbanChatMember",
+        payload: "UnbanChatMember",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &["only_if_banned"],
+    },
+    MethodSchema {
+        name: "restrictChatMember",
+        payload: "RestrictChatMember",
+        returns_true: true,
+        required: &["chat_id", "user_id", "permissions"],
+        optional: &["use_independent_chat_permissions", "until_date"],
+    },
+    MethodSchema {
+        name: "promoteChatMember",
+        payload: "PromoteChatMember",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &[
+            "is_anonymous",
+            "can_manage_chat",
+            "can_post_messages",
+            "can_edit_messages",
+            "can_delete_messages",
+            "can_post_stories",
+            "can_edit_stories",
+            "can_delete_stories",
+            "can_manage_video_chats",
+            "can_restrict_members",
+            "can_promote_members",
+            "can_change_info",
+            "can_invite_users",
+            "can_pin_messages",
+            "can_manage_topics",
+            "can_manage_direct_messages",
+        ],
+    },
+    MethodSchema {
+        name: "setChatAdministratorCustomTitle",
+        payload: "SetChatAdministratorCustomTitle",
+        returns_true: true,
+        required: &["chat_id", "user_id", "custom_title"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "banChatSenderChat",
+        payload: "BanChatSenderChat",
+        returns_true: true,
+        required: &["chat_id", "sender_chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "unbanChatSenderChat",
+        payload: "UnbanChatSenderChat",
+        returns_true: true,
+        required: &["chat_id", "sender_chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setChatPermissions",
+        payload: "SetChatPermissions",
+        returns_true: true,
+        required: &["chat_id", "permissions"],
+        optional: &["use_independent_chat_permissions"],
+    },
+    MethodSchema {
+        name: "exportChatInviteLink",
+        payload: "ExportChatInviteLink",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "createChatInviteLink",
+        payload: "CreateChatInviteLink",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &["name", "expire_date", "member_limit", "creates_join_request"],
+    },
+    MethodSchema {
+        name: "editChatInviteLink",
+        payload: "EditChatInviteLink",
+        returns_true: false,
+        required: &["chat_id", "invite_link"],
+        optional: &["name", "expire_date", "member_limit", "creates_join_request"],
+    },
+    MethodSchema {
+        name: "createChatSubscriptionInviteLink",
+        payload: "CreateChatSubscriptionInviteLink",
+        returns_true: false,
+        required: &["chat_id", "subscription_period", "subscription_price"],
+        optional: &["name"],
+    },
+    MethodSchema {
+        name: "editChatSubscriptionInviteLink",
+        payload: "EditChatSubscriptionInviteLink",
+        returns_true: false,
+        required: &["chat_id", "invite_link"],
+        optional: &["name"],
+    },
+    MethodSchema {
+        name: "revokeChatInviteLink",
+        payload: "RevokeChatInviteLink",
+        returns_true: false,
+        required: &["chat_id", "invite_link"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "approveChatJoinRequest",
+        payload: "ApproveChatJoinRequest",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "declineChatJoinRequest",
+        payload: "DeclineChatJoinRequest",
+        returns_true: true,
+        required: &["chat_id", "user_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setChatPhoto",
+        payload: "SetChatPhoto",
+        returns_true: true,
+        required: &["chat_id", "photo"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteChatPhoto",
+        payload: "DeleteChatPhoto",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setChatTitle",
+        payload: "SetChatTitle",
+        returns_true: true,
+        required: &["chat_id", "title"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setChatDescription",
+        payload: "SetChatDescription",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &["description"],
+    },
+    MethodSchema {
+        name: "pinChatMessage",
+        payload: "PinChatMessage",
+        returns_true: true,
+        required: &["chat_id", "message_id"],
+        optional: &["disable_notification", "business_connection_id"],
+    },
+    MethodSchema {
+        name: "unpinChatMessage",
+        payload: "UnpinChatMessage",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &["message_id", "business_connection_id"],
+    },
+    MethodSchema {
+        name: "unpinAllChatMessages",
+        payload: "UnpinAllChatMessages",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "leaveChat",
+        payload: "LeaveChat",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getChat",
+        payload: "GetChat",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getChatAdministrators",
+        payload: "GetChatAdministrators",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getChatMemberCount",
+        payload: "GetChatMemberCount",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getChatMembersCount",
+        payload: "GetChatMembersCount",
+        returns_true: false,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getChatMember",
+        payload: "GetChatMember",
+        returns_true: false,
+        required: &["chat_id", "user_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setChatStickerSet",
+        payload: "SetChatStickerSet",
+        returns_true: true,
+        required: &["chat_id", "sticker_set_name"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteChatStickerSet",
+        payload: "DeleteChatStickerSet",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getForumTopicIconStickers",
+        payload: "GetForumTopicIconStickers",
+        returns_true: false,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "createForumTopic",
+        payload: "CreateForumTopic",
+        returns_true: false,
+        required: &["chat_id", "name"],
+        optional: &["icon_color", "icon_custom_emoji_id"],
+    },
+    MethodSchema {
+        name: "editForumTopic",
+        payload: "EditForumTopic",
+        returns_true: true,
+        required: &["chat_id", "message_thread_id"],
+        optional: &["name", "icon_custom_emoji_id"],
+    },
+    MethodSchema {
+        name: "closeForumTopic",
+        payload: "CloseForumTopic",
+        returns_true: true,
+        required: &["chat_id", "message_thread_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "reopenForumTopic",
+        payload: "ReopenForumTopic",
+        returns_true: true,
+        required: &["chat_id", "message_thread_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteForumTopic",
+        payload: "DeleteForumTopic",
+        returns_true: true,
+        required: &["chat_id", "message_thread_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "unpinAllForumTopicMessages",
+        payload: "UnpinAllForumTopicMessages",
+        returns_true: true,
+        required: &["chat_id", "message_thread_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "editGeneralForumTopic",
+        payload: "EditGeneralForumTopic",
+        returns_true: true,
+        required: &["chat_id", "name"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "closeGeneralForumTopic",
+        payload: "CloseGeneralForumTopic",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "reopenGeneralForumTopic",
+        payload: "ReopenGeneralForumTopic",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "hideGeneralForumTopic",
+        payload: "HideGeneralForumTopic",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "unhideGeneralForumTopic",
+        payload: "UnhideGeneralForumTopic",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "unpinAllGeneralForumTopicMessages",
+        payload: "UnpinAllGeneralForumTopicMessages",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "answerCallbackQuery",
+        payload: "AnswerCallbackQuery",
+        returns_true: true,
+        required: &["callback_query_id"],
+        optional: &["text", "show_alert", "url", "cache_time"],
+    },
+    MethodSchema {
+        name: "getUserChatBoosts",
+        payload: "GetUserChatBoosts",
+        returns_true: false,
+        required: &["chat_id", "user_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setMyCommands",
+        payload: "SetMyCommands",
+        returns_true: true,
+        required: &["commands"],
+        optional: &["scope", "language_code"],
+    },
+    MethodSchema {
+        name: "getBusinessConnection",
+        payload: "GetBusinessConnection",
+        returns_true: false,
+        required: &["business_connection_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getMyCommands",
+        payload: "GetMyCommands",
+        returns_true: false,
+        required: &[],
+        optional: &["scope", "language_code"],
+    },
+    MethodSchema {
+        name: "setMyName",
+        payload: "SetMyName",
+        returns_true: true,
+        required: &[],
+        optional: &["name", "language_code"],
+    },
+    MethodSchema {
+        name: "getMyName",
+        payload: "GetMyName",
+        returns_true: false,
+        required: &[],
+        optional: &["language_code"],
+    },
+    MethodSchema {
+        name: "setMyDescription",
+        payload: "SetMyDescription",
+        returns_true: true,
+        required: &[],
+        optional: &["description", "language_code"],
+    },
+    MethodSchema {
+        name: "getMyDescription",
+        payload: "GetMyDescription",
+        returns_true: false,
+        required: &[],
+        optional: &["language_code"],
+    },
+    MethodSchema {
+        name: "setMyShortDescription",
+        payload: "SetMyShortDescription",
+        returns_true: true,
+        required: &[],
+        optional: &["short_description", "language_code"],
+    },
+    MethodSchema {
+        name: "getMyShortDescription",
+        payload: "GetMyShortDescription",
+        returns_true: false,
+        required: &[],
+        optional: &["language_code"],
+    },
+    MethodSchema {
+        name: "setChatMenuButton",
+        payload: "SetChatMenuButton",
+        returns_true: true,
+        required: &[],
+        optional: &["chat_id", "menu_button"],
+    },
+    MethodSchema {
+        name: "getChatMenuButton",
+        payload: "GetChatMenuButton",
+        returns_true: false,
+        required: &[],
+        optional: &["chat_id"],
+    },
+    MethodSchema {
+        name: "setMyDefaultAdministratorRights",
+        payload: "SetMyDefaultAdministratorRights",
+        returns_true: true,
+        required: &[],
+        optional: &["rights", "for_channels"],
+    },
+    MethodSchema {
+        name: "getMyDefaultAdministratorRights",
+        payload: "GetMyDefaultAdministratorRights",
+        returns_true: false,
+        required: &[],
+        optional: &["for_channels"],
+    },
+    MethodSchema {
+        name: "deleteMyCommands",
+        payload: "DeleteMyCommands",
+        returns_true: true,
+        required: &[],
+        optional: &["scope", "language_code"],
+    },
+    MethodSchema {
+        name: "answerInlineQuery",
+        payload: "AnswerInlineQuery",
+        returns_true: true,
+        required: &["inline_query_id", "results"],
+        optional: &["cache_time", "is_personal", "next_offset", "button"],
+    },
+    MethodSchema {
+        name: "answerWebAppQuery",
+        payload: "AnswerWebAppQuery",
+        returns_true: false,
+        required: &["web_app_query_id", "result"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "savePreparedInlineMessage",
+        payload: "SavePreparedInlineMessage",
+        returns_true: false,
+        required: &["user_id", "result"],
+        optional: &[
+            "allow_user_chats",
+            "allow_bot_chats",
+            "allow_group_chats",
+            "allow_channel_chats",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageText",
+        payload: "EditMessageText",
+        returns_true: false,
+        required: &["chat_id", "message_id", "text"],
+        optional: &[
+            "business_connection_id",
+            "parse_mode",
+            "entities",
+            "link_preview_options",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageTextInline",
+        payload: "EditMessageTextInline",
+        returns_true: true,
+        required: &["inline_message_id", "text"],
+        optional: &[
+            "business_connection_id",
+            "parse_mode",
+            "entities",
+            "disable_web_page_preview",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageCaption",
+        payload: "EditMessageCaption",
+        returns_true: false,
+        required: &["chat_id", "message_id"],
+        optional: &[
+            "business_connection_id",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "show_caption_above_media",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageCaptionInline",
+        payload: "EditMessageCaptionInline",
+        returns_true: true,
+        required: &["inline_message_id"],
+        optional: &[
+            "business_connection_id",
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "editMessageMedia",
+        payload: "EditMessageMedia",
+        returns_true: false,
+        required: &["chat_id", "message_id", "media"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "editMessageMediaInline",
+        payload: "EditMessageMediaInline",
+        returns_true: true,
+        required: &["inline_message_id", "media"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "editMessageReplyMarkup",
+        payload: "EditMessageReplyMarkup",
+        returns_true: false,
+        required: &["chat_id", "message_id"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "editMessageReplyMarkupInline",
+        payload: "EditMessageReplyMarkupInline",
+        returns_true: true,
+        required: &["inline_message_id"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "stopPoll",
+        payload: "StopPoll",
+        returns_true: false,
+        required: &["chat_id", "message_id"],
+        optional: &["business_connection_id", "reply_markup"],
+    },
+    MethodSchema {
+        name: "approveSuggestedPost",
+        payload: "ApproveSuggestedPost",
+        returns_true: true,
+        required: &["chat_id", "message_id"],
+        optional: &["send_date"],
+    },
+    MethodSchema {
+        name: "declineSuggestedPost",
+        payload: "DeclineSuggestedPost",
+        returns_true: true,
+        required: &["chat_id", "message_id"],
+        optional: &["comment"],
+    },
+    MethodSchema {
+        name: "deleteMessage",
+        payload: "DeleteMessage",
+        returns_true: true,
+        required: &["chat_id", "message_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteMessages",
+        payload: "DeleteMessages",
+        returns_true: true,
+        required: &["chat_id", "message_ids"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "sendSticker",
+        payload: "SendSticker",
+        returns_true: false,
+        required: &["chat_id", "sticker"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "emoji",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "getStickerSet",
+        payload: "GetStickerSet",
+        returns_true: false,
+        required: &["name"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getCustomEmojiStickers",
+        payload: "GetCustomEmojiStickers",
+        returns_true: false,
+        required: &["custom_emoji_ids"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "uploadStickerFile",
+        payload: "UploadStickerFile",
+        returns_true: false,
+        required: &["user_id", "sticker", "sticker_format"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "createNewStickerSet",
+        payload: "CreateNewStickerSet",
+        returns_true: true,
+        required: &["user_id", "name", "title", "stickers"],
+        optional: &["sticker_type", "needs_repainting"],
+    },
+    MethodSchema {
+        name: "addStickerToSet",
+        payload: "AddStickerToSet",
+        returns_true: true,
+        required: &["user_id", "name", "sticker"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setStickerPositionInSet",
+        payload: "SetStickerPositionInSet",
+        returns_true: true,
+        required: &["sticker", "position"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteStickerFromSet",
+        payload: "DeleteStickerFromSet",
+        returns_true: true,
+        required: &["sticker"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "replaceStickerInSet",
+        payload: "ReplaceStickerInSet",
+        returns_true: true,
+        required: &["user_id", "name", "old_sticker", "sticker"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setStickerSetThumbnail",
+        payload: "SetStickerSetThumbnail",
+        returns_true: true,
+        required: &["name", "user_id", "format"],
+        optional: &["thumbnail"],
+    },
+    MethodSchema {
+        name: "setCustomEmojiStickerSetThumbnail",
+        payload: "SetCustomEmojiStickerSetThumbnail",
+        returns_true: true,
+        required: &["name"],
+        optional: &["custom_emoji_id"],
+    },
+    MethodSchema {
+        name: "setStickerSetTitle",
+        payload: "SetStickerSetTitle",
+        returns_true: true,
+        required: &["name", "title"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteStickerSet",
+        payload: "DeleteStickerSet",
+        returns_true: true,
+        required: &["name"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setStickerEmojiList",
+        payload: "SetStickerEmojiList",
+        returns_true: true,
+        required: &["sticker", "emoji_list"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setStickerKeywords",
+        payload: "SetStickerKeywords",
+        returns_true: true,
+        required: &["sticker"],
+        optional: &["keywords"],
+    },
+    MethodSchema {
+        name: "setStickerMaskPosition",
+        payload: "SetStickerMaskPosition",
+        returns_true: true,
+        required: &["sticker"],
+        optional: &["mask_position"],
+    },
+    MethodSchema {
+        name: "getAvailableGifts",
+        payload: "GetAvailableGifts",
+        returns_true: false,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "sendGift",
+        payload: "SendGift",
+        returns_true: true,
+        required: &["user_id", "gift_id"],
+        optional: &["pay_for_upgrade", "text", "text_parse_mode", "text_entities"],
+    },
+    MethodSchema {
+        name: "sendGiftChat",
+        payload: "SendGiftChat",
+        returns_true: true,
+        required: &["chat_id", "gift_id"],
+        optional: &["pay_for_upgrade", "text", "text_parse_mode", "text_entities"],
+    },
+    MethodSchema {
+        name: "giftPremiumSubscription",
+        payload: "GiftPremiumSubscription",
+        returns_true: true,
+        required: &["user_id", "month_count", "star_count"],
+        optional: &["text", "text_parse_mode", "text_entities"],
+    },
+    MethodSchema {
+        name: "verifyUser",
+        payload: "VerifyUser",
+        returns_true: true,
+        required: &["user_id"],
+        optional: &["custom_description"],
+    },
+    MethodSchema {
+        name: "verifyChat",
+        payload: "VerifyChat",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &["custom_description"],
+    },
+    MethodSchema {
+        name: "removeUserVerification",
+        payload: "RemoveUserVerification",
+        returns_true: true,
+        required: &["user_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "removeChatVerification",
+        payload: "RemoveChatVerification",
+        returns_true: true,
+        required: &["chat_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "readBusinessMessage",
+        payload: "ReadBusinessMessage",
+        returns_true: true,
+        required: &["business_connection_id", "chat_id", "message_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "deleteBusinessMessages",
+        payload: "DeleteBusinessMessages",
+        returns_true: true,
+        required: &["business_connection_id", "message_ids"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setBusinessAccountName",
+        payload: "SetBusinessAccountName",
+        returns_true: true,
+        required: &["business_connection_id", "first_name"],
+        optional: &["last_name"],
+    },
+    MethodSchema {
+        name: "setBusinessAccountUsername",
+        payload: "SetBusinessAccountUsername",
+        returns_true: true,
+        required: &["business_connection_id"],
+        optional: &["username"],
+    },
+    MethodSchema {
+        name: "setBusinessAccountBio",
+        payload: "SetBusinessAccountBio",
+        returns_true: true,
+        required: &["business_connection_id"],
+        optional: &["bio"],
+    },
+    MethodSchema {
+        name: "setBusinessAccountProfilePhoto",
+        payload: "SetBusinessAccountProfilePhoto",
+        returns_true: true,
+        required: &["business_connection_id", "photo"],
+        optional: &["is_public"],
+    },
+    MethodSchema {
+        name: "removeBusinessAccountProfilePhoto",
+        payload: "RemoveBusinessAccountProfilePhoto",
+        returns_true: true,
+        required: &["business_connection_id"],
+        optional: &["is_public"],
+    },
+    MethodSchema {
+        name: "setBusinessAccountGiftSettings",
+        payload: "SetBusinessAccountGiftSettings",
+        returns_true: true,
+        required: &["business_connection_id", "show_gift_button", "accepted_gift_types"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getBusinessAccountStarBalance",
+        payload: "GetBusinessAccountStarBalance",
+        returns_true: false,
+        required: &["business_connection_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "transferBusinessAccountStars",
+        payload: "TransferBusinessAccountStars",
+        returns_true: true,
+        required: &["business_connection_id", "star_count"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getBusinessAccountGifts",
+        payload: "GetBusinessAccountGifts",
+        returns_true: false,
+        required: &["business_connection_id"],
+        optional: &[
+            "exclude_unsaved",
+            "exclude_saved",
+            "exclude_unlimited",
+            "exclude_limited",
+            "exclude_unique",
+            "sort_by_price",
+            "offset",
+            "limit",
+        ],
+    },
+    MethodSchema {
+        name: "convertGiftToStars",
+        payload: "ConvertGiftToStars",
+        returns_true: true,
+        required: &["business_connection_id", "owned_gift_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "upgradeGift",
+        payload: "UpgradeGift",
+        returns_true: true,
+        required: &["business_connection_id", "owned_gift_id"],
+        optional: &["keep_original_details", "star_count"],
+    },
+    MethodSchema {
+        name: "transferGift",
+        payload: "TransferGift",
+        returns_true: true,
+        required: &["business_connection_id", "owned_gift_id", "new_owner_chat_id"],
+        optional: &["star_count"],
+    },
+    MethodSchema {
+        name: "postStory",
+        payload: "PostStory",
+        returns_true: false,
+        required: &["business_connection_id", "content", "active_period"],
+        optional: &[
+            "caption",
+            "parse_mode",
+            "caption_entities",
+            "areas",
+            "post_to_chat_page",
+            "protect_content",
+        ],
+    },
+    MethodSchema {
+        name: "editStory",
+        payload: "EditStory",
+        returns_true: false,
+        required: &["business_connection_id", "story_id", "content"],
+        optional: &["caption", "parse_mode", "caption_entities", "areas"],
+    },
+    MethodSchema {
+        name: "deleteStory",
+        payload: "DeleteStory",
+        returns_true: true,
+        required: &["business_connection_id", "story_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "sendInvoice",
+        payload: "SendInvoice",
+        returns_true: false,
+        required: &["chat_id", "title", "description", "payload", "currency", "prices"],
+        optional: &[
+            "message_thread_id",
+            "direct_messages_topic_id",
+            "provider_token",
+            "max_tip_amount",
+            "suggested_tip_amounts",
+            "start_parameter",
+            "provider_data",
+            "photo_url",
+            "photo_size",
+            "photo_width",
+            "photo_height",
+            "need_name",
+            "need_phone_number",
+            "need_email",
+            "need_shipping_address",
+            "send_phone_number_to_provider",
+            "send_email_to_provider",
+            "is_flexible",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "suggested_post_parameters",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "createInvoiceLink",
+        payload: "CreateInvoiceLink",
+        returns_true: false,
+        required: &["title", "description", "payload", "currency", "prices"],
+        optional: &[
+            "provider_token",
+            "business_connection_id",
+            "subscription_period",
+            "max_tip_amount",
+            "suggested_tip_amounts",
+            "provider_data",
+            "photo_url",
+            "photo_size",
+            "photo_width",
+            "photo_height",
+            "need_name",
+            "need_phone_number",
+            "need_email",
+            "need_shipping_address",
+            "send_phone_number_to_provider",
+            "send_email_to_provider",
+            "is_flexible",
+        ],
+    },
+    MethodSchema {
+        name: "answerShippingQuery",
+        payload: "AnswerShippingQuery",
+        returns_true: true,
+        required: &["shipping_query_id", "ok"],
+        optional: &["shipping_options", "error_message"],
+    },
+    MethodSchema {
+        name: "answerPreCheckoutQuery",
+        payload: "AnswerPreCheckoutQuery",
+        returns_true: true,
+        required: &["pre_checkout_query_id", "ok"],
+        optional: &["error_message"],
+    },
+    MethodSchema {
+        name: "getMyStarBalance",
+        payload: "GetMyStarBalance",
+        returns_true: false,
+        required: &[],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "getStarTransactions",
+        payload: "GetStarTransactions",
+        returns_true: false,
+        required: &[],
+        optional: &["offset", "limit"],
+    },
+    MethodSchema {
+        name: "refundStarPayment",
+        payload: "RefundStarPayment",
+        returns_true: true,
+        required: &["user_id", "telegram_payment_charge_id"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "editUserStarSubscription",
+        payload: "EditUserStarSubscription",
+        returns_true: true,
+        required: &["user_id", "telegram_payment_charge_id", "is_canceled"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "setPassportDataErrors",
+        payload: "SetPassportDataErrors",
+        returns_true: true,
+        required: &["user_id", "errors"],
+        optional: &[],
+    },
+    MethodSchema {
+        name: "sendGame",
+        payload: "SendGame",
+        returns_true: false,
+        required: &["chat_id", "game_short_name"],
+        optional: &[
+            "business_connection_id",
+            "message_thread_id",
+            "disable_notification",
+            "protect_content",
+            "allow_paid_broadcast",
+            "message_effect_id",
+            "reply_parameters",
+            "reply_markup",
+        ],
+    },
+    MethodSchema {
+        name: "setGameScore",
+        payload: "SetGameScore",
+        returns_true: false,
+        required: &["user_id", "score", "chat_id", "message_id"],
+        optional: &["force", "disable_edit_message"],
+    },
+    MethodSchema {
+        name: "setGameScoreInline",
+        payload: "SetGameScoreInline",
+        returns_true: true,
+        required: &["user_id", "score", "inline_message_id"],
+        optional: &["force", "disable_edit_message"],
+    },
+    MethodSchema {
+        name: "getGameHighScores",
+        payload: "GetGameHighScores",
+        returns_true: false,
+        required: &["user_id", "target"],
+        optional: &[],
+    },
+];
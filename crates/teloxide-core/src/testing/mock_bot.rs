@@ -0,0 +1,599 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::IntoFuture,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::{
+    errors::ErrorResponse,
+    net::NoTransport,
+    requests::{HasPayload, Output, Payload, Request, Requester, ResponseResult},
+    types::*,
+    ApiError, Bot, RequestError,
+};
+
+/// An in-process [`Requester`] which never talks to Telegram.
+///
+/// `MockBot` records every request sent through it and answers with scripted
+/// (see [`respond`]) or automatically generated responses. This allows to
+/// test handlers without network access and then assert on the requests they
+/// have made.
+///
+/// Automatically generated responses are:
+///  - `True` for methods returning [`True`]
+///  - A text message echoing `text` (or `caption`) of the request and sent to
+///    its `chat_id` for methods returning [`Message`] or [`MessageId`]
+///  - [`MockBot::me`] for [`GetMe`]
+///  - An empty list for methods returning `Vec<_>` (including [`GetUpdates`])
+///
+/// Other methods fail with [`ApiError::Unknown`] unless a response is
+/// scripted.
+///
+/// Clones of a `MockBot` share recorded requests and scripted responses.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use teloxide_core::{payloads::SendMessage, prelude::*, testing::MockBot, types::ChatId};
+///
+/// let bot = MockBot::new();
+///
+/// let message = bot.send_message(ChatId(42), "Hi!").await?;
+/// assert_eq!(message.text(), Some("Hi!"));
+///
+/// let sent = bot.requests_of::<SendMessage>();
+/// assert_eq!(sent.len(), 1);
+/// assert_eq!(sent[0].text, "Hi!");
+/// # Ok::<_, teloxide_core::RequestError>(()) };
+/// ```
+///
+/// [`respond`]: MockBot::respond
+/// [`GetMe`]: crate::payloads::GetMe
+/// [`GetUpdates`]: crate::payloads::GetUpdates
+#[derive(Clone)]
+pub struct MockBot {
+    // `Bot` is only used to construct requests, it never sends anything.
+    bot: Bot,
+    state: Arc<Mutex<State>>,
+}
+
+/// A request recorded by [`MockBot`] or [`FakeServer`].
+///
+/// [`FakeServer`]: super::FakeServer
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedRequest {
+    /// Name of the Telegram method, see [`Payload::NAME`].
+    pub method: &'static str,
+
+    /// The payload of the request, serialized to JSON.
+    pub payload: Value,
+}
+
+struct State {
+    me: Me,
+    requests: Vec<(RecordedRequest, Box<dyn Any + Send>)>,
+    responses: HashMap<TypeId, VecDeque<Box<dyn Any + Send>>>,
+    last_message_id: i32,
+}
+
+impl MockBot {
+    /// Creates a new `MockBot` with the default bot user (`@mock_bot`).
+    pub fn new() -> Self {
+        Self::with_me(default_me())
+    }
+
+    /// Creates a new `MockBot` which returns `me` from [`GetMe`].
+    ///
+    /// [`GetMe`]: crate::payloads::GetMe
+    pub fn with_me(me: Me) -> Self {
+        let state =
+            State { me, requests: Vec::new(), responses: HashMap::new(), last_message_id: 0 };
+
+        Self { bot: Bot::with_transport("0:mock", NoTransport), state: Arc::new(Mutex::new(state)) }
+    }
+
+    /// Returns the bot user returned from [`GetMe`].
+    ///
+    /// [`GetMe`]: crate::payloads::GetMe
+    #[must_use]
+    pub fn me(&self) -> Me {
+        self.state().me.clone()
+    }
+
+    /// Scripts a successful response for the next request with payload `P`.
+    ///
+    /// Scripted responses are returned in the order they were added, after all
+    /// of them are used, responses are generated automatically again.
+    pub fn respond<P>(&self, output: P::Output)
+    where
+        P: Payload + 'static,
+        P::Output: Send + 'static,
+    {
+        self.respond_with::<P>(Ok(output))
+    }
+
+    /// Scripts an error for the next request with payload `P`.
+    ///
+    /// See also [`MockBot::respond`].
+    pub fn respond_error<P>(&self, error: RequestError)
+    where
+        P: Payload + 'static,
+        P::Output: Send + 'static,
+    {
+        self.respond_with::<P>(Err(error))
+    }
+
+    /// Returns all the requests sent through this bot so far, in order.
+    #[must_use]
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state().requests.iter().map(|(request, _)| request.clone()).collect()
+    }
+
+    /// Returns payloads of all the `P` requests sent through this bot so far,
+    /// in order.
+    #[must_use]
+    pub fn requests_of<P>(&self) -> Vec<P>
+    where
+        P: Payload + Clone + 'static,
+    {
+        self.state()
+            .requests
+            .iter()
+            .filter_map(|(_, payload)| payload.downcast_ref::<P>())
+            .cloned()
+            .collect()
+    }
+
+    /// Forgets all the recorded requests.
+    pub fn clear_requests(&self) {
+        self.state().requests.clear();
+    }
+
+    fn respond_with<P>(&self, response: ResponseResult<P::Output>)
+    where
+        P: Payload + 'static,
+        P::Output: Send + 'static,
+    {
+        self.state().responses.entry(TypeId::of::<P>()).or_default().push_back(Box::new(response))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        // A panic while holding the lock can't leave the state inconsistent
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn execute<P>(&self, payload: P) -> ResponseResult<P::Output>
+    where
+        P: Payload + Serialize + Send + 'static,
+        P::Output: DeserializeOwned + Send + 'static,
+    {
+        let json =
+            serde_json::to_value(&payload).expect("serialization of request to be infallible");
+
+        let mut state = self.state();
+        let recorded = RecordedRequest { method: P::NAME, payload: json };
+        state.requests.push((recorded.clone(), Box::new(payload)));
+
+        let scripted = state.responses.get_mut(&TypeId::of::<P>()).and_then(VecDeque::pop_front);
+        if let Some(response) = scripted {
+            return *response
+                .downcast::<ResponseResult<P::Output>>()
+                .expect("responses are stored by the payload type");
+        }
+
+        state.generate_response(&recorded)
+    }
+}
+
+impl Default for MockBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MockBot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        f.debug_struct("MockBot")
+            .field("me", &state.me.user.username)
+            .field("requests", &state.requests.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl State {
+    fn generate_response<T>(&mut self, request: &RecordedRequest) -> ResponseResult<T>
+    where
+        T: DeserializeOwned,
+    {
+        let message = self.generate_message(&request.payload);
+        let me = serde_json::to_value(&self.me).expect("serialization of `Me` to be infallible");
+
+        // Outputs of different methods can't be confused with each other, so we
+        // just try all the candidates
+        [Value::Bool(true), message, me, Value::Array(Vec::new())]
+            .into_iter()
+            .find_map(|candidate| serde_json::from_value(candidate).ok())
+            .ok_or_else(|| {
+                RequestError::from(ApiError::Unknown(ErrorResponse::new(format!(
+                    "MockBot can't generate a response for `{}`, use `MockBot::respond` to script \
+                     one",
+                    request.method
+                ))))
+            })
+    }
+
+    fn generate_message(&mut self, payload: &Value) -> Value {
+        self.last_message_id += 1;
+
+        let chat_id = payload.get("chat_id").and_then(Value::as_i64).unwrap_or(0);
+        let chat = if ChatId(chat_id).is_user() {
+            json!({ "id": chat_id, "type": "private" })
+        } else {
+            json!({ "id": chat_id, "type": "supergroup", "title": "" })
+        };
+        let text = payload.get("text").or_else(|| payload.get("caption")).cloned();
+        let date = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+
+        json!({
+            "message_id": self.last_message_id,
+            "date": date,
+            "chat": chat,
+            "from": self.me.user,
+            "text": text.unwrap_or_else(|| Value::String(String::new())),
+        })
+    }
+}
+
+pub(super) fn default_me() -> Me {
+    Me {
+        user: User {
+            id: UserId(1),
+            is_bot: true,
+            first_name: "Mock".to_owned(),
+            last_name: None,
+            username: Some("mock_bot".to_owned()),
+            language_code: None,
+            is_premium: false,
+            added_to_attachment_menu: false,
+        },
+        can_join_groups: true,
+        can_read_all_group_messages: false,
+        supports_inline_queries: false,
+        can_connect_to_business: false,
+        has_main_web_app: false,
+    }
+}
+
+macro_rules! f {
+    ($m:ident $this:ident ($($arg:ident : $T:ty),*)) => {
+        MockRequest { bot: $this.clone(), inner: $this.bot.$m($($arg),*) }
+    };
+}
+
+macro_rules! fty {
+    ($T:ident) => {
+        MockRequest<<Bot as Requester>::$T>
+    };
+}
+
+impl Requester for MockBot {
+    type Err = RequestError;
+
+    requester_forward! {
+        get_me,
+        log_out,
+        close,
+        get_updates,
+        set_webhook,
+        delete_webhook,
+        get_webhook_info,
+        forward_message,
+        forward_messages,
+        copy_message,
+        copy_messages,
+        send_message,
+        send_photo,
+        send_audio,
+        send_document,
+        send_video,
+        send_animation,
+        send_voice,
+        send_video_note,
+        send_paid_media,
+        send_media_group,
+        send_location,
+        edit_message_live_location,
+        edit_message_live_location_inline,
+        stop_message_live_location,
+        stop_message_live_location_inline,
+        edit_message_checklist,
+        send_venue,
+        send_contact,
+        send_poll,
+        send_checklist,
+        send_dice,
+        send_chat_action,
+        set_message_reaction,
+        get_user_profile_photos,
+        set_user_emoji_status,
+        get_file,
+        kick_chat_member,
+        ban_chat_member,
+        unban_chat_member,
+        restrict_chat_member,
+        promote_chat_member,
+        set_chat_administrator_custom_title,
+        ban_chat_sender_chat,
+        unban_chat_sender_chat,
+        set_chat_permissions,
+        export_chat_invite_link,
+        create_chat_invite_link,
+        edit_chat_invite_link,
+        create_chat_subscription_invite_link,
+        edit_chat_subscription_invite_link,
+        revoke_chat_invite_link,
+        set_chat_photo,
+        delete_chat_photo,
+        set_chat_title,
+        set_chat_description,
+        pin_chat_message,
+        unpin_chat_message,
+        unpin_all_chat_messages,
+        leave_chat,
+        get_chat,
+        get_chat_administrators,
+        get_chat_members_count,
+        get_chat_member_count,
+        get_chat_member,
+        set_chat_sticker_set,
+        delete_chat_sticker_set,
+        get_forum_topic_icon_stickers,
+        create_forum_topic,
+        edit_forum_topic,
+        close_forum_topic,
+        reopen_forum_topic,
+        delete_forum_topic,
+        unpin_all_forum_topic_messages,
+        edit_general_forum_topic,
+        close_general_forum_topic,
+        reopen_general_forum_topic,
+        hide_general_forum_topic,
+        unhide_general_forum_topic,
+        unpin_all_general_forum_topic_messages,
+        answer_callback_query,
+        get_user_chat_boosts,
+        set_my_commands,
+        get_business_connection,
+        get_my_commands,
+        set_my_name,
+        get_my_name,
+        set_my_description,
+        get_my_description,
+        set_my_short_description,
+        get_my_short_description,
+        set_chat_menu_button,
+        get_chat_menu_button,
+        set_my_default_administrator_rights,
+        get_my_default_administrator_rights,
+        delete_my_commands,
+        answer_inline_query,
+        answer_web_app_query,
+        save_prepared_inline_message,
+        edit_message_text,
+        edit_message_text_inline,
+        edit_message_caption,
+        edit_message_caption_inline,
+        edit_message_media,
+        edit_message_media_inline,
+        edit_message_reply_markup,
+        edit_message_reply_markup_inline,
+        stop_poll,
+        approve_suggested_post,
+        decline_suggested_post,
+        delete_message,
+        delete_messages,
+        send_sticker,
+        get_sticker_set,
+        get_custom_emoji_stickers,
+        upload_sticker_file,
+        create_new_sticker_set,
+        add_sticker_to_set,
+        set_sticker_position_in_set,
+        delete_sticker_from_set,
+        replace_sticker_in_set,
+        set_sticker_set_thumbnail,
+        set_custom_emoji_sticker_set_thumbnail,
+        set_sticker_set_title,
+        delete_sticker_set,
+        set_sticker_emoji_list,
+        set_sticker_keywords,
+        set_sticker_mask_position,
+        get_available_gifts,
+        send_gift,
+        send_gift_chat,
+        gift_premium_subscription,
+        verify_user,
+        verify_chat,
+        remove_user_verification,
+        remove_chat_verification,
+        read_business_message,
+        delete_business_messages,
+        set_business_account_name,
+        set_business_account_username,
+        set_business_account_bio,
+        set_business_account_profile_photo,
+        remove_business_account_profile_photo,
+        set_business_account_gift_settings,
+        get_business_account_star_balance,
+        transfer_business_account_stars,
+        get_business_account_gifts,
+        convert_gift_to_stars,
+        upgrade_gift,
+        transfer_gift,
+        post_story,
+        edit_story,
+        delete_story,
+        send_invoice,
+        create_invoice_link,
+        answer_shipping_query,
+        answer_pre_checkout_query,
+        get_my_star_balance,
+        get_star_transactions,
+        refund_star_payment,
+        edit_user_star_subscription,
+        set_passport_data_errors,
+        send_game,
+        set_game_score,
+        set_game_score_inline,
+        get_game_high_scores,
+        approve_chat_join_request,
+        decline_chat_join_request
+        => f, fty
+    }
+}
+
+/// Request returned by [`MockBot`] methods.
+#[must_use = "Requests are lazy and do nothing unless sent"]
+#[derive(Clone)]
+pub struct MockRequest<R> {
+    bot: MockBot,
+    inner: R,
+}
+
+impl<R> HasPayload for MockRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+
+    fn payload_ref(&self) -> &Self::Payload {
+        self.inner.payload_ref()
+    }
+}
+
+impl<R> Request for MockRequest<R>
+where
+    R: HasPayload,
+    R::Payload: Serialize + Clone + Send + 'static,
+    Output<R>: DeserializeOwned + Send + 'static,
+{
+    type Err = RequestError;
+
+    type Send = BoxFuture<'static, ResponseResult<Output<R>>>;
+
+    type SendRef = BoxFuture<'static, ResponseResult<Output<R>>>;
+
+    fn send(self) -> Self::Send {
+        self.send_ref()
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let bot = self.bot.clone();
+        let payload = self.inner.payload_ref().clone();
+
+        async move { bot.execute(payload) }.boxed()
+    }
+}
+
+impl<R> IntoFuture for MockRequest<R>
+where
+    R: HasPayload,
+    R::Payload: Serialize + Clone + Send + 'static,
+    Output<R>: DeserializeOwned + Send + 'static,
+{
+    type Output = ResponseResult<Output<R>>;
+    type IntoFuture = <Self as Request>::Send;
+
+    fn into_future(self) -> Self::IntoFuture {
+        self.send()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cool_asserts::assert_matches;
+
+    use crate::{
+        payloads::{GetChat, SendMessage, SendMessageSetters},
+        prelude::*,
+        testing::{MockBot, RecordedRequest},
+        types::{ChatId, MessageId, True},
+        ApiError, RequestError,
+    };
+
+    #[tokio::test]
+    async fn records_requests() {
+        let bot = MockBot::new();
+
+        bot.send_message(ChatId(1), "first").await.unwrap();
+        bot.delete_message(ChatId(1), MessageId(1)).await.unwrap();
+        bot.send_message(ChatId(2), "second").disable_notification(true).await.unwrap();
+
+        let methods = bot.requests().into_iter().map(|r| r.method).collect::<Vec<_>>();
+        assert_eq!(methods, ["SendMessage", "DeleteMessage", "SendMessage"]);
+
+        let sent = bot.requests_of::<SendMessage>();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].text, "first");
+        assert_eq!(sent[1].chat_id, ChatId(2).into());
+        assert_eq!(sent[1].disable_notification, Some(true));
+
+        bot.clear_requests();
+        assert_eq!(bot.requests(), Vec::<RecordedRequest>::new());
+    }
+
+    #[tokio::test]
+    async fn generated_responses() {
+        let bot = MockBot::new();
+
+        let message = bot.send_message(ChatId(42), "text").await.unwrap();
+        assert_eq!(message.chat.id, ChatId(42));
+        assert_eq!(message.text(), Some("text"));
+        assert_eq!(message.from.unwrap().id, bot.me().user.id);
+
+        let next = bot.copy_message(ChatId(1), ChatId(42), message.id).await.unwrap();
+        assert_ne!(next, message.id);
+
+        assert_matches!(bot.delete_message(ChatId(42), message.id).await, Ok(True));
+        assert_eq!(bot.get_me().await.unwrap(), bot.me());
+        assert_eq!(bot.get_updates().await.unwrap(), []);
+        assert_matches!(
+            bot.get_chat(ChatId(42)).await,
+            Err(RequestError::Api(ApiError::Unknown(_)))
+        );
+    }
+
+    #[tokio::test]
+    async fn scripted_responses() {
+        let bot = MockBot::new();
+
+        bot.respond_error::<SendMessage>(RequestError::from(ApiError::BotBlocked));
+        bot.respond_error::<GetChat>(RequestError::from(ApiError::ChatNotFound));
+
+        let clone = bot.clone();
+        assert_matches!(
+            clone.send_message(ChatId(1), "").await,
+            Err(RequestError::Api(ApiError::BotBlocked))
+        );
+        assert_matches!(bot.send_message(ChatId(1), "").await, Ok(_));
+        assert_matches!(
+            bot.get_chat(ChatId(1)).await,
+            Err(RequestError::Api(ApiError::ChatNotFound))
+        );
+        assert_eq!(clone.requests().len(), 3);
+    }
+}
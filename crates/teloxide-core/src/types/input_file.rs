@@ -1,7 +1,7 @@
 use bytes::{Bytes, BytesMut};
 use futures::{
-    future::{ready, Either},
-    stream,
+    future::{ready, BoxFuture, Either, Shared},
+    stream, FutureExt, Stream, TryStreamExt,
 };
 use once_cell::sync::OnceCell;
 use rc_box::ArcBox;
@@ -9,17 +9,20 @@ use reqwest::{multipart::Part, Body};
 use serde::Serialize;
 use takecell::TakeCell;
 use tokio::{
-    io::{AsyncRead, AsyncReadExt, ReadBuf},
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf},
     sync::watch,
 };
 use tokio_util::codec::{Decoder, FramedRead};
 
 use std::{
-    borrow::Cow, convert::Infallible, fmt, future::Future, io, iter, mem, path::PathBuf, pin::Pin,
-    sync::Arc, task,
+    borrow::Cow, convert::Infallible, error::Error, fmt, future::Future, io, iter, mem,
+    path::PathBuf, pin::Pin, sync::Arc, task,
 };
 
-use crate::types::{self, InputSticker};
+use crate::{
+    serde_multipart,
+    types::{self, InputSticker},
+};
 
 /// This object represents the contents of a file to be uploaded.
 ///
@@ -30,12 +33,43 @@ use crate::types::{self, InputSticker};
 pub struct InputFile {
     id: OnceCell<Arc<str>>,
     file_name: Option<Cow<'static, str>>,
+    progress: OnceCell<Arc<watch::Sender<UploadProgress>>>,
     inner: InnerFile,
 }
 
+/// Progress of an upload of an [`InputFile`], see [`InputFile::progress`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UploadProgress {
+    /// Number of bytes streamed to the HTTP client so far.
+    pub sent: u64,
+
+    /// Size of the file, if it's known before the upload.
+    pub total: Option<u64>,
+}
+
+impl UploadProgress {
+    /// Returns the uploaded fraction of the file, between `0.0` and `1.0`, if
+    /// the size of the file is known.
+    #[must_use]
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some(self.sent as f64 / total as f64),
+            None => None,
+        }
+    }
+}
+
+// Required to not trigger `clippy::type-complexity` lint
+type OpenRead = Arc<
+    dyn Fn() -> BoxFuture<'static, io::Result<Box<dyn AsyncRead + Send + Unpin>>> + Send + Sync,
+>;
+
 #[derive(Clone)]
 enum InnerFile {
     Read(Read),
+    Reopen(OpenRead),
     File(PathBuf),
     Bytes(bytes::Bytes),
     Url(url::Url),
@@ -85,6 +119,11 @@ impl InputFile {
     }
 
     /// Creates an `InputFile` from a file path.
+    ///
+    /// If the bot is set up for a [local Bot API server], the file isn't
+    /// uploaded, instead the server is given a `file://` URI to read it from.
+    ///
+    /// [local Bot API server]: crate::Bot::set_local_mode
     pub fn file(path: impl Into<PathBuf>) -> Self {
         Self::new(File(path.into()))
     }
@@ -100,18 +139,114 @@ impl InputFile {
         self
     }
 
+    /// Returns a receiver of the upload progress of this file.
+    ///
+    /// The progress is updated every time a chunk of the file is streamed to
+    /// the HTTP client, which is a close approximation of the bytes sent. If
+    /// the file isn't uploaded (e.g. it's a [file id](InputFile::file_id),
+    /// an [url](InputFile::url) or a file sent to a [local Bot API
+    /// server]), the progress is never updated.
+    ///
+    /// All receivers and clones of this `InputFile` created after this call
+    /// share the progress. If the file is sent multiple times, the progress is
+    /// reset to zero on every upload.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use teloxide_core::{prelude::*, types::InputFile};
+    ///
+    /// # async fn run(bot: Bot, chat_id: ChatId) -> Result<(), teloxide_core::RequestError> {
+    /// let video = InputFile::file("video.mp4");
+    /// let mut progress = video.progress();
+    ///
+    /// tokio::spawn(async move {
+    ///     // Wake up at least every 30 seconds to detect stalled uploads
+    ///     while let Ok(Ok(())) =
+    ///         tokio::time::timeout(Duration::from_secs(30), progress.changed()).await
+    ///     {
+    ///         if let Some(fraction) = progress.borrow().fraction() {
+    ///             println!("uploading {:.0}%", fraction * 100.0);
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// bot.send_video(chat_id, video).await?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// [local Bot API server]: crate::Bot::set_local_mode
+    pub fn progress(&self) -> watch::Receiver<UploadProgress> {
+        self.progress
+            .get_or_init(|| Arc::new(watch::channel(UploadProgress::default()).0))
+            .subscribe()
+    }
+
     /// Creates an `InputFile` from a in-memory bytes.
     ///
     /// Note: in some cases (e.g. sending the same `InputFile` multiple times)
-    /// this may read the whole `impl AsyncRead` into memory.
+    /// this may read the whole `impl AsyncRead` into memory. If the file may
+    /// need to be sent more than once (e.g. by [`Request::send_ref`] or by
+    /// adaptors which retry requests), consider [`InputFile::read_with`] or
+    /// [`InputFile::read_spooled`] instead.
+    ///
+    /// [`Request::send_ref`]: crate::requests::Request::send_ref
     pub fn read(it: impl AsyncRead + Send + Unpin + 'static) -> Self {
         Self::new(Read(Read::new(Arc::new(TakeCell::new(it)))))
     }
 
-    /// Shorthand for `Self { file_name: None, inner, id: default() }`
+    /// Creates an `InputFile` from a function which opens a reader of the
+    /// file.
+    ///
+    /// The function is called on every upload, so the file can be safely sent
+    /// multiple times (e.g. by [`Request::send_ref`] or by adaptors which retry
+    /// requests) without being buffered in memory.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use teloxide_core::types::InputFile;
+    ///
+    /// let file = InputFile::read_with(|| tokio::fs::File::open("video.mp4")).file_name("video.mp4");
+    /// ```
+    ///
+    /// [`Request::send_ref`]: crate::requests::Request::send_ref
+    pub fn read_with<F, Fut, R>(open: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = io::Result<R>> + Send + 'static,
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        let open: OpenRead =
+            Arc::new(move || open().map(|res| res.map(|read| Box::new(read) as Box<_>)).boxed());
+
+        Self::new(Reopen(open))
+    }
+
+    /// Creates an `InputFile` from a reader which is read to the end before
+    /// the first upload.
+    ///
+    /// Up to `memory_limit` bytes are kept in memory, larger files are
+    /// spooled to a temporary file, which is removed when the last clone of
+    /// this `InputFile` is dropped. Every upload then reads the spooled
+    /// copy, so, unlike [`InputFile::read`], the file can be safely sent
+    /// multiple times (e.g. by [`Request::send_ref`] or by adaptors which
+    /// retry requests).
+    ///
+    /// [`Request::send_ref`]: crate::requests::Request::send_ref
+    pub fn read_spooled(it: impl AsyncRead + Send + Unpin + 'static, memory_limit: usize) -> Self {
+        let spool = Arc::new(Spool { spooled: spool(Box::new(it), memory_limit).boxed().shared() });
+
+        Self::read_with(move || Arc::clone(&spool).open())
+    }
+
+    /// Shorthand for `Self { file_name: None, inner, id: default(), progress:
+    /// default() }`
     /// (private because `InnerFile` is private implementation detail)
     fn new(inner: InnerFile) -> Self {
-        Self { file_name: None, inner, id: OnceCell::new() }
+        Self { file_name: None, inner, id: OnceCell::new(), progress: OnceCell::new() }
     }
 
     /// Returns id of this file.
@@ -128,6 +263,17 @@ impl InputFile {
         !matches!(self.inner, Url(_) | FileId(_))
     }
 
+    /// Returns a `file://` URL of this file if it's a file from the filesystem.
+    ///
+    /// This is used to send files to a local Bot API server, which can read
+    /// them directly.
+    pub(crate) fn local_url(&self) -> Option<url::Url> {
+        match &self.inner {
+            File(path) => url::Url::from_file_path(std::path::absolute(path).ok()?).ok(),
+            _ => None,
+        }
+    }
+
     /// Takes this file out.
     ///
     /// **Note**: this replaces `self` with a dummy value, this function should
@@ -174,6 +320,7 @@ impl fmt::Debug for InnerFile {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Read(_) => f.debug_struct("Read").finish_non_exhaustive(),
+            Reopen(_) => f.debug_struct("Reopen").finish_non_exhaustive(),
             File(path) => f.debug_struct("File").field("path", path).finish(),
             Bytes(bytes) if f.alternate() => f.debug_tuple("Memory").field(bytes).finish(),
             Bytes(_) => f.debug_struct("Memory").finish_non_exhaustive(),
@@ -188,6 +335,12 @@ impl Serialize for InputFile {
     where
         S: serde::Serializer,
     {
+        if serde_multipart::local_mode() {
+            if let Some(url) = self.local_url() {
+                return url.as_str().serialize(serializer);
+            }
+        }
+
         self.attach_or_value().serialize(serializer)
     }
 }
@@ -195,42 +348,152 @@ impl Serialize for InputFile {
 // internal api
 
 impl InputFile {
-    pub(crate) fn into_part(mut self) -> Option<impl Future<Output = Part>> {
+    /// Converts this file into a multipart part, returning it together with
+    /// its file name and the length of its contents, if it's known.
+    pub(crate) fn into_part(
+        mut self,
+    ) -> Option<impl Future<Output = (Part, Cow<'static, str>, Option<u64>)>> {
         let filename = self.take_or_guess_filename();
+        let progress = self.progress.take();
+        let name = filename.clone();
 
-        match self.inner {
+        let part = match self.inner {
             // Url and FileId are serialized just as strings, they don't need additional parts
-            Url(_) | FileId(_) => None,
+            Url(_) | FileId(_) => return None,
 
             File(path_to_file) => {
                 let fut = async {
-                    let body = match tokio::fs::File::open(path_to_file).await {
+                    let (body, len) = match tokio::fs::File::open(path_to_file).await {
                         Ok(file) => {
+                            let len = file.metadata().await.ok().map(|m| m.len());
                             let file = FramedRead::new(file, BytesDecoder);
 
-                            Body::wrap_stream(file)
+                            (tracked_body(file, progress, len), len)
                         }
                         Err(err) => {
                             // explicit type needed for `Bytes: From<?T>` in `wrap_stream`
                             let err = Err::<Bytes, _>(err);
-                            Body::wrap_stream(stream::iter([err]))
+                            (Body::wrap_stream(stream::iter([err])), None)
                         }
                     };
 
-                    Part::stream(body).file_name(filename)
+                    (Part::stream(body).file_name(filename), len)
                 };
 
-                Some(Either::Left(fut))
+                Either::Left(fut)
             }
             Bytes(data) => {
-                let stream = Part::stream(data).file_name(filename);
-                Some(Either::Right(Either::Left(ready(stream))))
+                let len = data.len() as u64;
+                let body = match progress {
+                    // Split the data, so that the progress is reported while it's sent
+                    Some(progress) => {
+                        const CHUNK: usize = 64 * 1024;
+
+                        let chunks = (0..data.len()).step_by(CHUNK).map(move |i| {
+                            Ok::<_, Infallible>(data.slice(i..data.len().min(i + CHUNK)))
+                        });
+
+                        tracked_body(stream::iter(chunks), Some(progress), Some(len))
+                    }
+                    None => Body::from(data),
+                };
+                let stream = Part::stream(body).file_name(filename);
+                Either::Right(Either::Left(ready((stream, Some(len)))))
+            }
+            Read(read) => Either::Right(Either::Right(Either::Left(
+                read.into_part(filename, progress).map(|part| (part, None)),
+            ))),
+            Reopen(open) => {
+                let fut = async move {
+                    let body = match open().await {
+                        Ok(read) => {
+                            tracked_body(FramedRead::new(read, BytesDecoder), progress, None)
+                        }
+                        Err(err) => {
+                            // explicit type needed for `Bytes: From<?T>` in `wrap_stream`
+                            let err = Err::<Bytes, _>(err);
+                            Body::wrap_stream(stream::iter([err]))
+                        }
+                    };
+
+                    (Part::stream(body).file_name(filename), None)
+                };
+
+                Either::Right(Either::Right(Either::Right(fut)))
             }
-            Read(read) => Some(Either::Right(Either::Right(read.into_part(filename)))),
+        };
+
+        Some(part.map(|(part, len)| (part, name, len)))
+    }
+}
+
+#[cfg(feature = "file_cache")]
+impl InputFile {
+    /// Returns a SHA-256 hash of the name and the contents of this file, or
+    /// `None` if this file isn't uploaded or can't be read without consuming
+    /// it.
+    pub(crate) async fn content_hash(&self) -> Option<io::Result<[u8; 32]>> {
+        use sha2::{Digest, Sha256};
+        use tokio::io::AsyncReadExt;
+
+        let mut hasher = Sha256::new();
+        let name = match (&self.file_name, &self.inner) {
+            (Some(name), _) => name.as_bytes(),
+            (None, File(path)) => path.file_name().map_or(&[][..], |n| n.as_encoded_bytes()),
+            (None, _) => &[],
+        };
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name);
+
+        match &self.inner {
+            Bytes(data) => hasher.update(data),
+            File(path) => {
+                let res = async {
+                    let mut file = tokio::fs::File::open(path).await?;
+                    let mut buf = vec![0; 64 * 1024];
+                    loop {
+                        match file.read(&mut buf).await? {
+                            0 => break Ok(()),
+                            n => hasher.update(&buf[..n]),
+                        }
+                    }
+                };
+
+                if let Err(err) = res.await {
+                    return Some(Err(err));
+                }
+            }
+            Read(_) | Reopen(_) | Url(_) | FileId(_) => return None,
         }
+
+        Some(Ok(hasher.finalize().into()))
     }
 }
 
+/// Wraps `stream` into a [`Body`], reporting the streamed bytes to `progress`
+/// if it's set.
+fn tracked_body<S, E>(
+    stream: S,
+    progress: Option<Arc<watch::Sender<UploadProgress>>>,
+    total: Option<u64>,
+) -> Body
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Into<Box<dyn Error + Send + Sync>> + 'static,
+{
+    let Some(progress) = progress else {
+        return Body::wrap_stream(stream);
+    };
+
+    progress.send_replace(UploadProgress { sent: 0, total });
+
+    let mut sent = 0;
+    Body::wrap_stream(stream.inspect_ok(move |chunk| {
+        sent += chunk.len() as u64;
+        progress.send_replace(UploadProgress { sent, total });
+    }))
+}
+
 /// Adaptor for `AsyncRead` that allows clonning and converting to
 /// `multipart/form-data`
 #[derive(Clone)]
@@ -248,7 +511,11 @@ impl Read {
         Self { inner: it, buf: Arc::default(), notify: Arc::new(tx), wait: rx }
     }
 
-    pub(crate) async fn into_part(mut self, filename: Cow<'static, str>) -> Part {
+    pub(crate) async fn into_part(
+        mut self,
+        filename: Cow<'static, str>,
+        progress: Option<Arc<watch::Sender<UploadProgress>>>,
+    ) -> Part {
         if !self.inner.is_taken() {
             let res = ArcBox::<TakeCell<dyn AsyncRead + Send + Unpin>>::try_from(self.inner);
             match res {
@@ -257,7 +524,7 @@ impl Read {
                 Ok(arc_box) => {
                     let fr = FramedRead::new(ExclusiveArcAsyncRead(arc_box), BytesDecoder);
 
-                    let body = Body::wrap_stream(fr);
+                    let body = tracked_body(fr, progress, None);
                     return Part::stream(body).file_name(filename);
                 }
                 // move the arc back into `self`
@@ -267,12 +534,15 @@ impl Read {
 
         // Slow path: either wait until someone will read the whole `dyn AsyncRead` into
         // a buffer, or be the one who reads
-        let body = self.into_shared_body().await;
+        let body = self.into_shared_body(progress).await;
 
         Part::stream(body).file_name(filename)
     }
 
-    async fn into_shared_body(mut self) -> Body {
+    async fn into_shared_body(
+        mut self,
+        progress: Option<Arc<watch::Sender<UploadProgress>>>,
+    ) -> Body {
         match self.inner.take() {
             // Read `dyn AsyncRead` into a buffer
             Some(mut read_ref) => {
@@ -331,7 +601,9 @@ impl Read {
         // unwrap: `OnceCell` is initialized in the match above before sending
         // notification, so at this point it's already initialized.
         match buf.get().unwrap() {
-            Ok(_) => {
+            Ok(chunks) => {
+                let total = chunks.iter().map(|c| c.len() as u64).sum();
+
                 // We can't use `.iter()` here, because the iterator must capture `buf`
                 let mut i = 0;
                 let iter = iter::from_fn(move || match buf.get().unwrap() {
@@ -345,7 +617,7 @@ impl Read {
                     Err(_) => unreachable!(),
                 });
 
-                Body::wrap_stream(stream::iter(iter))
+                tracked_body(stream::iter(iter), progress, Some(total))
             }
 
             Err(err) => {
@@ -356,6 +628,98 @@ impl Read {
     }
 }
 
+/// State of [`InputFile::read_spooled`].
+struct Spool {
+    // `Shared` keeps the progress of spooling when the upload which started it
+    // is cancelled, the next upload continues where it stopped
+    spooled: Shared<BoxFuture<'static, Result<Arc<Spooled>, Arc<io::Error>>>>,
+}
+
+enum Spooled {
+    Memory(Bytes),
+    File(TempFile),
+}
+
+/// A temporary file, which is removed on drop.
+struct TempFile(PathBuf);
+
+impl Spool {
+    /// Opens the spooled copy, reading the original reader first if needed.
+    async fn open(self: Arc<Self>) -> io::Result<SpoolRead> {
+        let spooled = self.spooled.clone().await;
+        let read = match spooled.as_deref() {
+            Ok(Spooled::Memory(data)) => Either::Left(io::Cursor::new(data.clone())),
+            Ok(Spooled::File(TempFile(path))) => Either::Right(tokio::fs::File::open(path).await?),
+            Err(err) => return Err(io::Error::new(err.kind(), Arc::clone(err))),
+        };
+
+        Ok(SpoolRead { read, _spool: self })
+    }
+}
+
+/// Reads `read` to the end, into memory or into a temporary file if it's
+/// larger than `memory_limit`.
+async fn spool(
+    mut read: Box<dyn AsyncRead + Send + Unpin>,
+    memory_limit: usize,
+) -> Result<Arc<Spooled>, Arc<io::Error>> {
+    let mut buf = BytesMut::new();
+    loop {
+        // Read at most one byte over the limit to know if it was exceeded
+        let limit = (memory_limit + 1).saturating_sub(buf.len()) as u64;
+        buf.reserve(limit.min(64 * 1024) as usize);
+        let n = (&mut read).take(limit).read_buf(&mut buf).await?;
+        if n == 0 {
+            return Ok(Arc::new(Spooled::Memory(buf.freeze())));
+        }
+        if buf.len() > memory_limit {
+            break;
+        }
+    }
+
+    let name = format!("teloxide-{}", uuid::Uuid::new_v4().as_simple());
+    let path = std::env::temp_dir().join(name);
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // Other users shouldn't be able to read the spooled files
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&path).await?;
+    let temp = TempFile(path);
+    file.write_all(&buf).await?;
+    tokio::io::copy(&mut read, &mut file).await?;
+    file.flush().await?;
+
+    Ok(Arc::new(Spooled::File(temp)))
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Reader of a spooled copy, which keeps the copy alive.
+#[pin_project::pin_project]
+struct SpoolRead {
+    #[pin]
+    read: Either<io::Cursor<Bytes>, tokio::fs::File>,
+    _spool: Arc<Spool>,
+}
+
+impl AsyncRead for SpoolRead {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> task::Poll<io::Result<()>> {
+        match self.project().read.as_pin_mut() {
+            Either::Left(read) => read.poll_read(cx, buf),
+            Either::Right(read) => read.poll_read(cx, buf),
+        }
+    }
+}
+
 /// Wrapper over an `ArcBox` that implements `AsyncRead`.
 struct ExclusiveArcAsyncRead(ArcBox<TakeCell<dyn AsyncRead + Send + Unpin>>);
 
@@ -427,3 +791,115 @@ impl InputFileLike for InputSticker {
         self.sticker.move_into(into)
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use futures::future::ready;
+
+    use crate::{
+        net::Download,
+        prelude::*,
+        testing::{ChatBuilder, FakeServer},
+        types::{ChatId, InputFile, Message, UploadProgress},
+    };
+
+    async fn sent_data(bot: &Bot, message: &Message) -> Vec<u8> {
+        let file = bot.get_file(message.document().unwrap().file.id.clone()).await.unwrap();
+        let mut data = Vec::new();
+        bot.download_file(&file.path, &mut data).await.unwrap();
+        data
+    }
+
+    #[tokio::test]
+    async fn rewindable() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        server.add_chat(ChatBuilder::group(ChatId(-1), "Files").build());
+
+        let opened = Arc::new(AtomicUsize::new(0));
+        let read_with = InputFile::read_with({
+            let opened = Arc::clone(&opened);
+            move || {
+                opened.fetch_add(1, Ordering::Relaxed);
+                ready(Ok(std::io::Cursor::new("reopened")))
+            }
+        });
+        let in_memory = InputFile::read_spooled(std::io::Cursor::new("in memory"), 16);
+        let in_file = InputFile::read_spooled(std::io::Cursor::new("in a temporary file"), 16);
+
+        for (file, expected) in [
+            (read_with, &b"reopened"[..]),
+            (in_memory, b"in memory"),
+            (in_file, b"in a temporary file"),
+        ] {
+            let request = bot.send_document(ChatId(-1), file);
+            let first = request.send_ref().await.unwrap();
+            let second = request.send().await.unwrap();
+
+            assert_eq!(sent_data(&bot, &first).await, expected);
+            assert_eq!(sent_data(&bot, &second).await, expected);
+        }
+
+        assert_eq!(opened.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn cancelled_spooling_continues() {
+        use std::time::Duration;
+        use tokio::io::AsyncWriteExt;
+
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        server.add_chat(ChatBuilder::group(ChatId(-1), "Files").build());
+
+        let (mut tx, rx) = tokio::io::duplex(64);
+        let request = bot.send_document(ChatId(-1), InputFile::read_spooled(rx, 4));
+
+        tx.write_all(b"first ").await.unwrap();
+        // Cancelled while it waits for the rest of the reader
+        let first = tokio::time::timeout(Duration::from_millis(50), request.send_ref()).await;
+        assert!(first.is_err());
+
+        tx.write_all(b"second").await.unwrap();
+        drop(tx);
+        let message = request.send().await.unwrap();
+        assert_eq!(sent_data(&bot, &message).await, b"first second");
+    }
+
+    #[tokio::test]
+    async fn progress() {
+        let server = FakeServer::start().await.unwrap();
+        let bot = server.bot();
+        server.add_chat(ChatBuilder::group(ChatId(-1), "Files").build());
+
+        let data = vec![0; 200 * 1024];
+        let memory = InputFile::memory(data.clone());
+        let read = InputFile::read(std::io::Cursor::new(data));
+        let file = InputFile::file("../../media/teloxide-core-logo.png");
+        let file_size = std::fs::metadata("../../media/teloxide-core-logo.png").unwrap().len();
+
+        let memory_progress = memory.progress();
+        let read_progress = read.progress();
+        let file_progress = file.progress();
+
+        bot.send_document(ChatId(-1), memory).await.unwrap();
+        bot.send_document(ChatId(-1), read).await.unwrap();
+        bot.send_document(ChatId(-1), file).await.unwrap();
+
+        assert_eq!(
+            *memory_progress.borrow(),
+            UploadProgress { sent: 200 * 1024, total: Some(200 * 1024) }
+        );
+        assert_eq!(*read_progress.borrow(), UploadProgress { sent: 200 * 1024, total: None });
+        assert_eq!(
+            *file_progress.borrow(),
+            UploadProgress { sent: file_size, total: Some(file_size) }
+        );
+        assert_eq!(file_progress.borrow().fraction(), Some(1.0));
+    }
+}
@@ -45,6 +45,7 @@ pub struct DispatcherBuilder<R, Err, Key> {
     ctrlc_handler: bool,
     distribution_f: fn(&Update) -> Option<Key>,
     worker_queue_size: usize,
+    update_inspectors: Vec<UpdateInspector>,
 }
 
 impl<R, Err, Key> DispatcherBuilder<R, Err, Key>
@@ -105,6 +106,39 @@ where
         Self { worker_queue_size: size, ..self }
     }
 
+    /// Invalidates responses cached by a [`TtlCache`] when updates about chat
+    /// members arrive.
+    ///
+    /// Every update is passed to [`Invalidator::invalidate_update`] before
+    /// it's handled, so that handlers don't get outdated chat members or
+    /// administrators from the cache.
+    ///
+    /// ## Examples
+    ///
+    /// ```no_run
+    /// use teloxide::{dispatching::Dispatcher, dptree, requests::RequesterExt, Bot};
+    ///
+    /// # async {
+    /// let bot = Bot::from_env().ttl_cache();
+    /// let handler = dptree::entry() /* ... */;
+    /// let invalidator = bot.invalidator();
+    /// let mut dp = Dispatcher::builder(bot, handler).invalidate_ttl_cache(invalidator).build();
+    /// # let _: &mut Dispatcher<_, (), _> = &mut dp;
+    /// # };
+    /// ```
+    ///
+    /// [`TtlCache`]: crate::adaptors::TtlCache
+    /// [`Invalidator::invalidate_update`]: crate::adaptors::ttl_cache::Invalidator::invalidate_update
+    #[cfg(feature = "ttl-cache")]
+    #[must_use]
+    pub fn invalidate_ttl_cache(
+        mut self,
+        invalidator: crate::adaptors::ttl_cache::Invalidator,
+    ) -> Self {
+        self.update_inspectors.push(Arc::new(move |update| invalidator.invalidate_update(update)));
+        self
+    }
+
     /// Specifies the stack size available to the dispatcher.
     ///
     /// By default, it's 8 * 1024 * 1024 bytes (8 MiB).
@@ -186,6 +220,7 @@ where
             ctrlc_handler,
             distribution_f: _,
             worker_queue_size,
+            update_inspectors,
         } = self;
 
         DispatcherBuilder {
@@ -197,6 +232,7 @@ where
             ctrlc_handler,
             distribution_f: f,
             worker_queue_size,
+            update_inspectors,
         }
     }
 
@@ -216,6 +252,7 @@ where
             distribution_f,
             worker_queue_size,
             ctrlc_handler,
+            update_inspectors,
         } = self;
 
         dptree::type_check(
@@ -240,6 +277,7 @@ where
             state: ShutdownToken::new(),
             distribution_f,
             worker_queue_size,
+            update_inspectors,
             workers: HashMap::new(),
             default_worker: None,
             current_number_of_active_workers: Default::default(),
@@ -280,6 +318,8 @@ pub struct Dispatcher<R, Err, Key> {
 
     distribution_f: fn(&Update) -> Option<Key>,
     worker_queue_size: usize,
+    // Called for every update before it's passed to a worker
+    update_inspectors: Vec<UpdateInspector>,
     current_number_of_active_workers: Arc<AtomicU32>,
     max_number_of_active_workers: Arc<AtomicU32>,
     // Tokio TX channel parts associated with chat IDs that consume updates sequentially.
@@ -306,6 +346,8 @@ pub type UpdateHandler<Err> = dptree::Handler<'static, Result<(), Err>, DpHandle
 
 type DefaultHandler = Arc<dyn Fn(Arc<Update>) -> BoxFuture<'static, ()> + Send + Sync>;
 
+type UpdateInspector = Arc<dyn Fn(&Update) + Send + Sync>;
+
 impl<R, Err> Dispatcher<R, Err, DefaultKey>
 where
     R: Requester + Clone + Send + Sync + 'static,
@@ -331,6 +373,7 @@ where
             ctrlc_handler: false,
             worker_queue_size: DEFAULT_WORKER_QUEUE_SIZE,
             distribution_f: default_distribution_function,
+            update_inspectors: Vec::new(),
         }
     }
 }
@@ -488,6 +531,10 @@ where
                     return;
                 }
 
+                for inspect in &self.update_inspectors {
+                    inspect(&upd);
+                }
+
                 let worker = match (self.distribution_f)(&upd) {
                     Some(key) => self.workers.entry(key).or_insert_with(|| {
                         let deps = self.dependencies.clone();
@@ -8,6 +8,15 @@ use dptree::{
 use std::{collections::BTreeSet, sync::Arc};
 use tracing::{Instrument, Span};
 
+/// Extension methods for instrumenting an [`UpdateHandler`] with [`tracing`]
+/// spans.
+///
+/// Requests sent by a handler inside of such a span are nested under it if
+/// the bot is wrapped into the `TraceSpans` adaptor (see the `trace-spans`
+/// feature), so an update, its handler and all API calls show up as one
+/// trace.
+///
+/// [`tracing`]: ::tracing
 pub trait UpdateHandlerTracingExt<E> {
     /// Returns an `UpdateHandler` wrapped in an async span.
     fn instrument_with_async<F, FnArgs>(self, f: F) -> Self
@@ -0,0 +1,74 @@
+use std::{fmt::Debug, time::Duration};
+
+use teloxide::{
+    dispatching::UpdateHandler,
+    prelude::*,
+    testing::{FakeServer, MessageBuilder, UserBuilder},
+    types::{UpdateKind, UserId},
+    update_listeners::{Polling, UpdateListener},
+};
+
+type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+fn echo_handler() -> UpdateHandler<HandlerError> {
+    Update::filter_message().endpoint(|bot: Bot, msg: Message| async move {
+        bot.send_message(msg.chat.id, msg.text().unwrap_or_default().to_uppercase()).await?;
+        Ok(())
+    })
+}
+
+/// Sends a message to the bot and checks that it replies.
+async fn echo<L>(server: &FakeServer, listener: L)
+where
+    L: UpdateListener + Send,
+    L::Err: Debug,
+{
+    let mut dispatcher = Dispatcher::builder(server.bot(), echo_handler()).build();
+    let shutdown_token = dispatcher.shutdown_token();
+
+    let user = UserBuilder::new(UserId(7)).build();
+    server.push_update(UpdateKind::Message(MessageBuilder::text("hello").from(user).build()));
+
+    let check = async {
+        let replied = async {
+            while server.messages(ChatId(7)).len() < 2 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), replied).await.expect("bot didn't reply");
+
+        shutdown_token.shutdown().unwrap().await;
+    };
+
+    tokio::join!(dispatcher.dispatch_with_listener(listener, LoggingErrorHandler::new()), check);
+
+    let messages = server.messages(ChatId(7));
+    assert_eq!(messages[1].text(), Some("HELLO"));
+    assert_eq!(messages[1].from.as_ref().unwrap().id, server.me().id);
+    assert!(server.pending_updates().is_empty());
+}
+
+#[tokio::test]
+async fn polling() {
+    let server = FakeServer::start().await.unwrap();
+    let listener = Polling::builder(server.bot()).timeout(Duration::from_secs(1)).build();
+
+    echo(&server, listener).await;
+}
+
+#[cfg(feature = "webhooks-axum")]
+#[tokio::test]
+async fn webhook() {
+    use teloxide::update_listeners::webhooks;
+
+    let server = FakeServer::start().await.unwrap();
+
+    let address = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let url = format!("http://{address}/webhook").parse().unwrap();
+    let listener =
+        webhooks::axum(server.bot(), webhooks::Options::new(address, url)).await.unwrap();
+    assert!(server.webhook().is_some());
+
+    echo(&server, listener).await;
+    assert_eq!(server.webhook(), None);
+}
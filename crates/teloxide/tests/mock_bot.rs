@@ -0,0 +1,112 @@
+use std::convert::Infallible;
+
+use futures::stream;
+use teloxide::{
+    dispatching::{dialogue::InMemStorage, UpdateHandler},
+    payloads::SendMessage,
+    prelude::*,
+    stop::{mk_stop_token, StopToken},
+    testing::{MessageBuilder, MockBot, UpdateBuilder, UserBuilder},
+    update_listeners::StatefulListener,
+    utils::command::BotCommands,
+};
+
+#[derive(BotCommands, Clone)]
+#[command(rename_rule = "lowercase")]
+enum Command {
+    Start,
+}
+
+#[derive(Clone, Default)]
+enum State {
+    #[default]
+    Start,
+    ReceiveName,
+}
+
+type MyDialogue = Dialogue<State, InMemStorage<State>>;
+type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+type HandlerResult = Result<(), HandlerError>;
+
+fn message_update(chat_id: u64, text: &str) -> Update {
+    let user = UserBuilder::new(UserId(chat_id)).build();
+    UpdateBuilder::message(MessageBuilder::text(text).from(user).build()).build()
+}
+
+async fn dispatch(
+    bot: MockBot,
+    handler: UpdateHandler<HandlerError>,
+    deps: DependencyMap,
+    updates: Vec<Update>,
+) {
+    let (stop_token, _stop_flag) = mk_stop_token();
+    let listener = StatefulListener::new(
+        (updates, stop_token),
+        |(updates, _): &mut (Vec<Update>, StopToken)| {
+            stream::iter(std::mem::take(updates).into_iter().map(Ok::<_, Infallible>))
+        },
+        |(_, token): &mut (Vec<Update>, StopToken)| token.clone(),
+    );
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(deps)
+        .build()
+        .dispatch_with_listener(listener, LoggingErrorHandler::new())
+        .await;
+}
+
+#[tokio::test]
+async fn dispatcher_with_commands() {
+    let bot = MockBot::new();
+    let handler = Update::filter_message().filter_command::<Command>().endpoint(
+        |bot: MockBot, msg: Message, cmd: Command| async move {
+            match cmd {
+                Command::Start => bot.send_message(msg.chat.id, "Hello!").await?,
+            };
+            Ok(())
+        },
+    );
+
+    let updates = vec![
+        message_update(10, "/start"),
+        message_update(10, "not a command"),
+        message_update(20, "/start@mock_bot"),
+        message_update(20, "/start@another_bot"),
+    ];
+    dispatch(bot.clone(), handler, DependencyMap::new(), updates).await;
+
+    let mut sent = bot.requests_of::<SendMessage>();
+    sent.sort_by_key(|m| m.chat_id.to_string());
+    let sent = sent.into_iter().map(|m| (m.chat_id, m.text)).collect::<Vec<_>>();
+    assert_eq!(
+        sent,
+        [(ChatId(10).into(), "Hello!".to_owned()), (ChatId(20).into(), "Hello!".to_owned())]
+    );
+}
+
+#[tokio::test]
+async fn dispatcher_with_dialogue() {
+    async fn start(bot: MockBot, dialogue: MyDialogue, msg: Message) -> HandlerResult {
+        bot.send_message(msg.chat.id, "What's your name?").await?;
+        dialogue.update(State::ReceiveName).await?;
+        Ok(())
+    }
+
+    async fn receive_name(bot: MockBot, dialogue: MyDialogue, msg: Message) -> HandlerResult {
+        bot.send_message(msg.chat.id, format!("Hi, {}!", msg.text().unwrap())).await?;
+        dialogue.exit().await?;
+        Ok(())
+    }
+
+    let bot = MockBot::new();
+    let handler = Update::filter_message()
+        .enter_dialogue::<Message, InMemStorage<State>, State>()
+        .branch(dptree::case![State::Start].endpoint(start))
+        .branch(dptree::case![State::ReceiveName].endpoint(receive_name));
+
+    let updates = vec![message_update(10, "hey"), message_update(10, "Ferris")];
+    dispatch(bot.clone(), handler, dptree::deps![InMemStorage::<State>::new()], updates).await;
+
+    let sent = bot.requests_of::<SendMessage>().into_iter().map(|m| m.text).collect::<Vec<_>>();
+    assert_eq!(sent, ["What's your name?", "Hi, Ferris!"]);
+}